@@ -0,0 +1,2 @@
+-- Add up migration script here
+ALTER TABLE listings ADD COLUMN IF NOT EXISTS expired_at timestamp(6) WITH time zone DEFAULT NULL;
@@ -0,0 +1,21 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS failed_events (
+  id UUID PRIMARY KEY NOT NULL,
+  marketplace VARCHAR(30) NOT NULL,
+  contract_address VARCHAR(66) NOT NULL,
+  tx_id VARCHAR(66) NOT NULL,
+  tx_version BIGINT NOT NULL,
+  event_index BIGINT NOT NULL,
+  event_type VARCHAR NOT NULL,
+  step VARCHAR(30) NOT NULL,
+  error TEXT NOT NULL,
+  raw_event JSONB NOT NULL,
+  status VARCHAR(30) DEFAULT 'pending' NOT NULL,
+  attempts INT DEFAULT 1 NOT NULL,
+  block_time timestamp(6) WITH time zone NOT NULL,
+  block_height BIGINT NOT NULL,
+  created_at timestamp(6) WITH time zone DEFAULT NOW() NOT NULL,
+  updated_at timestamp(6) WITH time zone DEFAULT NOW() NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS failed_events_marketplace_status_idx ON failed_events (marketplace, status, tx_version);
@@ -0,0 +1,15 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS marketplace_events (
+  contract_address VARCHAR(66) NOT NULL,
+  tx_version BIGINT NOT NULL,
+  event_index BIGINT NOT NULL,
+  tx_id VARCHAR(66) NOT NULL,
+  account_address VARCHAR(66) NOT NULL,
+  creation_number BIGINT NOT NULL,
+  sequence_number BIGINT NOT NULL,
+  type_str VARCHAR NOT NULL,
+  data JSONB NOT NULL,
+  block_time timestamp(6) WITH time zone NOT NULL,
+  block_height BIGINT NOT NULL,
+  PRIMARY KEY (contract_address, tx_version, event_index)
+);
@@ -0,0 +1,2 @@
+-- Add up migration script here
+ALTER TABLE bids ADD COLUMN IF NOT EXISTS tx_index BIGINT DEFAULT NULL;
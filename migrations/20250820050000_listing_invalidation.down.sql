@@ -0,0 +1,2 @@
+-- Add down migration script here
+ALTER TABLE listings DROP COLUMN IF EXISTS invalid_reason;
@@ -0,0 +1,2 @@
+-- Add up migration script here
+ALTER TABLE listings ADD COLUMN IF NOT EXISTS invalid_reason VARCHAR(32) DEFAULT NULL;
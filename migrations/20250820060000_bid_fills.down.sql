@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS bid_fills;
+
+ALTER TABLE bids DROP COLUMN IF EXISTS amount;
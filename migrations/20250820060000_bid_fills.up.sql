@@ -0,0 +1,18 @@
+-- Add up migration script here
+ALTER TABLE bids ADD COLUMN IF NOT EXISTS amount BIGINT DEFAULT NULL;
+
+UPDATE bids SET amount = remaining_count WHERE amount IS NULL;
+
+CREATE TABLE IF NOT EXISTS bid_fills (
+  activity_id UUID PRIMARY KEY NOT NULL,
+  bid_id UUID NOT NULL,
+  tx_id VARCHAR(66) NOT NULL,
+  tx_index BIGINT NOT NULL,
+  nft_id UUID DEFAULT NULL,
+  seller VARCHAR(66) DEFAULT NULL,
+  price BIGINT DEFAULT NULL,
+  amount BIGINT NOT NULL,
+  block_time timestamp(6) WITH time zone NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS bid_fills_bid_id_idx ON bid_fills (bid_id);
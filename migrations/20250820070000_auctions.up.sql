@@ -0,0 +1,29 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS auctions (
+  id UUID PRIMARY KEY NOT NULL,
+  market_contract_id VARCHAR(66) DEFAULT NULL,
+  market_name VARCHAR(128) DEFAULT NULL,
+  collection_id UUID DEFAULT NULL,
+  nft_id UUID DEFAULT NULL,
+  nonce VARCHAR(128) DEFAULT NULL,
+  seller VARCHAR(66) DEFAULT NULL,
+  reserve_price BIGINT DEFAULT NULL,
+  highest_bid BIGINT DEFAULT NULL,
+  highest_bidder VARCHAR(66) DEFAULT NULL,
+  start_time timestamp(6) WITH time zone DEFAULT NULL,
+  end_time timestamp(6) WITH time zone DEFAULT NULL,
+  status VARCHAR(20) DEFAULT NULL,
+  created_tx_id VARCHAR(66) DEFAULT NULL,
+  settled_tx_id VARCHAR(66) DEFAULT NULL,
+  cancelled_tx_id VARCHAR(66) DEFAULT NULL,
+  tx_index BIGINT DEFAULT NULL,
+  block_time timestamp(6) WITH time zone DEFAULT NULL,
+  updated_at timestamp(6) WITH time zone DEFAULT NOW() NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS auctions_collection_id_status_end_time_idx ON auctions (collection_id, status, end_time);
+
+CREATE TRIGGER auction_before_insert_add_collection_id
+    BEFORE INSERT OR UPDATE ON auctions
+    FOR EACH ROW
+    EXECUTE FUNCTION add_collection_id ();
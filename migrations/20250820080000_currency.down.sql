@@ -0,0 +1,42 @@
+-- Add down migration script here
+CREATE OR REPLACE FUNCTION update_collection_listings ()
+    RETURNS TRIGGER
+AS $$
+BEGIN
+    WITH
+        listings AS (
+            SELECT
+                listings.collection_id,
+                MIN(listings.price)         AS floor,
+                COUNT(*)                    AS total
+            FROM listings
+            WHERE listings.listed AND listings.collection_id = NEW.collection_id
+            GROUP BY listings.collection_id
+            UNION
+            SELECT
+                NEW.collection_id,
+                NULL,
+                0
+        )
+    INSERT INTO collections (id, slug, floor)
+    SELECT
+        listings.collection_id,
+        listings.collection_id,
+        listings.floor
+    FROM listings
+    LIMIT 1
+    ON CONFLICT (id)
+        DO UPDATE SET
+            floor = EXCLUDED.floor;
+    RETURN NEW;
+END;
+$$
+LANGUAGE plpgsql;
+
+ALTER TABLE auctions DROP COLUMN IF EXISTS currency;
+
+ALTER TABLE bids DROP COLUMN IF EXISTS currency;
+
+ALTER TABLE listings DROP COLUMN IF EXISTS currency;
+
+ALTER TABLE activities DROP COLUMN IF EXISTS currency;
@@ -0,0 +1,50 @@
+-- Add up migration script here
+ALTER TABLE activities ADD COLUMN IF NOT EXISTS currency VARCHAR(256) DEFAULT NULL;
+
+ALTER TABLE listings ADD COLUMN IF NOT EXISTS currency VARCHAR(256) DEFAULT NULL;
+
+ALTER TABLE bids ADD COLUMN IF NOT EXISTS currency VARCHAR(256) DEFAULT NULL;
+
+ALTER TABLE auctions ADD COLUMN IF NOT EXISTS currency VARCHAR(256) DEFAULT NULL;
+
+-- Rows without a currency were indexed before it was tracked and are priced in APT,
+-- the floor only compares listings priced in APT. The APT metadata address is
+-- APT_TOKEN_ADDR in currency_config.rs
+CREATE OR REPLACE FUNCTION update_collection_listings ()
+    RETURNS TRIGGER
+AS $$
+BEGIN
+    WITH
+        listings AS (
+            SELECT
+                listings.collection_id,
+                MIN(listings.price)         AS floor,
+                COUNT(*)                    AS total
+            FROM listings
+            WHERE listings.listed
+                AND listings.collection_id = NEW.collection_id
+                AND (
+                    listings.currency IS NULL
+                    OR listings.currency = '0x000000000000000000000000000000000000000000000000000000000000000a'
+                )
+            GROUP BY listings.collection_id
+            UNION
+            SELECT
+                NEW.collection_id,
+                NULL,
+                0
+        )
+    INSERT INTO collections (id, slug, floor)
+    SELECT
+        listings.collection_id,
+        listings.collection_id,
+        listings.floor
+    FROM listings
+    LIMIT 1
+    ON CONFLICT (id)
+        DO UPDATE SET
+            floor = EXCLUDED.floor;
+    RETURN NEW;
+END;
+$$
+LANGUAGE plpgsql;
@@ -0,0 +1,6 @@
+-- Add down migration script here
+ALTER TABLE activities DROP COLUMN IF EXISTS royalty_amount;
+
+ALTER TABLE activities DROP COLUMN IF EXISTS marketplace_fee;
+
+ALTER TABLE activities DROP COLUMN IF EXISTS seller_proceeds;
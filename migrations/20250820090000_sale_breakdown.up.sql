@@ -0,0 +1,6 @@
+-- Add up migration script here
+ALTER TABLE activities ADD COLUMN IF NOT EXISTS seller_proceeds BIGINT DEFAULT NULL;
+
+ALTER TABLE activities ADD COLUMN IF NOT EXISTS marketplace_fee BIGINT DEFAULT NULL;
+
+ALTER TABLE activities ADD COLUMN IF NOT EXISTS royalty_amount BIGINT DEFAULT NULL;
@@ -0,0 +1,4 @@
+-- Add down migration script here
+ALTER TABLE bids DROP COLUMN IF EXISTS attr_value;
+
+ALTER TABLE bids DROP COLUMN IF EXISTS attr_type;
@@ -0,0 +1,4 @@
+-- Add up migration script here
+ALTER TABLE bids ADD COLUMN IF NOT EXISTS attr_type VARCHAR DEFAULT NULL;
+
+ALTER TABLE bids ADD COLUMN IF NOT EXISTS attr_value VARCHAR DEFAULT NULL;
@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS activities_nft_id_tx_index_idx;
+
+DROP TABLE IF EXISTS nft_ownerships;
@@ -0,0 +1,53 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS nft_ownerships (
+  nft_id UUID NOT NULL,
+  tx_index BIGINT NOT NULL,
+  collection_id UUID DEFAULT NULL,
+  owner VARCHAR(66) NOT NULL,
+  tx_id VARCHAR(66) NOT NULL,
+  activity_id UUID DEFAULT NULL,
+  from_version BIGINT NOT NULL,
+  from_time timestamp(6) WITH time zone NOT NULL,
+  to_version BIGINT DEFAULT NULL,
+  to_time timestamp(6) WITH time zone DEFAULT NULL,
+  PRIMARY KEY (nft_id, tx_index)
+);
+
+CREATE INDEX IF NOT EXISTS nft_ownerships_owner_idx ON nft_ownerships (owner);
+
+CREATE INDEX IF NOT EXISTS activities_nft_id_tx_index_idx ON activities (nft_id, tx_index);
+
+-- Seeded from the indexed mints and transfers, v1 transfers only show up once their
+-- transactions are indexed again
+INSERT INTO nft_ownerships (nft_id, tx_index, collection_id, owner, tx_id, activity_id, from_version, from_time)
+SELECT nft_id, tx_index, collection_id, receiver, tx_id, id, tx_index / 100000, block_time
+FROM (
+  SELECT
+    a.*,
+    LAG(a.receiver) OVER (PARTITION BY a.nft_id ORDER BY a.tx_index) AS previous_receiver
+  FROM activities a
+  WHERE a.nft_id IS NOT NULL AND a.receiver IS NOT NULL AND a.tx_type IN ('mint', 'transfer')
+) a
+WHERE a.previous_receiver IS DISTINCT FROM a.receiver
+ON CONFLICT (nft_id, tx_index) DO NOTHING;
+
+UPDATE nft_ownerships o
+SET
+  to_version = c.to_tx_index / 100000,
+  to_time = c.to_time
+FROM (
+  SELECT
+    o.nft_id,
+    o.tx_index,
+    COALESCE(LEAD(o.tx_index) OVER w, burn.tx_index) AS to_tx_index,
+    COALESCE(LEAD(o.from_time) OVER w, burn.block_time) AS to_time
+  FROM nft_ownerships o
+    LEFT JOIN LATERAL (
+      SELECT a.tx_index, a.block_time FROM activities a
+      WHERE a.nft_id = o.nft_id AND a.tx_type = 'burn' AND a.tx_index > o.tx_index
+      ORDER BY a.tx_index
+      LIMIT 1
+    ) burn ON TRUE
+  WINDOW w AS (PARTITION BY o.nft_id ORDER BY o.tx_index)
+) c
+WHERE o.nft_id = c.nft_id AND o.tx_index = c.tx_index;
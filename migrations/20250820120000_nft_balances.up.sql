@@ -0,0 +1,24 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS nft_balances (
+  nft_id UUID NOT NULL,
+  owner VARCHAR(66) NOT NULL,
+  collection_id UUID DEFAULT NULL,
+  amount BIGINT NOT NULL DEFAULT 0,
+  last_version BIGINT NOT NULL DEFAULT 0,
+  updated_at timestamp(6) WITH time zone DEFAULT NOW() NOT NULL,
+  PRIMARY KEY (nft_id, owner)
+);
+
+CREATE INDEX IF NOT EXISTS nft_balances_owner_idx ON nft_balances (owner);
+
+CREATE INDEX IF NOT EXISTS nft_balances_collection_id_idx ON nft_balances (collection_id);
+
+-- Seeded with one edition for the current owner of every nft. The seed covers the versions
+-- the token processor already indexed, so indexing them again doesn't add to it and only
+-- fills in the other owners of v1 editions.
+INSERT INTO nft_balances (nft_id, owner, collection_id, amount, last_version)
+SELECT n.id, n.owner, n.collection_id, 1, COALESCE(ps.last_success_version, 0)
+FROM nfts n
+  LEFT JOIN processor_status ps ON ps.processor = 'token'
+WHERE n.owner IS NOT NULL AND (n.burned IS NULL OR NOT n.burned)
+ON CONFLICT (nft_id, owner) DO NOTHING;
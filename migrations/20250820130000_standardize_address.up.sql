@@ -0,0 +1,7 @@
+-- Add up migration script here
+-- Same padding as standardize_address of the indexer sdk, for addresses stored as emitted
+CREATE OR REPLACE FUNCTION standardize_address(address TEXT)
+RETURNS TEXT AS $$
+    SELECT '0x' || LPAD(hex, GREATEST(LENGTH(hex), 64), '0')
+    FROM (SELECT REGEXP_REPLACE(address, '^0x', '') AS hex) a
+$$ LANGUAGE SQL IMMUTABLE STRICT;
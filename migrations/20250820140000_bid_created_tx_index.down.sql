@@ -0,0 +1,2 @@
+-- Add down migration script here
+ALTER TABLE bids DROP COLUMN IF EXISTS created_tx_index;
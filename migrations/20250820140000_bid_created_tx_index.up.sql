@@ -0,0 +1,3 @@
+-- Add up migration script here
+-- Bids created before this column count every fill of their id, as they did so far
+ALTER TABLE bids ADD COLUMN IF NOT EXISTS created_tx_index BIGINT DEFAULT NULL;
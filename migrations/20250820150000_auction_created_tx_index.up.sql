@@ -0,0 +1,2 @@
+-- Add up migration script here
+ALTER TABLE auctions ADD COLUMN IF NOT EXISTS created_tx_index BIGINT DEFAULT NULL;
@@ -0,0 +1,47 @@
+-- Add down migration script here
+CREATE OR REPLACE FUNCTION update_collection_sales ()
+    RETURNS TRIGGER
+    AS $$
+BEGIN
+    IF NEW.collection_id IS NULL THEN
+        SELECT nfts.collection_id FROM nfts
+        WHERE nfts.id = NEW.nft_id
+        INTO NEW.collection_id;
+    END IF;
+
+    IF NEW.tx_type = 'buy' OR NEW.tx_type = 'accept-bid' OR NEW.tx_type = 'accept-collection-bid' THEN
+        WITH
+            sales AS (
+                SELECT
+                    activities.collection_id,
+                    SUM(activities.price)           AS volume,
+                    SUM(activities.usd_price)       AS volume_usd
+                FROM activities
+                WHERE activities.tx_type IN ('buy', 'accept-bid', 'accept-collection-bid')
+                    AND activities.collection_id = NEW.collection_id
+                GROUP BY activities.collection_id
+                UNION
+                SELECT
+                    NEW.collection_id,
+                    NEW.price,
+                    NEW.usd_price,
+                    1
+            )
+        INSERT INTO collections (id, slug, volume, volume_usd)
+        SELECT
+            sales.collection_id,
+            sales.collection_id,
+            SUM(sales.volume),
+            SUM(sales.volume_usd)
+        FROM sales
+        GROUP BY sales.collection_id
+        ON CONFLICT (id)
+            DO UPDATE SET
+                volume = EXCLUDED.volume,
+                volume_usd = EXCLUDED.volume_usd;
+    END IF;
+
+    RETURN NEW;
+END;
+$$
+LANGUAGE plpgsql;
@@ -0,0 +1,70 @@
+-- Add up migration script here
+-- The collection volume only sums sales priced in APT, like the floor, while the USD volume
+-- sums every currency. The APT metadata address is APT_TOKEN_ADDR in currency_config.rs
+CREATE OR REPLACE FUNCTION update_collection_sales ()
+    RETURNS TRIGGER
+    AS $$
+BEGIN
+    IF NEW.collection_id IS NULL THEN
+        SELECT nfts.collection_id FROM nfts
+        WHERE nfts.id = NEW.nft_id
+        INTO NEW.collection_id;
+    END IF;
+
+    IF NEW.tx_type = 'buy' OR NEW.tx_type = 'accept-bid' OR NEW.tx_type = 'accept-collection-bid' THEN
+        WITH
+            sales AS (
+                SELECT
+                    activities.collection_id,
+                    SUM(activities.price) FILTER (
+                        WHERE activities.currency IS NULL
+                            OR activities.currency = '0x000000000000000000000000000000000000000000000000000000000000000a'
+                    )                               AS volume,
+                    SUM(activities.usd_price)       AS volume_usd
+                FROM activities
+                WHERE activities.tx_type IN ('buy', 'accept-bid', 'accept-collection-bid')
+                    AND activities.collection_id = NEW.collection_id
+                GROUP BY activities.collection_id
+                UNION ALL
+                SELECT
+                    NEW.collection_id,
+                    CASE
+                        WHEN NEW.currency IS NULL
+                            OR NEW.currency = '0x000000000000000000000000000000000000000000000000000000000000000a'
+                        THEN NEW.price
+                    END,
+                    NEW.usd_price
+            )
+        INSERT INTO collections (id, slug, volume, volume_usd)
+        SELECT
+            sales.collection_id,
+            sales.collection_id,
+            SUM(sales.volume),
+            SUM(sales.volume_usd)
+        FROM sales
+        GROUP BY sales.collection_id
+        ON CONFLICT (id)
+            DO UPDATE SET
+                volume = EXCLUDED.volume,
+                volume_usd = EXCLUDED.volume_usd;
+    END IF;
+
+    RETURN NEW;
+END;
+$$
+LANGUAGE plpgsql;
+
+UPDATE collections
+SET volume = COALESCE(sales.volume, 0)
+FROM (
+    SELECT
+        activities.collection_id,
+        SUM(activities.price) FILTER (
+            WHERE activities.currency IS NULL
+                OR activities.currency = '0x000000000000000000000000000000000000000000000000000000000000000a'
+        ) AS volume
+    FROM activities
+    WHERE activities.tx_type IN ('buy', 'accept-bid', 'accept-collection-bid')
+    GROUP BY activities.collection_id
+) sales
+WHERE collections.id = sales.collection_id;
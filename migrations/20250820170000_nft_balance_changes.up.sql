@@ -0,0 +1,20 @@
+-- Add up migration script here
+-- Every v1 deposit and withdrawal an owner's editions moved by, balances are summed from it
+CREATE TABLE IF NOT EXISTS nft_balance_changes (
+  nft_id UUID NOT NULL,
+  owner VARCHAR(66) NOT NULL,
+  tx_index BIGINT NOT NULL,
+  collection_id UUID DEFAULT NULL,
+  amount BIGINT NOT NULL,
+  seeded BOOLEAN NOT NULL DEFAULT FALSE,
+  PRIMARY KEY (nft_id, owner, tx_index)
+);
+
+-- The current balances carry over as seeded changes at the end of the last indexed version.
+-- They count until the changes up to that version are indexed again, which replace them.
+INSERT INTO nft_balance_changes (nft_id, owner, tx_index, collection_id, amount, seeded)
+SELECT b.nft_id, b.owner, (COALESCE(ps.last_success_version, 0) + 1) * 100000 - 1, b.collection_id, b.amount, TRUE
+FROM nft_balances b
+  LEFT JOIN processor_status ps ON ps.processor = 'token'
+WHERE b.amount > 0
+ON CONFLICT (nft_id, owner, tx_index) DO NOTHING;
@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+
+use aptos_indexer_processor_sdk::utils::convert::standardize_address;
+use bigdecimal::BigDecimal;
+use serde::{Deserialize, Serialize};
+
+/// Metadata address of APT, also hardcoded in the collection floor and volume triggers
+/// (migrations `20250820080000_currency` and `20250820160000_apt_collection_volume`)
+pub const APT_TOKEN_ADDR: &str =
+    "0x000000000000000000000000000000000000000000000000000000000000000a";
+pub const APT_COIN_TYPE: &str = "0x1::aptos_coin::AptosCoin";
+
+/// A coin or fungible asset that marketplace prices can be paid in
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct CurrencyConfig {
+    pub symbol: String,
+    /// Fungible asset metadata address, prices are stored and looked up by it
+    pub address: String,
+    /// Coin type paired with the fungible asset, if any
+    #[serde(default)]
+    pub coin_type: Option<String>,
+    pub decimals: u32,
+    /// Id of the currency at each price source by source name, for sources that don't
+    /// derive one from the address
+    #[serde(default)]
+    pub price_ids: HashMap<String, String>,
+}
+
+impl CurrencyConfig {
+    pub fn apt() -> Self {
+        Self {
+            symbol: "APT".to_string(),
+            address: APT_TOKEN_ADDR.to_string(),
+            coin_type: Some(APT_COIN_TYPE.to_string()),
+            decimals: 8,
+            price_ids: HashMap::new(),
+        }
+    }
+
+    /// Whether a currency detected from an event, either a metadata address or a coin
+    /// type, is this currency
+    pub fn matches(&self, currency: &str) -> bool {
+        let currency = standardize_currency(currency);
+
+        standardize_currency(&self.address) == currency
+            || self
+                .coin_type
+                .as_deref()
+                .is_some_and(|coin_type| standardize_currency(coin_type) == currency)
+    }
+
+    /// Converts an amount in the smallest unit of the currency to USD
+    pub fn to_usd(&self, amount: i64, usd: &BigDecimal) -> BigDecimal {
+        BigDecimal::from(amount) / BigDecimal::from(10_i64.pow(self.decimals)) * usd
+    }
+}
+
+/// Standardizes the configured currencies, APT is always priced even when it isn't listed
+pub fn resolve_currencies(currencies: &[CurrencyConfig]) -> Vec<CurrencyConfig> {
+    let mut currencies = currencies
+        .iter()
+        .map(|currency| CurrencyConfig {
+            address: standardize_address(&currency.address),
+            coin_type: currency.coin_type.as_deref().map(standardize_currency),
+            ..currency.clone()
+        })
+        .collect::<Vec<_>>();
+
+    if !currencies
+        .iter()
+        .any(|currency| currency.matches(APT_TOKEN_ADDR))
+    {
+        currencies.push(CurrencyConfig::apt());
+    }
+
+    currencies
+}
+
+/// Resolves a detected currency to its config, activities without one are priced in APT.
+/// Returns None for currencies that aren't configured.
+pub fn resolve_currency(
+    currencies: &[CurrencyConfig],
+    currency: Option<&str>,
+) -> Option<CurrencyConfig> {
+    match currency {
+        Some(currency) => currencies
+            .iter()
+            .find(|config| config.matches(currency))
+            .cloned()
+            .or_else(|| Some(CurrencyConfig::apt()).filter(|apt| apt.matches(currency))),
+        None => currencies
+            .iter()
+            .find(|config| config.matches(APT_TOKEN_ADDR))
+            .cloned()
+            .or_else(|| Some(CurrencyConfig::apt())),
+    }
+}
+
+/// Standardizes the address of a metadata address or of a `<address>::<module>::<struct>`
+/// coin type
+pub fn standardize_currency(currency: &str) -> String {
+    match currency.split_once("::") {
+        Some((address, rest)) => format!("{}::{}", standardize_address(address), rest),
+        None => standardize_address(currency),
+    }
+}
@@ -0,0 +1,169 @@
+use std::collections::HashMap;
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumString};
+
+/// Fields that entry function templates can reference as `{field}`
+pub const PAYLOAD_FIELDS: &[&str] = &[
+    "contract_address",
+    "token_addr",
+    "token_name",
+    "property_version",
+    "collection_addr",
+    "collection_name",
+    "creator_address",
+    "price",
+    "amount",
+    "expiration_time",
+    "nonce",
+    "seller",
+    "bidder",
+    "currency",
+    "coin_type",
+];
+
+/// What the aggregator can call a marketplace for
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, Hash, Display, EnumString)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum EntryFunctionAction {
+    Buy,
+    List,
+    Bid,
+    CancelBid,
+    CollectionBid,
+    CancelCollectionBid,
+}
+
+pub type EntryFunctionsConfig = HashMap<EntryFunctionAction, EntryFunctionConfig>;
+
+/// Move entry function called for an action. Type arguments and arguments are templates
+/// whose `{field}` placeholders are replaced with the fields of the listing, bid or nft.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct EntryFunctionConfig {
+    /// Address of the module, defaults to the contract address
+    #[serde(default)]
+    pub address: Option<String>,
+    pub module: String,
+    pub function: String,
+    #[serde(default)]
+    pub type_args: Vec<String>,
+    /// Json values, strings are rendered and arrays are rendered per element
+    #[serde(default)]
+    pub args: Vec<serde_json::Value>,
+}
+
+impl EntryFunctionConfig {
+    pub fn get_function(&self, contract_address: &str) -> String {
+        let address = self.address.as_deref().unwrap_or(contract_address);
+        format!("{}::{}::{}", address, self.module, self.function)
+    }
+
+    /// Every placeholder referenced by the type arguments and arguments
+    pub fn get_placeholders(&self) -> anyhow::Result<Vec<String>> {
+        let mut placeholders = Vec::new();
+        for type_arg in self.type_args.iter() {
+            placeholders.extend(get_placeholders(type_arg)?);
+        }
+
+        for arg in self.args.iter() {
+            collect_value_placeholders(arg, &mut placeholders)?;
+        }
+
+        Ok(placeholders)
+    }
+}
+
+/// Replaces the `{field}` placeholders of a template, failing on fields that aren't set
+pub fn render_template(template: &str, fields: &HashMap<&str, String>) -> anyhow::Result<String> {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some((start, end)) = find_placeholder(template, rest)? {
+        let field = &rest[start + 1..end];
+        let value = fields
+            .get(field)
+            .with_context(|| format!("Missing {field} for {template}"))?;
+
+        rendered.push_str(&rest[..start]);
+        rendered.push_str(value);
+        rest = &rest[end + 1..];
+    }
+
+    rendered.push_str(rest);
+
+    Ok(rendered)
+}
+
+/// Renders the strings of a json value, in arrays and objects too
+pub fn render_value(
+    value: &serde_json::Value,
+    fields: &HashMap<&str, String>,
+) -> anyhow::Result<serde_json::Value> {
+    match value {
+        serde_json::Value::String(template) => {
+            render_template(template, fields).map(serde_json::Value::String)
+        }
+        serde_json::Value::Array(values) => values
+            .iter()
+            .map(|value| render_value(value, fields))
+            .collect::<anyhow::Result<Vec<_>>>()
+            .map(serde_json::Value::Array),
+        serde_json::Value::Object(values) => values
+            .iter()
+            .map(|(key, value)| Ok((key.clone(), render_value(value, fields)?)))
+            .collect::<anyhow::Result<serde_json::Map<_, _>>>()
+            .map(serde_json::Value::Object),
+        value => Ok(value.clone()),
+    }
+}
+
+fn get_placeholders(template: &str) -> anyhow::Result<Vec<String>> {
+    let mut placeholders = Vec::new();
+    let mut rest = template;
+
+    while let Some((start, end)) = find_placeholder(template, rest)? {
+        placeholders.push(rest[start + 1..end].to_string());
+        rest = &rest[end + 1..];
+    }
+
+    Ok(placeholders)
+}
+
+fn collect_value_placeholders(
+    value: &serde_json::Value,
+    placeholders: &mut Vec<String>,
+) -> anyhow::Result<()> {
+    match value {
+        serde_json::Value::String(template) => placeholders.extend(get_placeholders(template)?),
+        serde_json::Value::Array(values) => {
+            for value in values {
+                collect_value_placeholders(value, placeholders)?;
+            }
+        }
+        serde_json::Value::Object(values) => {
+            for value in values.values() {
+                collect_value_placeholders(value, placeholders)?;
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+/// Byte offsets of the braces of the next placeholder in `rest`
+fn find_placeholder(template: &str, rest: &str) -> anyhow::Result<Option<(usize, usize)>> {
+    let start = match rest.find('{') {
+        Some(start) => start,
+        None => return Ok(None),
+    };
+
+    let end = rest[start..]
+        .find('}')
+        .map(|end| start + end)
+        .with_context(|| format!("Unclosed placeholder in {template}"))?;
+
+    Ok(Some((start, end)))
+}
@@ -0,0 +1,255 @@
+use std::collections::HashMap;
+
+use anyhow::Context;
+
+use crate::config::marketplace_config::{
+    DbColumn, EventRemapping, EventRemappingConfig, NFTMarketplaceConfig,
+};
+
+/// Resolves marketplace `extends` and event templates, so every returned config only
+/// holds concrete field mappings.
+pub fn resolve_marketplace_configs(
+    configs: &[NFTMarketplaceConfig],
+) -> anyhow::Result<Vec<NFTMarketplaceConfig>> {
+    configs
+        .iter()
+        .map(|config| {
+            resolve_config(config, configs, &mut vec![config.name.clone()])
+                .with_context(|| format!("Failed to resolve marketplace {}", config.name))
+        })
+        .collect()
+}
+
+impl NFTMarketplaceConfig {
+    /// Resolves a single config, `extends` is looked up in the already resolved `bases`
+    pub fn resolve(&self, bases: &[NFTMarketplaceConfig]) -> anyhow::Result<Self> {
+        resolve_config(self, bases, &mut vec![self.name.clone()])
+    }
+}
+
+fn resolve_config(
+    config: &NFTMarketplaceConfig,
+    configs: &[NFTMarketplaceConfig],
+    stack: &mut Vec<String>,
+) -> anyhow::Result<NFTMarketplaceConfig> {
+    let mut resolved = match config.extends.as_ref() {
+        Some(parent_name) => {
+            if stack.contains(parent_name) {
+                anyhow::bail!(
+                    "Circular extends: {} -> {}",
+                    stack.join(" -> "),
+                    parent_name
+                );
+            }
+
+            let parent = configs
+                .iter()
+                .find(|c| &c.name == parent_name)
+                .with_context(|| format!("Unknown marketplace {} in extends", parent_name))?;
+
+            stack.push(parent_name.clone());
+            let parent = resolve_config(parent, configs, stack)?;
+            stack.pop();
+
+            merge(parent, config)?
+        }
+        None => {
+            let mut resolved = config.clone();
+            resolved.events = resolve_events(&config.events, &config.templates)?;
+            resolved
+        }
+    };
+
+    for deployment in resolved.deployments.iter_mut() {
+        deployment.events = resolve_events(&deployment.events, &resolved.templates)?;
+        remove_unmapped(&mut deployment.events);
+    }
+    remove_unmapped(&mut resolved.events);
+
+    Ok(resolved)
+}
+
+/// Child values override the parent, event and resource mappings are merged per json path.
+/// The child's events are resolved against their own templates first, so a path the child
+/// maps through a template still overrides the parent's mapping of it.
+fn merge(
+    parent: NFTMarketplaceConfig,
+    child: &NFTMarketplaceConfig,
+) -> anyhow::Result<NFTMarketplaceConfig> {
+    let mut event_model_mapping = parent.event_model_mapping;
+    event_model_mapping.extend(child.event_model_mapping.clone());
+
+    let mut templates = parent.templates;
+    templates.extend(child.templates.clone());
+
+    let mut entry_functions = parent.entry_functions;
+    entry_functions.extend(child.entry_functions.clone());
+
+    let mut events = parent.events;
+    for (event_type, remapping) in resolve_events(&child.events, &templates)? {
+        events
+            .entry(event_type)
+            .or_default()
+            .event_fields
+            .extend(remapping.event_fields);
+    }
+
+    let mut resources = parent.resources;
+    for (resource_type, remapping) in &child.resources {
+        let resource = resources.entry(resource_type.clone()).or_default();
+        resource
+            .resource_fields
+            .extend(remapping.resource_fields.clone());
+        resource
+            .resource_fields
+            .retain(|_, columns| !columns.is_empty());
+    }
+
+    Ok(NFTMarketplaceConfig {
+        name: child.name.clone(),
+        extends: None,
+        starting_version: child.starting_version,
+        ending_version: child.ending_version,
+        contract_address: child.contract_address.clone(),
+        fee_account: child.fee_account.clone(),
+        fee_bps: child.fee_bps,
+        escrow_accounts: child.escrow_accounts.clone(),
+        event_model_mapping,
+        events,
+        resources,
+        templates,
+        entry_functions,
+        deployments: child.deployments.clone(),
+    })
+}
+
+fn resolve_events(
+    events: &EventRemappingConfig,
+    templates: &EventRemappingConfig,
+) -> anyhow::Result<EventRemappingConfig> {
+    events
+        .iter()
+        .map(|(event_type, remapping)| {
+            let event_fields = resolve_fields(remapping, templates, &mut Vec::new())
+                .with_context(|| format!("Failed to resolve event {}", event_type))?;
+
+            let remapping = EventRemapping {
+                extends: Vec::new(),
+                event_fields,
+            };
+
+            Ok((event_type.clone(), remapping))
+        })
+        .collect()
+}
+
+/// Drops the paths whose mapping was removed with an empty list
+fn remove_unmapped(events: &mut EventRemappingConfig) {
+    for remapping in events.values_mut() {
+        remapping
+            .event_fields
+            .retain(|_, columns| !columns.is_empty());
+    }
+}
+
+/// Applies the extended templates in order, then the remapping's own fields on top. Removed
+/// paths are kept as empty lists until the config is fully resolved.
+fn resolve_fields(
+    remapping: &EventRemapping,
+    templates: &EventRemappingConfig,
+    stack: &mut Vec<String>,
+) -> anyhow::Result<HashMap<String, Vec<DbColumn>>> {
+    let mut event_fields = HashMap::new();
+
+    for template_name in &remapping.extends {
+        if stack.contains(template_name) {
+            anyhow::bail!(
+                "Circular template: {} -> {}",
+                stack.join(" -> "),
+                template_name
+            );
+        }
+
+        let template = templates
+            .get(template_name)
+            .with_context(|| format!("Unknown template {}", template_name))?;
+
+        stack.push(template_name.clone());
+        event_fields.extend(resolve_fields(template, templates, stack)?);
+        stack.pop();
+    }
+
+    event_fields.extend(remapping.event_fields.clone());
+
+    Ok(event_fields)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn configs() -> Vec<NFTMarketplaceConfig> {
+        serde_yaml::from_str(
+            r#"
+- name: parent
+  starting_version: 0
+  contract_address: "0x1"
+  events:
+    events::ListEvent:
+      event_fields:
+        "$.price":
+          - table: nft_marketplace_activities
+            column: price
+        "$.seller":
+          - table: nft_marketplace_activities
+            column: seller
+        "$.amount":
+          - table: nft_marketplace_activities
+            column: token_amount
+- name: child
+  extends: parent
+  starting_version: 0
+  contract_address: "0x2"
+  templates:
+    price_in_octas:
+      event_fields:
+        "$.price":
+          - table: nft_marketplace_activities
+            column: price
+            decimals: 8
+  events:
+    events::ListEvent:
+      extends: [price_in_octas]
+      event_fields:
+        "$.amount": []
+"#,
+        )
+        .unwrap()
+    }
+
+    fn columns<'a>(config: &'a NFTMarketplaceConfig, path: &str) -> Option<&'a Vec<DbColumn>> {
+        config.events["events::ListEvent"].event_fields.get(path)
+    }
+
+    #[test]
+    fn child_template_fields_override_parent_fields() {
+        let resolved = resolve_marketplace_configs(&configs()).unwrap();
+        let child = resolved.iter().find(|c| c.name == "child").unwrap();
+
+        let price = columns(child, "$.price").unwrap();
+        assert_eq!(price.len(), 1);
+        assert_eq!(price[0].decimals, Some(8));
+        assert_eq!(columns(child, "$.seller").unwrap()[0].column, "seller");
+    }
+
+    #[test]
+    fn child_empty_fields_remove_parent_paths() {
+        let resolved = resolve_marketplace_configs(&configs()).unwrap();
+        let parent = resolved.iter().find(|c| c.name == "parent").unwrap();
+        let child = resolved.iter().find(|c| c.name == "child").unwrap();
+
+        assert!(columns(parent, "$.amount").is_some());
+        assert!(columns(child, "$.amount").is_none());
+        assert_eq!(columns(parent, "$.price").unwrap()[0].decimals, None);
+    }
+}
@@ -4,12 +4,15 @@ use aptos_indexer_processor_sdk::utils::convert::standardize_address;
 use serde::{Deserialize, Serialize};
 use strum::{Display, EnumString};
 
-use crate::workers::steps::marketplace::HashableJsonPath;
+use crate::{
+    config::entry_function_config::EntryFunctionsConfig,
+    workers::steps::marketplace::{HashableJsonPath, remappers::field_mapping::FieldMapping},
+};
 
 // event_type -> json_path, db_column
-pub type EventFieldRemappings = HashMap<EventType, HashMap<HashableJsonPath, Vec<DbColumn>>>;
+pub type EventFieldRemappings = HashMap<EventType, HashMap<HashableJsonPath, Vec<FieldMapping>>>;
 // resource_type -> json_path, db_column
-pub type ResourceFieldRemappings = HashMap<String, HashMap<HashableJsonPath, Vec<DbColumn>>>;
+pub type ResourceFieldRemappings = HashMap<String, HashMap<HashableJsonPath, Vec<FieldMapping>>>;
 
 pub type EventRemappingConfig = HashMap<String, EventRemapping>;
 pub type ResourceRemappingConfig = HashMap<String, ResourceRemapping>;
@@ -17,25 +20,285 @@ pub type ResourceRemappingConfig = HashMap<String, ResourceRemapping>;
 #[derive(Clone, Debug, Deserialize, Serialize, Default)]
 pub struct NFTMarketplaceConfig {
     pub name: String,
+    /// Name of another marketplace whose mappings this one inherits and overrides
+    #[serde(default)]
+    pub extends: Option<String>,
     pub starting_version: i64,
     pub ending_version: Option<i64>,
     pub contract_address: String,
+    /// Account receiving the marketplace fee of sales, defaults to the contract address
+    #[serde(default)]
+    pub fee_account: Option<String>,
+    /// Marketplace fee taken off a sale, in basis points
+    #[serde(default)]
+    pub fee_bps: Option<i64>,
+    /// Accounts holding listed tokens besides the contract address
     #[serde(default)]
-    pub event_model_mapping: HashMap<String, MarketplaceEventType>,
+    pub escrow_accounts: Vec<String>,
+    #[serde(default)]
+    pub event_model_mapping: HashMap<String, EventModelMapping>,
     #[serde(default)]
     pub events: EventRemappingConfig,
     #[serde(default)]
     pub resources: ResourceRemappingConfig,
+    /// Named field mappings that events can extend
+    #[serde(default)]
+    pub templates: EventRemappingConfig,
+    /// Entry functions the aggregator builds payloads for, per action
+    #[serde(default)]
+    pub entry_functions: EntryFunctionsConfig,
+    /// Additional contract deployments indexed under the same marketplace name
+    #[serde(default)]
+    pub deployments: Vec<MarketplaceDeployment>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, Default)]
+pub struct MarketplaceDeployment {
+    pub contract_address: String,
+    pub starting_version: i64,
+    pub ending_version: Option<i64>,
+    /// Falls back to the marketplace fee account
+    #[serde(default)]
+    pub fee_account: Option<String>,
+    /// Falls back to the marketplace fee
+    #[serde(default)]
+    pub fee_bps: Option<i64>,
+    /// Falls back to the marketplace escrow accounts when empty
+    #[serde(default)]
+    pub escrow_accounts: Vec<String>,
+    /// Falls back to the marketplace event_model_mapping when empty
+    #[serde(default)]
+    pub event_model_mapping: HashMap<String, EventModelMapping>,
+    /// Falls back to the marketplace events when empty
+    #[serde(default)]
+    pub events: EventRemappingConfig,
+    /// Falls back to the marketplace resources when empty
+    #[serde(default)]
+    pub resources: ResourceRemappingConfig,
+    /// Falls back to the marketplace entry functions when empty
+    #[serde(default)]
+    pub entry_functions: EntryFunctionsConfig,
+}
+
+impl NFTMarketplaceConfig {
+    /// Account the marketplace fee of sales is deposited to
+    pub fn get_fee_account(&self) -> String {
+        standardize_address(
+            self.fee_account
+                .as_deref()
+                .unwrap_or(self.contract_address.as_str()),
+        )
+    }
+
+    /// Accounts of every deployment that hold tokens for the marketplace: the contracts, the
+    /// fee accounts and the escrow accounts
+    pub fn get_escrow_accounts(&self) -> Vec<String> {
+        let mut accounts = Vec::new();
+        for (_, deployment) in self.get_deployments() {
+            accounts.push(standardize_address(&deployment.contract_address));
+            accounts.push(deployment.get_fee_account());
+            accounts.extend(
+                deployment
+                    .escrow_accounts
+                    .iter()
+                    .map(|account| standardize_address(account)),
+            );
+        }
+
+        accounts.sort();
+        accounts.dedup();
+        accounts
+    }
+
+    /// Flattens the marketplace into one config per deployment, the top level contract first.
+    /// Each config is paired with the processor name its progress is saved under, which is
+    /// `<name>:<contract_address>` for the additional deployments so reordering them keeps
+    /// their progress.
+    pub fn get_deployments(&self) -> Vec<(String, NFTMarketplaceConfig)> {
+        let mut deployments = vec![(
+            self.name.clone(),
+            NFTMarketplaceConfig {
+                deployments: Vec::new(),
+                ..self.clone()
+            },
+        )];
+
+        for deployment in self.deployments.iter() {
+            let config = NFTMarketplaceConfig {
+                name: self.name.clone(),
+                extends: self.extends.clone(),
+                starting_version: deployment.starting_version,
+                ending_version: deployment.ending_version,
+                contract_address: deployment.contract_address.clone(),
+                fee_account: deployment
+                    .fee_account
+                    .clone()
+                    .or_else(|| self.fee_account.clone()),
+                fee_bps: deployment.fee_bps.or(self.fee_bps),
+                escrow_accounts: if deployment.escrow_accounts.is_empty() {
+                    self.escrow_accounts.clone()
+                } else {
+                    deployment.escrow_accounts.clone()
+                },
+                event_model_mapping: if deployment.event_model_mapping.is_empty() {
+                    self.event_model_mapping.clone()
+                } else {
+                    deployment.event_model_mapping.clone()
+                },
+                events: if deployment.events.is_empty() {
+                    self.events.clone()
+                } else {
+                    deployment.events.clone()
+                },
+                resources: if deployment.resources.is_empty() {
+                    self.resources.clone()
+                } else {
+                    deployment.resources.clone()
+                },
+                templates: self.templates.clone(),
+                entry_functions: if deployment.entry_functions.is_empty() {
+                    self.entry_functions.clone()
+                } else {
+                    deployment.entry_functions.clone()
+                },
+                deployments: Vec::new(),
+            };
+
+            let processor_name = format!(
+                "{}:{}",
+                self.name,
+                standardize_address(&deployment.contract_address)
+            );
+            deployments.push((processor_name, config));
+        }
+
+        deployments
+    }
+
+    /// The deployment new transactions go to, the last one that is still open
+    pub fn get_latest_deployment(&self) -> NFTMarketplaceConfig {
+        let mut deployments = self
+            .get_deployments()
+            .into_iter()
+            .map(|(_, config)| config)
+            .collect::<Vec<_>>();
+
+        let index = deployments
+            .iter()
+            .rposition(|config| config.ending_version.is_none())
+            .unwrap_or(deployments.len() - 1);
+
+        deployments.swap_remove(index)
+    }
+}
+
+/// Configured marketplace fee of every deployment, by standardized contract address
+pub fn get_fee_bps(configs: &[NFTMarketplaceConfig]) -> HashMap<String, i64> {
+    configs
+        .iter()
+        .flat_map(|config| config.get_deployments())
+        .filter_map(|(_, config)| {
+            config
+                .fee_bps
+                .map(|fee_bps| (standardize_address(&config.contract_address), fee_bps))
+        })
+        .collect()
+}
+
+/// The deployment of any marketplace with the given contract address
+pub fn find_deployment(
+    configs: &[NFTMarketplaceConfig],
+    contract_address: &str,
+) -> Option<NFTMarketplaceConfig> {
+    let contract_address = standardize_address(contract_address);
+
+    configs
+        .iter()
+        .flat_map(|config| config.get_deployments())
+        .map(|(_, config)| config)
+        .find(|config| standardize_address(&config.contract_address) == contract_address)
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct DbColumn {
     pub table: String,
     pub column: String,
+    /// Value used when the path is missing or resolves to an empty value
+    #[serde(default)]
+    pub default: Option<String>,
+    /// Shifts the decimal point of a numeric value, e.g. 2 turns 1.5 into 150
+    #[serde(default)]
+    pub decimals: Option<i64>,
+    /// Additional json paths whose values are appended to the mapped value
+    #[serde(default)]
+    pub concat: Vec<String>,
+    /// Separator used to join concatenated values
+    #[serde(default)]
+    pub separator: Option<String>,
+    /// Decoding applied to the extracted value
+    #[serde(default)]
+    pub decode: Option<ValueDecoding>,
+    /// Only map the value when a sibling field matches
+    #[serde(default)]
+    pub when: Option<FieldCondition>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ValueDecoding {
+    /// Hex string or byte array to an utf8 string
+    Utf8,
+    /// Hex string to a decimal number
+    HexNumber,
+}
+
+/// Predicate over the raw event data, every check that is set must hold
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct FieldCondition {
+    pub path: String,
+    /// The value at `path` equals the given value
+    #[serde(default)]
+    pub equals: Option<String>,
+    /// The value at `path` is one of the given values
+    #[serde(default)]
+    pub r#in: Option<Vec<String>>,
+    /// A non null value is, or is not, present at `path`
+    #[serde(default)]
+    pub exists: Option<bool>,
+}
+
+/// Marketplace event type of a raw event, either fixed or picked from the event data
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum EventModelMapping {
+    Single(MarketplaceEventType),
+    /// The first rule whose conditions all match wins, the event is ignored if none does
+    Conditional(Vec<EventModelRule>),
+}
+
+impl EventModelMapping {
+    pub fn event_types(&self) -> Vec<&MarketplaceEventType> {
+        match self {
+            Self::Single(event_type) => vec![event_type],
+            Self::Conditional(rules) => rules.iter().map(|rule| &rule.r#type).collect(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct EventModelRule {
+    #[serde(default)]
+    pub when: Vec<FieldCondition>,
+    pub r#type: MarketplaceEventType,
 }
 
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
 pub struct EventRemapping {
+    /// Templates whose field mappings are applied first, in order
+    #[serde(default)]
+    pub extends: Vec<String>,
+    /// Field mappings, an empty list removes a path inherited from a template
+    #[serde(default)]
     pub event_fields: HashMap<String, Vec<DbColumn>>,
 }
 
@@ -66,6 +329,11 @@ pub enum MarketplaceEventType {
     CollectionBid,
     CancelCollectionBid,
     AcceptCollectionBid,
+    // Auction events
+    AuctionCreate,
+    AuctionBid,
+    AuctionSettle,
+    AuctionCancel,
     #[default]
     Unknown,
 }
@@ -119,6 +387,10 @@ impl EventType {
         addr_bytes[..32 - 1].iter().all(|x| *x == 0) && addr_bytes[32 - 1] < 0b10000
     }
 
+    pub fn get_address(&self) -> &str {
+        &self.address
+    }
+
     pub fn get_struct(&self) -> &str {
         &self.r#struct
     }
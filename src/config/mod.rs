@@ -3,12 +3,25 @@ use std::{fs::File, io::Read};
 use anyhow::Context;
 use serde::{Deserialize, Serialize};
 
-use crate::config::marketplace_config::NFTMarketplaceConfig;
+use crate::config::{
+    currency_config::{CurrencyConfig, resolve_currencies},
+    inheritance::resolve_marketplace_configs,
+    marketplace_config::NFTMarketplaceConfig,
+    price_config::{PriceIndexerConfig, PriceSourceConfig, PriceSourceKind},
+    validation::{IssueSeverity, validate_marketplace_configs},
+};
 
+pub mod currency_config;
+pub mod entry_function_config;
+pub mod inheritance;
 pub mod marketplace_config;
+pub mod price_config;
+pub mod validation;
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Config {
+    /// Tapp price indexer, used as the only price source when none are configured
+    #[serde(default)]
     pub tapp_url: String,
     pub admin_config: AdminConfig,
     pub server_config: ServerConfig,
@@ -16,6 +29,13 @@ pub struct Config {
     pub db_config: DbConfig,
     pub stream_config: StreamConfig,
     pub nft_marketplace_configs: Vec<NFTMarketplaceConfig>,
+    /// Currencies marketplace prices can be paid in, APT when omitted
+    #[serde(default = "Config::default_currencies")]
+    pub currencies: Vec<CurrencyConfig>,
+    #[serde(default)]
+    pub price_history_config: PriceHistoryConfig,
+    #[serde(default)]
+    pub price_indexer_config: PriceIndexerConfig,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -56,9 +76,70 @@ pub struct StreamConfig {
     pub starting_version: u64,
     pub ending_version: Option<u64>,
     pub active: bool,
+    pub replay: Option<ReplayConfig>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ReplayConfig {
+    /// A recorded batch file or a directory of batch files, replayed in file name order
+    pub path: String,
+    #[serde(default)]
+    pub format: ReplayFormat,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReplayFormat {
+    /// A JSON array of `Transaction` protos
+    #[default]
+    Json,
+    /// A protobuf encoded `TransactionsResponse`
+    Binary,
+}
+
+/// How usd prices are looked up in the price history at the block time of an activity
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PriceHistoryConfig {
+    /// Largest distance in seconds between a block time and the price used for it
+    #[serde(default = "PriceHistoryConfig::default_max_gap_secs")]
+    pub max_gap_secs: i64,
+    /// What to use when there is no price within `max_gap_secs`
+    #[serde(default)]
+    pub fallback: PriceFallback,
+}
+
+impl PriceHistoryConfig {
+    pub const fn default_max_gap_secs() -> i64 {
+        3600
+    }
+}
+
+impl Default for PriceHistoryConfig {
+    fn default() -> Self {
+        Self {
+            max_gap_secs: Self::default_max_gap_secs(),
+            fallback: PriceFallback::default(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PriceFallback {
+    /// The nearest price, however far it is
+    #[default]
+    Nearest,
+    /// The latest price
+    Latest,
+    /// No usd price, until the history is backfilled and usd prices are recomputed
+    None,
 }
 
 impl Config {
+    pub fn default_currencies() -> Vec<CurrencyConfig> {
+        vec![CurrencyConfig::apt()]
+    }
+
     pub fn load() -> anyhow::Result<Self> {
         let mut file = File::open("config.yaml").with_context(|| "failed to open the file path")?;
         let mut contents = String::new();
@@ -66,9 +147,46 @@ impl Config {
         file.read_to_string(&mut contents)
             .with_context(|| "failed to read the file path")?;
 
-        let config =
+        let mut config =
             serde_yaml::from_str::<Self>(&contents).with_context(|| "failed to parse yaml file")?;
 
+        config.nft_marketplace_configs =
+            resolve_marketplace_configs(&config.nft_marketplace_configs)?;
+        config.currencies = resolve_currencies(&config.currencies);
+
+        if config.price_indexer_config.sources.is_empty() && !config.tapp_url.is_empty() {
+            config.price_indexer_config.sources.push(PriceSourceConfig {
+                name: "tapp".to_string(),
+                kind: PriceSourceKind::Tapp,
+                url: config.tapp_url.clone(),
+                api_key: None,
+            });
+        }
+
+        config.validate()?;
+
         Ok(config)
     }
+
+    /// Logs every marketplace config issue and fails if any of them is a hard error
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let issues = validate_marketplace_configs(&self.nft_marketplace_configs);
+
+        let mut errors = Vec::new();
+        for issue in issues {
+            match issue.severity {
+                IssueSeverity::Warning => tracing::warn!("{}", issue),
+                IssueSeverity::Error => {
+                    tracing::error!("{}", issue);
+                    errors.push(issue.to_string());
+                }
+            }
+        }
+
+        if !errors.is_empty() {
+            anyhow::bail!("invalid marketplace configs:\n{}", errors.join("\n"));
+        }
+
+        Ok(())
+    }
 }
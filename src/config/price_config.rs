@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+
+/// Where and how often the price indexer fetches the usd prices of the currencies
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PriceIndexerConfig {
+    #[serde(default = "PriceIndexerConfig::default_interval_secs")]
+    pub interval_secs: u64,
+    /// Quotes older than this are ignored, and a stored price older than this is reported
+    /// as stale
+    #[serde(default = "PriceIndexerConfig::default_max_staleness_secs")]
+    pub max_staleness_secs: i64,
+    #[serde(default)]
+    pub selection: PriceSelection,
+    /// Price providers, in fallback order
+    #[serde(default)]
+    pub sources: Vec<PriceSourceConfig>,
+}
+
+impl PriceIndexerConfig {
+    pub const fn default_interval_secs() -> u64 {
+        5 * 60
+    }
+
+    pub const fn default_max_staleness_secs() -> i64 {
+        15 * 60
+    }
+}
+
+impl Default for PriceIndexerConfig {
+    fn default() -> Self {
+        Self {
+            interval_secs: Self::default_interval_secs(),
+            max_staleness_secs: Self::default_max_staleness_secs(),
+            selection: PriceSelection::default(),
+            sources: Vec::new(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PriceSelection {
+    /// The first source, in configured order, with a fresh quote
+    #[default]
+    Fallback,
+    /// The median of the fresh quotes of every source
+    Median,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PriceSourceConfig {
+    /// Unique name of the source, currencies set their id at the source by it
+    pub name: String,
+    pub kind: PriceSourceKind,
+    pub url: String,
+    pub api_key: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PriceSourceKind {
+    /// Tapp JSON-RPC `public/get_index_price`, ids default to `<address>_usd`
+    Tapp,
+    /// CoinGecko `simple/price`, with history from `market_chart/range`
+    Coingecko,
+}
@@ -0,0 +1,272 @@
+use std::{collections::HashSet, fmt};
+
+use serde::Serialize;
+use strum::Display;
+use utoipa::ToSchema;
+
+use crate::{
+    config::{
+        entry_function_config::PAYLOAD_FIELDS,
+        marketplace_config::{DbColumn, EventType, MarketplaceEventType, NFTMarketplaceConfig},
+    },
+    workers::steps::marketplace::{
+        HashableJsonPath,
+        remappers::{TableType, field_mapping::FieldMapping, predicate::EventModelRules},
+    },
+};
+
+#[derive(Clone, Copy, Debug, Display, Serialize, ToSchema, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum IssueSeverity {
+    /// The marketplace would silently drop or mangle data, the processor refuses to start
+    Error,
+    /// Suspicious but valid configuration
+    Warning,
+}
+
+#[derive(Clone, Debug, Serialize, ToSchema)]
+pub struct ConfigIssue {
+    pub severity: IssueSeverity,
+    pub marketplace: String,
+    pub event_type: Option<String>,
+    pub message: String,
+}
+
+impl fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.event_type.as_ref() {
+            Some(event_type) => write!(
+                f,
+                "[{}] {} ({}): {}",
+                self.severity, self.marketplace, event_type, self.message
+            ),
+            None => write!(
+                f,
+                "[{}] {}: {}",
+                self.severity, self.marketplace, self.message
+            ),
+        }
+    }
+}
+
+impl NFTMarketplaceConfig {
+    /// Statically checks the remapping configuration of every deployment, returning every
+    /// problem found.
+    pub fn validate(&self) -> Vec<ConfigIssue> {
+        let deployments = self.get_deployments();
+        let mut issues = Vec::new();
+
+        for (index, (name, deployment)) in deployments.iter().enumerate() {
+            issues.extend(deployment.validate_deployment(name));
+
+            let overlapping = deployments[..index].iter().find(|(_, other)| {
+                other.contract_address == deployment.contract_address
+                    && other
+                        .ending_version
+                        .is_none_or(|v| v >= deployment.starting_version)
+                    && deployment
+                        .ending_version
+                        .is_none_or(|v| v >= other.starting_version)
+            });
+
+            if deployments[..index]
+                .iter()
+                .any(|(other_name, _)| other_name == name)
+            {
+                issues.push(ConfigIssue {
+                    severity: IssueSeverity::Error,
+                    marketplace: name.clone(),
+                    event_type: None,
+                    message: format!(
+                        "{} is listed in more than one deployment, its progress would be shared",
+                        deployment.contract_address
+                    ),
+                });
+            }
+
+            if let Some((other_name, _)) = overlapping {
+                issues.push(ConfigIssue {
+                    severity: IssueSeverity::Error,
+                    marketplace: name.clone(),
+                    event_type: None,
+                    message: format!(
+                        "version range of {} overlaps with deployment {}",
+                        deployment.contract_address, other_name
+                    ),
+                });
+            }
+        }
+
+        issues
+    }
+
+    fn validate_deployment(&self, name: &str) -> Vec<ConfigIssue> {
+        let mut issues = Vec::new();
+
+        let mut push = |severity: IssueSeverity, event_type: Option<&str>, message: String| {
+            issues.push(ConfigIssue {
+                severity,
+                marketplace: name.to_string(),
+                event_type: event_type.map(String::from),
+                message,
+            });
+        };
+
+        if let Some(fee_bps) = self.fee_bps {
+            if !(0..=10_000).contains(&fee_bps) {
+                push(
+                    IssueSeverity::Error,
+                    None,
+                    format!("fee_bps {} is not between 0 and 10000", fee_bps),
+                );
+            }
+        }
+
+        if let Some(ending_version) = self.ending_version {
+            if ending_version < self.starting_version {
+                push(
+                    IssueSeverity::Error,
+                    None,
+                    format!(
+                        "ending_version {} is lower than starting_version {}",
+                        ending_version, self.starting_version
+                    ),
+                );
+            }
+        }
+
+        for (event_type, event_remapping) in &self.events {
+            let full_event_type = format!("{}::{}", self.contract_address, event_type);
+            if let Err(e) = EventType::try_from(full_event_type.as_str()) {
+                push(IssueSeverity::Error, Some(event_type), format!("{e:#}"));
+            }
+
+            if !self.event_model_mapping.contains_key(event_type) {
+                push(
+                    IssueSeverity::Error,
+                    Some(event_type),
+                    "event has field mappings but no event_model_mapping entry".to_string(),
+                );
+            }
+
+            for (json_path, db_columns) in &event_remapping.event_fields {
+                for message in validate_field_mapping(json_path, db_columns) {
+                    push(IssueSeverity::Error, Some(event_type), message);
+                }
+            }
+        }
+
+        for (event_type, event_model_mapping) in &self.event_model_mapping {
+            if !self.events.contains_key(event_type) {
+                push(
+                    IssueSeverity::Error,
+                    Some(event_type),
+                    "event_model_mapping entry has no field mappings under events".to_string(),
+                );
+            }
+
+            if let Err(e) = EventModelRules::new(event_model_mapping) {
+                push(IssueSeverity::Error, Some(event_type), format!("{e:#}"));
+            }
+
+            if event_model_mapping
+                .event_types()
+                .contains(&&MarketplaceEventType::Unknown)
+            {
+                push(
+                    IssueSeverity::Warning,
+                    Some(event_type),
+                    "event is mapped to the unknown event type".to_string(),
+                );
+            }
+        }
+
+        for (resource_type, resource_remapping) in &self.resources {
+            for (json_path, db_columns) in &resource_remapping.resource_fields {
+                for message in validate_field_mapping(json_path, db_columns) {
+                    push(IssueSeverity::Error, Some(resource_type), message);
+                }
+            }
+        }
+
+        for (action, entry_function) in &self.entry_functions {
+            let action = action.to_string();
+            match entry_function.get_placeholders() {
+                Ok(placeholders) => {
+                    for placeholder in placeholders {
+                        if !PAYLOAD_FIELDS.contains(&placeholder.as_str()) {
+                            push(
+                                IssueSeverity::Error,
+                                Some(action.as_str()),
+                                format!("unknown payload field {{{placeholder}}}"),
+                            );
+                        }
+                    }
+                }
+                Err(e) => push(
+                    IssueSeverity::Error,
+                    Some(action.as_str()),
+                    format!("{e:#}"),
+                ),
+            }
+        }
+
+        issues
+    }
+}
+
+/// Validates every marketplace config, including name uniqueness across marketplaces.
+pub fn validate_marketplace_configs(configs: &[NFTMarketplaceConfig]) -> Vec<ConfigIssue> {
+    let mut issues = Vec::new();
+    let mut names = HashSet::new();
+
+    for config in configs {
+        if !names.insert(config.name.as_str()) {
+            issues.push(ConfigIssue {
+                severity: IssueSeverity::Error,
+                marketplace: config.name.clone(),
+                event_type: None,
+                message: "marketplace name is used more than once".to_string(),
+            });
+        }
+
+        issues.extend(config.validate());
+    }
+
+    issues
+}
+
+fn validate_field_mapping(json_path: &str, db_columns: &[DbColumn]) -> Vec<String> {
+    let mut messages = Vec::new();
+
+    if let Err(e) = HashableJsonPath::new(json_path) {
+        messages.push(format!("invalid json path {json_path}: {e}"));
+    }
+
+    for db_column in db_columns {
+        if let Err(e) = FieldMapping::new(db_column) {
+            messages.push(format!(
+                "invalid transform for column {} at path {json_path}: {e:#}",
+                db_column.column
+            ));
+        }
+
+        match TableType::from_str(&db_column.table) {
+            Some(table_type) => {
+                if !table_type.is_valid_column(&db_column.column) {
+                    messages.push(format!(
+                        "unknown column {} in table {} for path {json_path}",
+                        db_column.column, db_column.table
+                    ));
+                }
+            }
+            None => messages.push(format!(
+                "unknown table {} for path {json_path}",
+                db_column.table
+            )),
+        }
+    }
+
+    messages
+}
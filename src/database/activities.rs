@@ -3,7 +3,7 @@ use std::{collections::HashMap, sync::Arc};
 use crate::{
     database::Schema,
     models::{
-        db::activity::DbActivity,
+        db::activity::{DbActivity, DbActivityPrice},
         schema::{
             AggregateFieldsSchema,
             activity::{
@@ -16,7 +16,10 @@ use crate::{
     utils::schema::{create_aggregate_query_builder, create_query_builder},
 };
 use anyhow::Context;
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Utc};
 use sqlx::{PgPool, Postgres, QueryBuilder, Transaction, postgres::PgQueryResult};
+use uuid::Uuid;
 
 #[async_trait::async_trait]
 pub trait IActivities: Send + Sync {
@@ -26,6 +29,28 @@ pub trait IActivities: Send + Sync {
         items: Vec<DbActivity>,
     ) -> anyhow::Result<PgQueryResult>;
 
+    async fn tx_upsert_activities(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        items: Vec<DbActivity>,
+    ) -> anyhow::Result<PgQueryResult>;
+
+    /// Fetches a page of priced activities with a block time in `[start_time, end_time]`,
+    /// ordered by block time and continuing after `after`
+    async fn fetch_activity_prices(
+        &self,
+        start_time: Option<DateTime<Utc>>,
+        end_time: Option<DateTime<Utc>>,
+        after: Option<(DateTime<Utc>, Uuid)>,
+        limit: i64,
+    ) -> anyhow::Result<Vec<DbActivityPrice>>;
+
+    /// Sets the usd price of activities, returns the collections of the updated activities
+    async fn update_usd_prices(
+        &self,
+        items: Vec<(Uuid, Option<BigDecimal>)>,
+    ) -> anyhow::Result<Vec<Uuid>>;
+
     async fn fetch_activities(
         &self,
         query: &QueryActivitySchema,
@@ -93,7 +118,11 @@ impl IActivities for Activities {
                 usd_price,
                 block_time,
                 block_height,
-                amount
+                amount,
+                currency,
+                seller_proceeds,
+                marketplace_fee,
+                royalty_amount
             )
             "#,
         )
@@ -113,6 +142,10 @@ impl IActivities for Activities {
             b.push_bind(item.block_time);
             b.push_bind(item.block_height);
             b.push_bind(item.amount);
+            b.push_bind(item.currency);
+            b.push_bind(item.seller_proceeds);
+            b.push_bind(item.marketplace_fee);
+            b.push_bind(item.royalty_amount);
         })
         .push(
             r#"
@@ -127,6 +160,155 @@ impl IActivities for Activities {
         Ok(res)
     }
 
+    async fn tx_upsert_activities(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        items: Vec<DbActivity>,
+    ) -> anyhow::Result<PgQueryResult> {
+        if items.is_empty() {
+            return Ok(PgQueryResult::default());
+        }
+
+        let res = QueryBuilder::<Postgres>::new(
+            r#"
+            INSERT INTO activities (
+                id,
+                tx_type,
+                tx_index,
+                tx_id,
+                sender,
+                receiver,
+                price,
+                nft_id,
+                collection_id,
+                market_contract_id,
+                market_name,
+                usd_price,
+                block_time,
+                block_height,
+                amount,
+                currency,
+                seller_proceeds,
+                marketplace_fee,
+                royalty_amount
+            )
+            "#,
+        )
+        .push_values(items, |mut b, item| {
+            b.push_bind(item.id);
+            b.push_bind(item.tx_type);
+            b.push_bind(item.tx_index);
+            b.push_bind(item.tx_id.clone());
+            b.push_bind(item.sender.clone());
+            b.push_bind(item.receiver.clone());
+            b.push_bind(item.price);
+            b.push_bind(item.nft_id.clone());
+            b.push_bind(item.collection_id.clone());
+            b.push_bind(item.market_contract_id.clone());
+            b.push_bind(item.market_name.clone());
+            b.push_bind(item.usd_price);
+            b.push_bind(item.block_time);
+            b.push_bind(item.block_height);
+            b.push_bind(item.amount);
+            b.push_bind(item.currency);
+            b.push_bind(item.seller_proceeds);
+            b.push_bind(item.marketplace_fee);
+            b.push_bind(item.royalty_amount);
+        })
+        .push(
+            r#"
+            ON CONFLICT (id) DO UPDATE SET
+                tx_type = COALESCE(EXCLUDED.tx_type, activities.tx_type),
+                sender = COALESCE(EXCLUDED.sender, activities.sender),
+                receiver = COALESCE(EXCLUDED.receiver, activities.receiver),
+                price = COALESCE(EXCLUDED.price, activities.price),
+                nft_id = COALESCE(EXCLUDED.nft_id, activities.nft_id),
+                collection_id = COALESCE(EXCLUDED.collection_id, activities.collection_id),
+                market_contract_id = COALESCE(EXCLUDED.market_contract_id, activities.market_contract_id),
+                market_name = COALESCE(EXCLUDED.market_name, activities.market_name),
+                usd_price = COALESCE(activities.usd_price, EXCLUDED.usd_price),
+                amount = COALESCE(EXCLUDED.amount, activities.amount),
+                currency = COALESCE(EXCLUDED.currency, activities.currency),
+                seller_proceeds = COALESCE(EXCLUDED.seller_proceeds, activities.seller_proceeds),
+                marketplace_fee = COALESCE(EXCLUDED.marketplace_fee, activities.marketplace_fee),
+                royalty_amount = COALESCE(EXCLUDED.royalty_amount, activities.royalty_amount)
+            "#,
+        )
+        .build()
+        .execute(&mut **tx)
+        .await
+        .context("Failed to upsert activities")?;
+
+        Ok(res)
+    }
+
+    async fn fetch_activity_prices(
+        &self,
+        start_time: Option<DateTime<Utc>>,
+        end_time: Option<DateTime<Utc>>,
+        after: Option<(DateTime<Utc>, Uuid)>,
+        limit: i64,
+    ) -> anyhow::Result<Vec<DbActivityPrice>> {
+        let (after_time, after_id) = after.unzip();
+
+        sqlx::query_as::<_, DbActivityPrice>(
+            r#"
+            SELECT a.id, a.price, a.currency, a.block_time FROM activities a
+            WHERE a.price IS NOT NULL
+                AND ($1::TIMESTAMPTZ IS NULL OR a.block_time >= $1)
+                AND ($2::TIMESTAMPTZ IS NULL OR a.block_time <= $2)
+                AND ($3::TIMESTAMPTZ IS NULL OR (a.block_time, a.id) > ($3, $4))
+            ORDER BY a.block_time, a.id
+            LIMIT $5
+            "#,
+        )
+        .bind(start_time)
+        .bind(end_time)
+        .bind(after_time)
+        .bind(after_id)
+        .bind(limit)
+        .fetch_all(&*self.pool)
+        .await
+        .context("Failed to fetch activity prices")
+    }
+
+    async fn update_usd_prices(
+        &self,
+        items: Vec<(Uuid, Option<BigDecimal>)>,
+    ) -> anyhow::Result<Vec<Uuid>> {
+        if items.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut query_builder = QueryBuilder::<Postgres>::new(
+            r#"
+            UPDATE activities
+            SET usd_price = prices.usd_price
+            FROM (
+            "#,
+        );
+
+        query_builder.push_values(items, |mut b, (id, usd_price)| {
+            b.push_bind(id);
+            b.push_bind(usd_price);
+        });
+
+        let res = query_builder
+            .push(
+                r#"
+                ) AS prices (id, usd_price)
+                WHERE activities.id = prices.id
+                RETURNING activities.collection_id
+                "#,
+            )
+            .build_query_scalar::<Option<Uuid>>()
+            .fetch_all(&*self.pool)
+            .await
+            .context("Failed to update usd prices")?;
+
+        Ok(res.into_iter().flatten().collect())
+    }
+
     async fn fetch_activities(
         &self,
         query: &QueryActivitySchema,
@@ -0,0 +1,302 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+use sqlx::{PgPool, Postgres, QueryBuilder, Transaction, postgres::PgQueryResult};
+use uuid::Uuid;
+
+use crate::models::{
+    db::auction::DbAuction,
+    schema::auction::{AuctionSchema, AuctionState, AuctionStatus},
+};
+
+/// The upserted row is a later auction of the same key than the stored one
+const NEW_AUCTION: &str =
+    "EXCLUDED.created_tx_index > COALESCE(auctions.created_tx_index, auctions.tx_index, -1)";
+
+#[async_trait::async_trait]
+pub trait IAuctions: Send + Sync {
+    async fn tx_insert_auctions(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        items: Vec<DbAuction>,
+    ) -> anyhow::Result<PgQueryResult>;
+
+    async fn fetch_collection_auctions(
+        &self,
+        collection_id: Uuid,
+        state: AuctionState,
+        limit: i64,
+        offset: i64,
+    ) -> anyhow::Result<Vec<AuctionSchema>>;
+}
+
+pub struct Auctions {
+    pool: Arc<PgPool>,
+}
+
+impl Auctions {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait::async_trait]
+impl IAuctions for Auctions {
+    async fn tx_insert_auctions(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        items: Vec<DbAuction>,
+    ) -> anyhow::Result<PgQueryResult> {
+        if items.is_empty() {
+            return Ok(PgQueryResult::default());
+        }
+
+        // The highest bid only goes up, so replays and batches out of order can't lower it,
+        // while the status and the end time follow the latest event by tx_index. Auctions
+        // without a listing id are keyed by their token, so a newer create starts the row
+        // over and the events of the auctions before it are ignored
+        let res = QueryBuilder::<Postgres>::new(
+            r#"
+            INSERT INTO auctions (
+                id,
+                market_contract_id,
+                market_name,
+                collection_id,
+                nft_id,
+                nonce,
+                seller,
+                reserve_price,
+                highest_bid,
+                highest_bidder,
+                currency,
+                start_time,
+                end_time,
+                status,
+                created_tx_id,
+                settled_tx_id,
+                cancelled_tx_id,
+                created_tx_index,
+                tx_index,
+                block_time
+            )
+            "#,
+        )
+        .push_values(items, |mut b, item| {
+            b.push_bind(item.id);
+            b.push_bind(item.market_contract_id);
+            b.push_bind(item.market_name);
+            b.push_bind(item.collection_id);
+            b.push_bind(item.nft_id);
+            b.push_bind(item.nonce);
+            b.push_bind(item.seller);
+            b.push_bind(item.reserve_price);
+            b.push_bind(item.highest_bid);
+            b.push_bind(item.highest_bidder);
+            b.push_bind(item.currency);
+            b.push_bind(item.start_time);
+            b.push_bind(item.end_time);
+            b.push_bind(item.status);
+            b.push_bind(item.created_tx_id);
+            b.push_bind(item.settled_tx_id);
+            b.push_bind(item.cancelled_tx_id);
+            b.push_bind(item.created_tx_index);
+            b.push_bind(item.tx_index);
+            b.push_bind(item.block_time);
+        })
+        .push(format!(
+            r#"
+            ON CONFLICT (id) DO UPDATE SET
+                collection_id = COALESCE(EXCLUDED.collection_id, auctions.collection_id),
+                nft_id = COALESCE(EXCLUDED.nft_id, auctions.nft_id),
+                nonce = COALESCE(EXCLUDED.nonce, auctions.nonce),
+                seller = CASE WHEN {new_auction}
+                    THEN EXCLUDED.seller ELSE COALESCE(EXCLUDED.seller, auctions.seller) END,
+                reserve_price = CASE WHEN {new_auction}
+                    THEN EXCLUDED.reserve_price ELSE COALESCE(EXCLUDED.reserve_price, auctions.reserve_price) END,
+                start_time = CASE WHEN {new_auction}
+                    THEN EXCLUDED.start_time ELSE COALESCE(EXCLUDED.start_time, auctions.start_time) END,
+                currency = CASE WHEN {new_auction}
+                    THEN EXCLUDED.currency ELSE COALESCE(EXCLUDED.currency, auctions.currency) END,
+                highest_bidder = CASE
+                    WHEN {new_auction} THEN EXCLUDED.highest_bidder
+                    WHEN auctions.highest_bid IS NULL OR EXCLUDED.highest_bid > auctions.highest_bid
+                    THEN COALESCE(EXCLUDED.highest_bidder, auctions.highest_bidder)
+                    ELSE auctions.highest_bidder
+                END,
+                highest_bid = CASE WHEN {new_auction}
+                    THEN EXCLUDED.highest_bid ELSE GREATEST(EXCLUDED.highest_bid, auctions.highest_bid) END,
+                created_tx_id = COALESCE(EXCLUDED.created_tx_id, auctions.created_tx_id),
+                settled_tx_id = CASE WHEN {new_auction}
+                    THEN EXCLUDED.settled_tx_id ELSE COALESCE(EXCLUDED.settled_tx_id, auctions.settled_tx_id) END,
+                cancelled_tx_id = CASE WHEN {new_auction}
+                    THEN EXCLUDED.cancelled_tx_id ELSE COALESCE(EXCLUDED.cancelled_tx_id, auctions.cancelled_tx_id) END,
+                status = CASE
+                    WHEN {new_auction} OR auctions.tx_index IS NULL OR EXCLUDED.tx_index >= auctions.tx_index
+                    THEN EXCLUDED.status
+                    ELSE auctions.status
+                END,
+                end_time = CASE
+                    WHEN {new_auction} THEN EXCLUDED.end_time
+                    WHEN auctions.tx_index IS NULL OR EXCLUDED.tx_index >= auctions.tx_index
+                    THEN COALESCE(EXCLUDED.end_time, auctions.end_time)
+                    ELSE auctions.end_time
+                END,
+                block_time = CASE
+                    WHEN {new_auction} OR auctions.tx_index IS NULL OR EXCLUDED.tx_index >= auctions.tx_index
+                    THEN EXCLUDED.block_time
+                    ELSE auctions.block_time
+                END,
+                created_tx_index = GREATEST(EXCLUDED.created_tx_index, auctions.created_tx_index),
+                tx_index = CASE WHEN {new_auction}
+                    THEN EXCLUDED.tx_index ELSE GREATEST(EXCLUDED.tx_index, auctions.tx_index) END,
+                updated_at = NOW()
+            WHERE COALESCE(EXCLUDED.created_tx_index, EXCLUDED.tx_index) >= COALESCE(auctions.created_tx_index, 0)
+            "#,
+            new_auction = NEW_AUCTION,
+        ))
+        .build()
+        .execute(&mut **tx)
+        .await
+        .context("Failed to insert auctions")?;
+
+        Ok(res)
+    }
+
+    async fn fetch_collection_auctions(
+        &self,
+        collection_id: Uuid,
+        state: AuctionState,
+        limit: i64,
+        offset: i64,
+    ) -> anyhow::Result<Vec<AuctionSchema>> {
+        let mut query_builder = QueryBuilder::<Postgres>::new(
+            r#"
+            SELECT * FROM auctions
+            WHERE collection_id = "#,
+        );
+
+        query_builder.push_bind(collection_id);
+
+        // Live auctions end soonest first, ended auctions are the most recent first
+        match state {
+            AuctionState::Live => query_builder
+                .push(" AND status = ")
+                .push_bind(AuctionStatus::Active.to_string())
+                .push(" AND (end_time IS NULL OR end_time > NOW())")
+                .push(" ORDER BY end_time ASC NULLS LAST"),
+            AuctionState::Ended => query_builder
+                .push(" AND (status IS DISTINCT FROM ")
+                .push_bind(AuctionStatus::Active.to_string())
+                .push(" OR end_time <= NOW())")
+                .push(" ORDER BY COALESCE(end_time, block_time) DESC NULLS LAST"),
+        };
+
+        let res = query_builder
+            .push(" LIMIT ")
+            .push_bind(limit)
+            .push(" OFFSET ")
+            .push_bind(offset)
+            .build_query_as::<AuctionSchema>()
+            .fetch_all(&*self.pool)
+            .await
+            .context("Failed to fetch collection auctions")?;
+
+        Ok(res)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn auction(tx_index: i64, status: AuctionStatus) -> DbAuction {
+        DbAuction {
+            id: Uuid::nil(),
+            status: Some(status.to_string()),
+            tx_index: Some(tx_index),
+            ..Default::default()
+        }
+    }
+
+    fn created(tx_index: i64, reserve_price: i64) -> DbAuction {
+        DbAuction {
+            reserve_price: Some(reserve_price),
+            created_tx_id: Some(format!("0x{tx_index}")),
+            created_tx_index: Some(tx_index),
+            ..auction(tx_index, AuctionStatus::Active)
+        }
+    }
+
+    fn bid(tx_index: i64, highest_bid: i64) -> DbAuction {
+        DbAuction {
+            highest_bid: Some(highest_bid),
+            highest_bidder: Some("0x1".to_string()),
+            ..auction(tx_index, AuctionStatus::Active)
+        }
+    }
+
+    fn settled(tx_index: i64) -> DbAuction {
+        DbAuction {
+            settled_tx_id: Some(format!("0x{tx_index}")),
+            ..auction(tx_index, AuctionStatus::Settled)
+        }
+    }
+
+    async fn insert_auctions(pool: &PgPool, auctions: Vec<DbAuction>) {
+        let db = Auctions::new(Arc::new(pool.clone()));
+        let mut tx = pool.begin().await.unwrap();
+        db.tx_insert_auctions(&mut tx, auctions).await.unwrap();
+        tx.commit().await.unwrap();
+    }
+
+    async fn fetch_auction(
+        pool: &PgPool,
+    ) -> (
+        Option<String>,
+        Option<i64>,
+        Option<i64>,
+        Option<String>,
+        Option<String>,
+    ) {
+        sqlx::query_as(
+            "SELECT status, reserve_price, highest_bid, highest_bidder, settled_tx_id FROM auctions WHERE id = $1",
+        )
+        .bind(Uuid::nil())
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    #[sqlx::test]
+    async fn newer_create_starts_the_auction_over(pool: PgPool) {
+        insert_auctions(&pool, vec![created(100, 100)]).await;
+        insert_auctions(&pool, vec![bid(101, 150)]).await;
+        insert_auctions(&pool, vec![settled(102)]).await;
+        insert_auctions(&pool, vec![created(200, 120)]).await;
+
+        let (status, reserve_price, highest_bid, highest_bidder, settled_tx_id) =
+            fetch_auction(&pool).await;
+        assert_eq!(status, Some(AuctionStatus::Active.to_string()));
+        assert_eq!(reserve_price, Some(120));
+        assert_eq!(highest_bid, None);
+        assert_eq!(highest_bidder, None);
+        assert_eq!(settled_tx_id, None);
+    }
+
+    #[sqlx::test]
+    async fn events_of_an_earlier_auction_are_ignored(pool: PgPool) {
+        insert_auctions(&pool, vec![created(200, 120)]).await;
+        insert_auctions(&pool, vec![bid(101, 150)]).await;
+        insert_auctions(&pool, vec![settled(102)]).await;
+        insert_auctions(&pool, vec![created(100, 100)]).await;
+        insert_auctions(&pool, vec![bid(201, 130)]).await;
+
+        let (status, reserve_price, highest_bid, highest_bidder, settled_tx_id) =
+            fetch_auction(&pool).await;
+        assert_eq!(status, Some(AuctionStatus::Active.to_string()));
+        assert_eq!(reserve_price, Some(120));
+        assert_eq!(highest_bid, Some(130));
+        assert_eq!(highest_bidder, Some("0x1".to_string()));
+        assert_eq!(settled_tx_id, None);
+    }
+}
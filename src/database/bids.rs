@@ -4,15 +4,19 @@ use crate::models::schema::AggregateFieldsSchema;
 use crate::models::schema::bid::{AggregateBidFieldsSchema, DistinctBidSchema};
 use crate::utils::schema::{create_aggregate_query_builder, create_query_builder};
 use crate::{
+    config::currency_config::APT_TOKEN_ADDR,
     database::Schema,
     models::{
-        db::bid::DbBid,
-        schema::bid::{BidSchema, OrderBidSchema, QueryBidSchema},
+        db::bid::{DbBid, DbBidFill},
+        schema::bid::{
+            BidFillSchema, BidSchema, BidStatus, OrderBidSchema, QueryBidSchema, SellQuoteSchema,
+        },
     },
 };
 use anyhow::Context;
 use chrono::Utc;
 use sqlx::{PgPool, Postgres, QueryBuilder, Transaction, postgres::PgQueryResult};
+use uuid::Uuid;
 
 #[async_trait::async_trait]
 pub trait IBids: Send + Sync {
@@ -22,6 +26,20 @@ pub trait IBids: Send + Sync {
         bids: Vec<DbBid>,
     ) -> anyhow::Result<PgQueryResult>;
 
+    async fn tx_insert_bid_fills(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        fills: Vec<DbBidFill>,
+    ) -> anyhow::Result<PgQueryResult>;
+
+    async fn tx_refresh_bid_fills(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        bid_ids: &[Uuid],
+    ) -> anyhow::Result<PgQueryResult>;
+
+    async fn expire_bids(&self) -> anyhow::Result<PgQueryResult>;
+
     async fn fetch_bids(
         &self,
         query: &QueryBidSchema,
@@ -37,6 +55,16 @@ pub trait IBids: Send + Sync {
         query: &QueryBidSchema,
         distinct: Option<&DistinctBidSchema>,
     ) -> anyhow::Result<AggregateFieldsSchema<AggregateBidFieldsSchema>>;
+
+    async fn fetch_bid_fills(&self, bid_id: &Uuid) -> anyhow::Result<Vec<BidFillSchema>>;
+
+    /// Best active APT bid the nft can be sold into, by what the seller receives after the
+    /// royalty and the marketplace fee, `fee_bps` being keyed by standardized contract address
+    async fn fetch_sell_quote(
+        &self,
+        nft_id: Uuid,
+        fee_bps: &HashMap<String, i64>,
+    ) -> anyhow::Result<Option<SellQuoteSchema>>;
 }
 
 pub struct Bids {
@@ -79,6 +107,12 @@ impl IBids for Bids {
                 remaining_count, 
                 status,
                 bid_type,
+                tx_index,
+                created_tx_index,
+                amount,
+                currency,
+                attr_type,
+                attr_value,
                 updated_at
             )
             "#,
@@ -100,20 +134,42 @@ impl IBids for Bids {
             b.push_bind(item.remaining_count);
             b.push_bind(item.status.clone());
             b.push_bind(item.bid_type.clone());
+            b.push_bind(item.tx_index);
+            b.push_bind(item.created_tx_index);
+            b.push_bind(item.amount);
+            b.push_bind(item.currency.clone());
+            b.push_bind(item.attr_type.clone());
+            b.push_bind(item.attr_value.clone());
             b.push_bind(Utc::now());
         })
         .push(
             r#"
             ON CONFLICT (id) DO UPDATE
-            SET 
-                bidder = EXCLUDED.bidder,
-                status = EXCLUDED.status,
-                nonce = EXCLUDED.nonce,
-                created_tx_id = COALESCE(EXCLUDED.created_tx_id, bids.created_tx_id),
+            SET
+                created_tx_id = CASE WHEN bids.created_tx_index IS NULL OR EXCLUDED.created_tx_index >= bids.created_tx_index
+                    THEN COALESCE(EXCLUDED.created_tx_id, bids.created_tx_id) ELSE bids.created_tx_id END,
+                amount = CASE WHEN bids.created_tx_index IS NULL OR EXCLUDED.created_tx_index >= bids.created_tx_index
+                    THEN COALESCE(EXCLUDED.amount, bids.amount) ELSE bids.amount END,
+                created_tx_index = GREATEST(EXCLUDED.created_tx_index, bids.created_tx_index),
                 accepted_tx_id = COALESCE(EXCLUDED.accepted_tx_id, bids.accepted_tx_id),
                 cancelled_tx_id = COALESCE(EXCLUDED.cancelled_tx_id, bids.cancelled_tx_id),
                 nft_id = COALESCE(EXCLUDED.nft_id, bids.nft_id),
-                receiver = EXCLUDED.receiver,
+                currency = COALESCE(EXCLUDED.currency, bids.currency),
+                attr_type = COALESCE(EXCLUDED.attr_type, bids.attr_type),
+                attr_value = COALESCE(EXCLUDED.attr_value, bids.attr_value),
+                bidder = CASE WHEN bids.tx_index IS NULL OR EXCLUDED.tx_index >= bids.tx_index
+                    THEN EXCLUDED.bidder ELSE bids.bidder END,
+                status = CASE WHEN bids.tx_index IS NULL OR EXCLUDED.tx_index >= bids.tx_index
+                    THEN EXCLUDED.status ELSE bids.status END,
+                nonce = CASE WHEN bids.tx_index IS NULL OR EXCLUDED.tx_index >= bids.tx_index
+                    THEN EXCLUDED.nonce ELSE bids.nonce END,
+                expired_at = CASE WHEN bids.tx_index IS NULL OR EXCLUDED.tx_index >= bids.tx_index
+                    THEN COALESCE(EXCLUDED.expired_at, bids.expired_at) ELSE bids.expired_at END,
+                remaining_count = CASE WHEN bids.tx_index IS NULL OR EXCLUDED.tx_index >= bids.tx_index
+                    THEN COALESCE(EXCLUDED.remaining_count, bids.remaining_count) ELSE bids.remaining_count END,
+                receiver = CASE WHEN bids.tx_index IS NULL OR EXCLUDED.tx_index >= bids.tx_index
+                    THEN EXCLUDED.receiver ELSE bids.receiver END,
+                tx_index = GREATEST(EXCLUDED.tx_index, bids.tx_index),
                 updated_at = EXCLUDED.updated_at
             "#,
         )
@@ -125,6 +181,119 @@ impl IBids for Bids {
         Ok(res)
     }
 
+    async fn tx_insert_bid_fills(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        items: Vec<DbBidFill>,
+    ) -> anyhow::Result<PgQueryResult> {
+        if items.is_empty() {
+            return Ok(PgQueryResult::default());
+        }
+
+        let res = QueryBuilder::<Postgres>::new(
+            r#"
+            INSERT INTO bid_fills (
+                activity_id,
+                bid_id,
+                tx_id,
+                tx_index,
+                nft_id,
+                seller,
+                price,
+                amount,
+                block_time
+            )
+            "#,
+        )
+        .push_values(items, |mut b, item| {
+            b.push_bind(item.activity_id);
+            b.push_bind(item.bid_id);
+            b.push_bind(item.tx_id);
+            b.push_bind(item.tx_index);
+            b.push_bind(item.nft_id);
+            b.push_bind(item.seller);
+            b.push_bind(item.price);
+            b.push_bind(item.amount);
+            b.push_bind(item.block_time);
+        })
+        .push(
+            r#"
+            ON CONFLICT (activity_id) DO NOTHING
+            "#,
+        )
+        .build()
+        .execute(&mut **tx)
+        .await
+        .context("Failed to insert bid fills")?;
+
+        Ok(res)
+    }
+
+    /// Recomputes what remains on bids with a known amount from their fills since their
+    /// latest create, so that fills from earlier batches and out of order accepts are
+    /// accounted for while fills of a previous bid under the same id aren't
+    async fn tx_refresh_bid_fills(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        bid_ids: &[Uuid],
+    ) -> anyhow::Result<PgQueryResult> {
+        if bid_ids.is_empty() {
+            return Ok(PgQueryResult::default());
+        }
+
+        let res = sqlx::query(
+            r#"
+            UPDATE bids
+            SET
+                remaining_count = GREATEST(bids.amount - f.filled, 0),
+                status = CASE
+                    WHEN bids.status NOT IN ($2, $3) THEN bids.status
+                    WHEN bids.amount - f.filled > 0 THEN $2
+                    ELSE $3
+                END,
+                updated_at = NOW()
+            FROM (
+                SELECT
+                    b.id AS bid_id,
+                    COALESCE(
+                        SUM(bf.amount) FILTER (WHERE b.created_tx_index IS NULL OR bf.tx_index > b.created_tx_index),
+                        0
+                    )::BIGINT AS filled
+                FROM bids b
+                    JOIN bid_fills bf ON bf.bid_id = b.id
+                WHERE b.id = ANY($1)
+                GROUP BY b.id
+            ) f
+            WHERE bids.id = f.bid_id AND bids.amount IS NOT NULL
+            "#,
+        )
+        .bind(bid_ids)
+        .bind(BidStatus::Active.to_string())
+        .bind(BidStatus::Matched.to_string())
+        .execute(&mut **tx)
+        .await
+        .context("Failed to refresh bid fills")?;
+
+        Ok(res)
+    }
+
+    async fn expire_bids(&self) -> anyhow::Result<PgQueryResult> {
+        let res = sqlx::query(
+            r#"
+            UPDATE bids
+            SET status = $2, updated_at = NOW()
+            WHERE status = $1 AND expired_at <= NOW()
+            "#,
+        )
+        .bind(BidStatus::Active.to_string())
+        .bind(BidStatus::Expired.to_string())
+        .execute(&*self.pool)
+        .await
+        .context("Failed to expire bids")?;
+
+        Ok(res)
+    }
+
     async fn fetch_bids(
         &self,
         query: &QueryBidSchema,
@@ -168,4 +337,233 @@ impl IBids for Bids {
 
         Ok(result)
     }
+
+    async fn fetch_bid_fills(&self, bid_id: &Uuid) -> anyhow::Result<Vec<BidFillSchema>> {
+        let res = sqlx::query_as::<_, BidFillSchema>(
+            r#"
+            SELECT * FROM bid_fills
+            WHERE bid_id = $1
+            ORDER BY tx_index
+            "#,
+        )
+        .bind(bid_id)
+        .fetch_all(&*self.pool)
+        .await
+        .context("Failed to fetch bid fills")?;
+
+        Ok(res)
+    }
+
+    async fn fetch_sell_quote(
+        &self,
+        nft_id: Uuid,
+        fee_bps: &HashMap<String, i64>,
+    ) -> anyhow::Result<Option<SellQuoteSchema>> {
+        let (contract_addresses, fee_bps): (Vec<String>, Vec<i64>) = fee_bps
+            .iter()
+            .map(|(contract_address, fee_bps)| (contract_address.clone(), *fee_bps))
+            .unzip();
+
+        // The owner can't accept their own bids, and trait bids only match nfts having it
+        let res = sqlx::query_as::<_, SellQuoteSchema>(
+            r#"
+            WITH
+                target AS (
+                    SELECT
+                        n.id,
+                        n.collection_id,
+                        n.owner,
+                        COALESCE(n.royalty, c.royalty, 0)  AS royalty
+                    FROM nfts n
+                        LEFT JOIN collections c ON c.id = n.collection_id
+                    WHERE n.id = $1
+                        AND (n.burned IS NULL OR NOT n.burned)
+                ),
+                candidates AS (
+                    SELECT b.*
+                    FROM bids b, target t
+                    WHERE b.status = 'active'
+                        AND b.price IS NOT NULL
+                        AND (b.expired_at IS NULL OR b.expired_at > NOW())
+                        AND (b.currency IS NULL OR b.currency = $2)
+                        AND b.bidder IS DISTINCT FROM t.owner
+                        AND (
+                            (b.bid_type = 'solo' AND b.nft_id = t.id)
+                            OR (
+                                b.bid_type = 'collection'
+                                AND b.collection_id = t.collection_id
+                                AND COALESCE(b.remaining_count, 0) > 0
+                                AND (
+                                    b.attr_type IS NULL
+                                    OR EXISTS (
+                                        SELECT 1 FROM attributes a
+                                        WHERE a.nft_id = t.id
+                                            AND a.attr_type = b.attr_type
+                                            AND a.value = b.attr_value
+                                    )
+                                )
+                            )
+                        )
+                ),
+                fees AS (
+                    SELECT * FROM UNNEST($3::TEXT[], $4::BIGINT[]) AS f (contract_address, fee_bps)
+                ),
+                quotes AS (
+                    SELECT
+                        c.*,
+                        FLOOR(c.price * t.royalty / 100)::BIGINT        AS royalty_amount,
+                        (c.price * f.fee_bps / 10000)::BIGINT           AS marketplace_fee
+                    FROM candidates c
+                        CROSS JOIN target t
+                        LEFT JOIN fees f ON f.contract_address = standardize_address(c.market_contract_id)
+                )
+            SELECT
+                q.*,
+                q.price - q.royalty_amount - COALESCE(q.marketplace_fee, 0) AS net_proceeds
+            FROM quotes q
+            ORDER BY net_proceeds DESC, q.price DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(nft_id)
+        .bind(APT_TOKEN_ADDR)
+        .bind(contract_addresses)
+        .bind(fee_bps)
+        .fetch_optional(&*self.pool)
+        .await
+        .context("Failed to fetch sell quote")?;
+
+        Ok(res)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bid(tx_index: i64, status: BidStatus, price: i64) -> DbBid {
+        DbBid {
+            id: Uuid::nil(),
+            bidder: Some("0x1".to_string()),
+            status: Some(status.to_string()),
+            price: Some(price),
+            tx_index: Some(tx_index),
+            ..Default::default()
+        }
+    }
+
+    async fn insert_bids(pool: &PgPool, bids: Vec<DbBid>) {
+        let db = Bids::new(Arc::new(pool.clone()));
+        let mut tx = pool.begin().await.unwrap();
+        db.tx_insert_bids(&mut tx, bids).await.unwrap();
+        tx.commit().await.unwrap();
+    }
+
+    fn collection_bid(tx_index: i64, amount: i64) -> DbBid {
+        DbBid {
+            bid_type: Some("collection".to_string()),
+            created_tx_id: Some(format!("0x{tx_index}")),
+            created_tx_index: Some(tx_index),
+            amount: Some(amount),
+            remaining_count: Some(amount),
+            ..bid(tx_index, BidStatus::Active, 100)
+        }
+    }
+
+    fn fill(tx_index: i64) -> DbBidFill {
+        DbBidFill {
+            activity_id: Uuid::from_u128(tx_index as u128),
+            bid_id: Uuid::nil(),
+            tx_id: format!("0x{tx_index}"),
+            tx_index,
+            amount: 1,
+            ..Default::default()
+        }
+    }
+
+    async fn fill_bid(pool: &PgPool, fills: Vec<DbBidFill>) {
+        let db = Bids::new(Arc::new(pool.clone()));
+        let mut tx = pool.begin().await.unwrap();
+        db.tx_insert_bid_fills(&mut tx, fills).await.unwrap();
+        db.tx_refresh_bid_fills(&mut tx, &[Uuid::nil()])
+            .await
+            .unwrap();
+        tx.commit().await.unwrap();
+    }
+
+    async fn fetch_remaining(pool: &PgPool) -> (Option<String>, Option<i64>) {
+        sqlx::query_as("SELECT status, remaining_count FROM bids WHERE id = $1")
+            .bind(Uuid::nil())
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    async fn fetch_bid(pool: &PgPool) -> (Option<String>, Option<i64>, Option<i64>) {
+        sqlx::query_as("SELECT status, price, tx_index FROM bids WHERE id = $1")
+            .bind(Uuid::nil())
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    #[sqlx::test]
+    async fn older_bid_keeps_the_newer_state(pool: PgPool) {
+        insert_bids(&pool, vec![bid(200, BidStatus::Cancelled, 150)]).await;
+        insert_bids(&pool, vec![bid(100, BidStatus::Active, 100)]).await;
+
+        let (status, price, tx_index) = fetch_bid(&pool).await;
+        assert_eq!(status, Some(BidStatus::Cancelled.to_string()));
+        assert_eq!(price, Some(150));
+        assert_eq!(tx_index, Some(200));
+    }
+
+    #[sqlx::test]
+    async fn replayed_bid_is_applied_again(pool: PgPool) {
+        insert_bids(&pool, vec![bid(100, BidStatus::Active, 100)]).await;
+        insert_bids(&pool, vec![bid(100, BidStatus::Cancelled, 100)]).await;
+
+        let (status, _, tx_index) = fetch_bid(&pool).await;
+        assert_eq!(status, Some(BidStatus::Cancelled.to_string()));
+        assert_eq!(tx_index, Some(100));
+    }
+
+    #[sqlx::test]
+    async fn fills_count_towards_the_latest_create(pool: PgPool) {
+        insert_bids(&pool, vec![collection_bid(100, 10)]).await;
+        fill_bid(&pool, vec![fill(101), fill(102), fill(103)]).await;
+        assert_eq!(
+            fetch_remaining(&pool).await,
+            (Some(BidStatus::Active.to_string()), Some(7))
+        );
+
+        // The bidder creates the bid again under the same id
+        insert_bids(&pool, vec![collection_bid(200, 10)]).await;
+        fill_bid(&pool, vec![fill(201)]).await;
+        assert_eq!(
+            fetch_remaining(&pool).await,
+            (Some(BidStatus::Active.to_string()), Some(9))
+        );
+
+        // Replaying the first create doesn't bring its fills back
+        insert_bids(&pool, vec![collection_bid(100, 10)]).await;
+        fill_bid(&pool, Vec::new()).await;
+        assert_eq!(
+            fetch_remaining(&pool).await,
+            (Some(BidStatus::Active.to_string()), Some(9))
+        );
+    }
+
+    #[sqlx::test]
+    async fn cancelled_bid_stays_cancelled_after_fills(pool: PgPool) {
+        insert_bids(&pool, vec![collection_bid(100, 10)]).await;
+        fill_bid(&pool, vec![fill(101), fill(102), fill(103)]).await;
+        insert_bids(&pool, vec![bid(300, BidStatus::Cancelled, 100)]).await;
+        fill_bid(&pool, Vec::new()).await;
+
+        assert_eq!(
+            fetch_remaining(&pool).await,
+            (Some(BidStatus::Cancelled.to_string()), Some(7))
+        );
+    }
 }
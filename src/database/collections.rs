@@ -1,6 +1,7 @@
 use std::{collections::HashMap, sync::Arc};
 
 use crate::{
+    config::currency_config::APT_TOKEN_ADDR,
     database::Schema,
     models::{
         db::collection::DbCollection,
@@ -15,6 +16,7 @@ use crate::{
                 nft_distribution::{NftAmountDistributionSchema, NftPeriodDistributionSchema},
                 nft_holder::NftHolderSchema,
                 profit_leaderboard::ProfitLeaderboardSchema,
+                royalty::CollectionRoyaltySchema,
                 stat::CollectionStatSchema,
                 top_wallet::{TopWalletSchema, TopWalletType},
                 trending::{CollectionTrendingSchema, OrderTrendingType},
@@ -27,6 +29,7 @@ use crate::{
 };
 use anyhow::Context;
 use async_graphql::{FieldError, dataloader::Loader};
+use bigdecimal::BigDecimal;
 use chrono::{DateTime, Utc};
 use sqlx::{
     PgPool, Postgres, QueryBuilder, Transaction,
@@ -42,6 +45,15 @@ pub trait ICollections: Send + Sync {
         items: Vec<DbCollection>,
     ) -> anyhow::Result<PgQueryResult>;
 
+    /// Recomputes `volume_usd` of the collections from the usd prices of their sales
+    async fn refresh_volume_usd(&self, collection_ids: &[Uuid]) -> anyhow::Result<PgQueryResult>;
+
+    /// Royalty percentages of the collections that have one
+    async fn fetch_royalties(
+        &self,
+        collection_ids: &[Uuid],
+    ) -> anyhow::Result<HashMap<Uuid, BigDecimal>>;
+
     async fn fetch_collections(
         &self,
         query: &QueryCollectionSchema,
@@ -68,6 +80,11 @@ pub trait ICollections: Send + Sync {
 
     async fn fetch_stats(&self, collection_id: Uuid) -> anyhow::Result<CollectionStatSchema>;
 
+    async fn fetch_royalty_stats(
+        &self,
+        collection_id: Uuid,
+    ) -> anyhow::Result<CollectionRoyaltySchema>;
+
     async fn fetch_trending_nfts(
         &self,
         collection_id: Uuid,
@@ -226,6 +243,59 @@ impl ICollections for Collections {
         Ok(res)
     }
 
+    async fn refresh_volume_usd(&self, collection_ids: &[Uuid]) -> anyhow::Result<PgQueryResult> {
+        if collection_ids.is_empty() {
+            return Ok(PgQueryResult::default());
+        }
+
+        let res = sqlx::query(
+            r#"
+            UPDATE collections
+            SET volume_usd = sales.volume_usd
+            FROM (
+                SELECT
+                    a.collection_id,
+                    SUM(a.usd_price)    AS volume_usd
+                FROM activities a
+                WHERE a.tx_type IN ('buy', 'accept-bid', 'accept-collection-bid')
+                    AND a.collection_id = ANY($1)
+                GROUP BY a.collection_id
+            ) AS sales
+            WHERE collections.id = sales.collection_id
+            "#,
+        )
+        .bind(collection_ids)
+        .execute(&*self.pool)
+        .await
+        .context("Failed to refresh collection usd volumes")?;
+
+        Ok(res)
+    }
+
+    async fn fetch_royalties(
+        &self,
+        collection_ids: &[Uuid],
+    ) -> anyhow::Result<HashMap<Uuid, BigDecimal>> {
+        if collection_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let res = sqlx::query_as::<_, (Uuid, BigDecimal)>(
+            r#"
+            SELECT c.id, c.royalty
+            FROM collections c
+            WHERE c.id = ANY($1)
+                AND c.royalty IS NOT NULL
+            "#,
+        )
+        .bind(collection_ids)
+        .fetch_all(&*self.pool)
+        .await
+        .context("Failed to fetch collection royalties")?;
+
+        Ok(res.into_iter().collect())
+    }
+
     async fn fetch_collections(
         &self,
         query: &QueryCollectionSchema,
@@ -290,6 +360,8 @@ impl ICollections for Collections {
             CollectionTrendingSchema,
             r#"
             WITH
+                -- Floors, volumes and bids only compare APT prices, rows without a currency
+                -- were indexed before it was tracked and are priced in APT
                 previous_listings AS (
                     SELECT
                         collection_id, 
@@ -297,6 +369,7 @@ impl ICollections for Collections {
                         COUNT(*)                        AS total_listed
                     FROM listings
                     WHERE listed 
+                        AND (currency IS NULL OR currency = $5)
                         AND ($1::INTERVAL IS NULL OR block_time < NOW() - $1::INTERVAL)
                     GROUP BY collection_id
                 ),
@@ -307,13 +380,14 @@ impl ICollections for Collections {
                         COUNT(*)                        AS total_listed
                     FROM listings
                     WHERE listed 
+                        AND (currency IS NULL OR currency = $5)
                         AND ($1::INTERVAL IS NULL OR block_time >= NOW() - $1::INTERVAL)
                     GROUP BY collection_id
                 ),
                 previous_sale_activities AS (
                     SELECT
                         collection_id, 
-                        SUM(price)                      AS volume
+                        SUM(price) FILTER (WHERE currency IS NULL OR currency = $5)   AS volume
                     FROM activities
                     WHERE tx_type IN ('mint', 'buy', 'accept-bid', 'accept-collection-bid')
                         AND ($1::INTERVAL IS NULL
@@ -327,7 +401,7 @@ impl ICollections for Collections {
                 current_activities AS (
                     SELECT
                         collection_id, 
-                        SUM(price)                      AS volume,
+                        SUM(price) FILTER (WHERE currency IS NULL OR currency = $5)   AS volume,
                         SUM(
                             CASE
                                 WHEN price > 0 THEN 1
@@ -352,6 +426,7 @@ impl ICollections for Collections {
                         MAX(price)                      AS top_bid
                     FROM bids
                     WHERE status = 'active' 
+                        AND (currency IS NULL OR currency = $5)
                         AND (expired_at IS NULL OR expired_at > NOW())
                     GROUP BY collection_id
                 ),
@@ -405,6 +480,7 @@ impl ICollections for Collections {
             order.to_string(),
             limit,
             offset,
+            APT_TOKEN_ADDR,
         )
         .fetch_all(&*self.pool)
         .await
@@ -418,6 +494,7 @@ impl ICollections for Collections {
             CollectionStatSchema,
             r#"
             WITH
+                -- Bids and volumes only compare APT prices, like the floor
                 top_bids AS (
                     SELECT
                         b.collection_id,
@@ -426,13 +503,16 @@ impl ICollections for Collections {
                     WHERE b.collection_id = $1
                         AND b.status = 'active'
                         AND b.bid_type = 'solo'
-                        AND b.expired_at > NOW()
+                        AND (b.currency IS NULL OR b.currency = $2)
+                        AND (b.expired_at IS NULL OR b.expired_at > NOW())
                     GROUP BY b.collection_id
                 ),
                 total_sale_activities AS (
                     SELECT
                         activities.collection_id,
-                        SUM(activities.price)::BIGINT   AS volume,
+                        (SUM(activities.price) FILTER (
+                            WHERE activities.currency IS NULL OR activities.currency = $2
+                        ))::BIGINT                      AS volume,
                         COUNT(*)                        AS sales
                     FROM activities
                     WHERE activities.tx_type IN ('buy', 'accept-bid', 'accept-collection-bid')
@@ -442,7 +522,9 @@ impl ICollections for Collections {
                 sale_activities AS (
                     SELECT
                         activities.collection_id,
-                        SUM(activities.price)::BIGINT   AS volume,
+                        (SUM(activities.price) FILTER (
+                            WHERE activities.currency IS NULL OR activities.currency = $2
+                        ))::BIGINT                      AS volume,
                         COUNT(*)                        AS sales
                     FROM activities
                     WHERE activities.block_time >= NOW() - '24h'::INTERVAL
@@ -459,9 +541,8 @@ impl ICollections for Collections {
                     GROUP BY l.collection_id
                 ),
                 collection_owners AS (
-                    SELECT collection_id, COUNT(DISTINCT owner) FROM nfts
-                    WHERE collection_id = $1 
-                        AND (burned IS NULL OR NOT burned)
+                    SELECT collection_id, COUNT(DISTINCT owner) FROM nft_balances
+                    WHERE collection_id = $1 AND amount > 0
                     GROUP BY collection_id
                 ),
                 collection_scores AS (
@@ -494,6 +575,7 @@ impl ICollections for Collections {
             WHERE c.id = $1
             "#,
             collection_id,
+            APT_TOKEN_ADDR,
         )
         .fetch_one(&*self.pool)
         .await
@@ -502,6 +584,45 @@ impl ICollections for Collections {
         Ok(res)
     }
 
+    async fn fetch_royalty_stats(
+        &self,
+        collection_id: Uuid,
+    ) -> anyhow::Result<CollectionRoyaltySchema> {
+        let res = sqlx::query_as::<_, CollectionRoyaltySchema>(
+            r#"
+            WITH sales AS (
+                SELECT
+                    a.price,
+                    a.usd_price,
+                    a.marketplace_fee,
+                    a.royalty_amount,
+                    (a.currency IS NULL OR a.currency = $2) AS is_apt
+                FROM activities a
+                WHERE a.collection_id = $1
+                    AND a.tx_type IN ('buy', 'accept-bid', 'accept-collection-bid')
+                    AND a.royalty_amount IS NOT NULL
+            )
+            SELECT
+                COUNT(*)                                                        AS sales,
+                SUM(s.royalty_amount) FILTER (WHERE s.is_apt)::BIGINT           AS royalties_earned,
+                SUM(s.usd_price * s.royalty_amount / NULLIF(s.price, 0))        AS royalties_earned_usd,
+                SUM(s.marketplace_fee) FILTER (WHERE s.is_apt)::BIGINT          AS marketplace_fees,
+                (
+                    100 * SUM(s.royalty_amount) FILTER (WHERE s.is_apt)
+                    / NULLIF(SUM(s.price) FILTER (WHERE s.is_apt), 0)
+                )::NUMERIC                                                      AS royalty_paid_rate
+            FROM sales s
+            "#,
+        )
+        .bind(collection_id)
+        .bind(APT_TOKEN_ADDR)
+        .fetch_one(&*self.pool)
+        .await
+        .context("Failed to fetch collection royalty stats")?;
+
+        Ok(res)
+    }
+
     async fn fetch_trending_nfts(
         &self,
         collection_id: Uuid,
@@ -558,9 +679,9 @@ impl ICollections for Collections {
             r#"
             WITH 
                 current_nft_owners AS (
-                    SELECT n.owner, COUNT(*) FROM nfts n
-                    WHERE n.burned IS NULL OR NOT n.burned AND n.collection_id = $1
-                    GROUP BY n.collection_id, n.owner
+                    SELECT b.owner, SUM(b.amount)::BIGINT AS count FROM nft_balances b
+                    WHERE b.collection_id = $1 AND b.amount > 0
+                    GROUP BY b.owner
                 ),
                 transfer_in AS (
                     SELECT a.collection_id, a.receiver AS address, COUNT(*) FROM activities a
@@ -765,11 +886,11 @@ impl ICollections for Collections {
                 ),
                 nft_owners AS (
                     SELECT 
-                        n.owner     AS address,
-                        COUNT(*)    AS count
-                    FROM nfts n
-                    WHERE n.collection_id = $1 AND (n.burned IS NULL OR NOT n.burned)
-                    GROUP BY n.owner
+                        b.owner                 AS address,
+                        SUM(b.amount)::BIGINT   AS count
+                    FROM nft_balances b
+                    WHERE b.collection_id = $1 AND b.amount > 0
+                    GROUP BY b.owner
                 )
             SELECT 
                 no.address, 
@@ -803,9 +924,9 @@ impl ICollections for Collections {
             NftAmountDistributionSchema,
             r#"
             WITH nft_distributions AS (
-                SELECT n.collection_id, n.owner, COUNT(*) FROM nfts n
-                WHERE n.collection_id = $1
-                GROUP BY n.collection_id, n.owner
+                SELECT b.collection_id, b.owner, SUM(b.amount)::BIGINT AS count FROM nft_balances b
+                WHERE b.collection_id = $1 AND b.amount > 0
+                GROUP BY b.collection_id, b.owner
             )
             SELECT 
                 SUM(
@@ -934,9 +1055,9 @@ impl ICollections for Collections {
             r#"
             WITH 
                 current_holders AS (
-                    SELECT collection_id, owner, COUNT(*) AS count
-                    FROM nfts
-                    WHERE collection_id = $1 AND (burned IS NULL OR NOT burned)
+                    SELECT collection_id, owner, SUM(amount)::BIGINT AS count
+                    FROM nft_balances
+                    WHERE collection_id = $1 AND amount > 0
                     GROUP BY collection_id, owner
                 ),
                 sale_activities AS (
@@ -0,0 +1,202 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+use sqlx::{PgPool, Postgres, QueryBuilder, Transaction, postgres::PgQueryResult};
+use uuid::Uuid;
+
+use crate::models::{
+    api::requests::failed_event::FailedEventQuery,
+    db::failed_event::{DbFailedEvent, FailedEventStatus},
+};
+
+#[async_trait::async_trait]
+pub trait IFailedEvents: Send + Sync {
+    async fn tx_insert_failed_events(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        items: Vec<DbFailedEvent>,
+    ) -> anyhow::Result<PgQueryResult>;
+
+    async fn fetch_failed_events(
+        &self,
+        query: &FailedEventQuery,
+    ) -> anyhow::Result<Vec<DbFailedEvent>>;
+
+    async fn fetch_failed_event(&self, id: &Uuid) -> anyhow::Result<Option<DbFailedEvent>>;
+
+    async fn tx_resolve_failed_event(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        id: &Uuid,
+    ) -> anyhow::Result<PgQueryResult>;
+
+    async fn record_failed_attempt(&self, id: &Uuid, error: &str) -> anyhow::Result<PgQueryResult>;
+}
+
+pub struct FailedEvents {
+    pool: Arc<PgPool>,
+}
+
+impl FailedEvents {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait::async_trait]
+impl IFailedEvents for FailedEvents {
+    async fn tx_insert_failed_events(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        items: Vec<DbFailedEvent>,
+    ) -> anyhow::Result<PgQueryResult> {
+        if items.is_empty() {
+            return Ok(PgQueryResult::default());
+        }
+
+        let res = QueryBuilder::<Postgres>::new(
+            r#"
+            INSERT INTO failed_events (
+                id,
+                marketplace,
+                contract_address,
+                tx_id,
+                tx_version,
+                event_index,
+                event_type,
+                step,
+                error,
+                raw_event,
+                status,
+                attempts,
+                block_time,
+                block_height
+            )
+            "#,
+        )
+        .push_values(items, |mut b, item| {
+            b.push_bind(item.id);
+            b.push_bind(item.marketplace);
+            b.push_bind(item.contract_address);
+            b.push_bind(item.tx_id);
+            b.push_bind(item.tx_version);
+            b.push_bind(item.event_index);
+            b.push_bind(item.event_type);
+            b.push_bind(item.step);
+            b.push_bind(item.error);
+            b.push_bind(item.raw_event);
+            b.push_bind(item.status);
+            b.push_bind(item.attempts);
+            b.push_bind(item.block_time);
+            b.push_bind(item.block_height);
+        })
+        .push(
+            r#"
+            ON CONFLICT (id) DO UPDATE SET
+                step = EXCLUDED.step,
+                error = EXCLUDED.error,
+                raw_event = EXCLUDED.raw_event,
+                status = EXCLUDED.status,
+                attempts = failed_events.attempts + 1,
+                updated_at = NOW()
+            "#,
+        )
+        .build()
+        .execute(&mut **tx)
+        .await
+        .context("Failed to insert failed events")?;
+
+        Ok(res)
+    }
+
+    async fn fetch_failed_events(
+        &self,
+        query: &FailedEventQuery,
+    ) -> anyhow::Result<Vec<DbFailedEvent>> {
+        let mut query_builder = QueryBuilder::<Postgres>::new(
+            r#"
+            SELECT * FROM failed_events
+            WHERE TRUE
+            "#,
+        );
+
+        if let Some(marketplace) = query.marketplace.as_ref() {
+            query_builder
+                .push(" AND marketplace = ")
+                .push_bind(marketplace);
+        }
+
+        if let Some(status) = query.status.as_ref() {
+            query_builder.push(" AND status = ").push_bind(status);
+        }
+
+        if let Some(step) = query.step.as_ref() {
+            query_builder.push(" AND step = ").push_bind(step);
+        }
+
+        let res = query_builder
+            .push(" ORDER BY tx_version, event_index LIMIT ")
+            .push_bind(query.limit)
+            .push(" OFFSET ")
+            .push_bind(query.offset)
+            .build_query_as::<DbFailedEvent>()
+            .fetch_all(&*self.pool)
+            .await
+            .context("Failed to fetch failed events")?;
+
+        Ok(res)
+    }
+
+    async fn fetch_failed_event(&self, id: &Uuid) -> anyhow::Result<Option<DbFailedEvent>> {
+        let res = sqlx::query_as::<_, DbFailedEvent>(
+            r#"
+            SELECT * FROM failed_events
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&*self.pool)
+        .await
+        .context("Failed to fetch failed event")?;
+
+        Ok(res)
+    }
+
+    async fn tx_resolve_failed_event(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        id: &Uuid,
+    ) -> anyhow::Result<PgQueryResult> {
+        let res = sqlx::query(
+            r#"
+            UPDATE failed_events
+            SET status = $2, updated_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(FailedEventStatus::Resolved.to_string())
+        .execute(&mut **tx)
+        .await
+        .context("Failed to resolve failed event")?;
+
+        Ok(res)
+    }
+
+    async fn record_failed_attempt(&self, id: &Uuid, error: &str) -> anyhow::Result<PgQueryResult> {
+        let res = sqlx::query(
+            r#"
+            UPDATE failed_events
+            SET error = $2, attempts = attempts + 1, updated_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(error)
+        .execute(&*self.pool)
+        .await
+        .context("Failed to record failed event attempt")?;
+
+        Ok(res)
+    }
+}
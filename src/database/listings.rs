@@ -1,14 +1,21 @@
 use std::{collections::HashMap, sync::Arc};
 
+use crate::config::currency_config::APT_TOKEN_ADDR;
 use crate::database::Schema;
 use crate::models::schema::AggregateFieldsSchema;
 use crate::models::schema::listing::{
-    AggregateListingFieldsSchema, DistinctListingSchema, OrderListingSchema, QueryListingSchema,
+    AggregateListingFieldsSchema, AttributeFilterSchema, DistinctListingSchema, OrderListingSchema,
+    QueryListingSchema,
+};
+use crate::models::{
+    TX_INDEX_MULTIPLIER,
+    db::listing::{DbListing, DbListingInvalidation},
+    schema::listing::ListingSchema,
 };
-use crate::models::{db::listing::DbListing, schema::listing::ListingSchema};
 use crate::utils::schema::{create_aggregate_query_builder, create_query_builder};
 use anyhow::Context;
 use sqlx::{PgPool, Postgres, QueryBuilder, Transaction, postgres::PgQueryResult};
+use uuid::Uuid;
 
 #[async_trait::async_trait]
 pub trait IListings: Send + Sync {
@@ -18,6 +25,12 @@ pub trait IListings: Send + Sync {
         listings: Vec<DbListing>,
     ) -> anyhow::Result<PgQueryResult>;
 
+    async fn tx_invalidate_listings(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        invalidations: Vec<DbListingInvalidation>,
+    ) -> anyhow::Result<PgQueryResult>;
+
     async fn fetch_listings(
         &self,
         query: &QueryListingSchema,
@@ -33,6 +46,16 @@ pub trait IListings: Send + Sync {
         query: &QueryListingSchema,
         distinct: Option<&DistinctListingSchema>,
     ) -> anyhow::Result<AggregateFieldsSchema<AggregateListingFieldsSchema>>;
+
+    /// Cheapest valid APT listings of a collection, one per nft, while their cumulative
+    /// price stays within `budget`
+    async fn fetch_sweep_listings(
+        &self,
+        collection_id: Uuid,
+        count: i64,
+        budget: Option<i64>,
+        attributes: &[AttributeFilterSchema],
+    ) -> anyhow::Result<Vec<ListingSchema>>;
 }
 
 pub struct Listings {
@@ -70,7 +93,9 @@ impl IListings for Listings {
                 nonce,
                 price,
                 seller, 
-                tx_index
+                tx_index,
+                expired_at,
+                currency
             )
             "#,
         )
@@ -87,6 +112,8 @@ impl IListings for Listings {
             b.push_bind(item.price);
             b.push_bind(item.seller.clone());
             b.push_bind(item.tx_index);
+            b.push_bind(item.expired_at);
+            b.push_bind(item.currency);
         })
         .push(
             r#"
@@ -97,7 +124,11 @@ impl IListings for Listings {
                 listed = EXCLUDED.listed, 
                 nonce = EXCLUDED.nonce,
                 seller = EXCLUDED.seller,
-                tx_index = EXCLUDED.tx_index
+                tx_index = EXCLUDED.tx_index,
+                expired_at = EXCLUDED.expired_at,
+                currency = EXCLUDED.currency,
+                invalid_reason = NULL
+            WHERE listings.tx_index IS NULL OR EXCLUDED.tx_index >= listings.tx_index
             "#,
         )
         .build()
@@ -108,6 +139,64 @@ impl IListings for Listings {
         Ok(res)
     }
 
+    async fn tx_invalidate_listings(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        invalidations: Vec<DbListingInvalidation>,
+    ) -> anyhow::Result<PgQueryResult> {
+        if invalidations.is_empty() {
+            return Ok(PgQueryResult::default());
+        }
+
+        // Only listings whose last event is in an earlier transaction are closed, and the
+        // listing keeps the start of the invalidating transaction as its tx_index, so
+        // events of that same transaction still apply while older replays don't.
+        // Addresses are compared standardized, marketplaces emit them unpadded
+        let mut query_builder = QueryBuilder::<Postgres>::new(
+            r#"
+            UPDATE listings
+            SET
+                listed = FALSE,
+                invalid_reason = v.reason,
+                tx_index = (v.tx_index / "#,
+        );
+
+        let res = query_builder
+            .push_bind(TX_INDEX_MULTIPLIER)
+            .push(") * ")
+            .push_bind(TX_INDEX_MULTIPLIER)
+            .push(" FROM (")
+            .push_values(invalidations, |mut b, item| {
+            b.push_bind(item.nft_id);
+            b.push_bind(item.tx_index);
+            b.push_bind(item.reason);
+            b.push_bind(item.owner);
+            b.push_bind(item.market_contract_id);
+        })
+        .push(
+            r#"
+            ) AS v (nft_id, tx_index, reason, owner, market_contract_id)
+            WHERE listings.nft_id = v.nft_id
+                AND listings.listed
+                AND (v.owner IS NULL OR standardize_address(listings.seller) IS DISTINCT FROM standardize_address(v.owner))
+                AND (
+                    v.market_contract_id IS NULL
+                    OR standardize_address(listings.market_contract_id) IS DISTINCT FROM standardize_address(v.market_contract_id)
+                )
+                AND (listings.tx_index IS NULL OR listings.tx_index / "#,
+        )
+        .push_bind(TX_INDEX_MULTIPLIER)
+        .push(" < v.tx_index / ")
+        .push_bind(TX_INDEX_MULTIPLIER)
+        .push(")")
+        .build()
+        .execute(&mut **tx)
+        .await
+        .context("Failed to invalidate listings")?;
+
+        Ok(res)
+    }
+
     async fn fetch_listings(
         &self,
         query: &QueryListingSchema,
@@ -160,4 +249,133 @@ impl IListings for Listings {
 
         Ok(result)
     }
+
+    async fn fetch_sweep_listings(
+        &self,
+        collection_id: Uuid,
+        count: i64,
+        budget: Option<i64>,
+        attributes: &[AttributeFilterSchema],
+    ) -> anyhow::Result<Vec<ListingSchema>> {
+        // An nft listed on several marketplaces only counts its cheapest listing
+        let mut query_builder = QueryBuilder::<Postgres>::new(
+            r#"
+            WITH
+                candidates AS (
+                    SELECT DISTINCT ON (l.nft_id) l.*
+                    FROM listings l
+                        JOIN nfts n ON n.id = l.nft_id
+                    WHERE l.listed
+                        AND l.invalid_reason IS NULL
+                        AND l.price IS NOT NULL
+                        AND (l.expired_at IS NULL OR l.expired_at > NOW())
+                        AND (n.burned IS NULL OR NOT n.burned)
+                        AND (l.currency IS NULL OR l.currency = "#,
+        );
+
+        query_builder
+            .push_bind(APT_TOKEN_ADDR)
+            .push(") AND l.collection_id = ")
+            .push_bind(collection_id);
+
+        for attribute in attributes {
+            query_builder
+                .push(
+                    r#"
+                        AND EXISTS (
+                            SELECT 1 FROM attributes a
+                            WHERE a.nft_id = l.nft_id AND a.attr_type = "#,
+                )
+                .push_bind(attribute.attr_type.clone())
+                .push(" AND a.value = ANY(")
+                .push_bind(attribute.values.clone())
+                .push("))");
+        }
+
+        query_builder.push(
+            r#"
+                    ORDER BY l.nft_id, l.price ASC, l.block_time DESC
+                ),
+                sweep AS (
+                    SELECT
+                        c.*,
+                        SUM(c.price) OVER (ORDER BY c.price ASC, c.id) AS cumulative_price
+                    FROM candidates c
+                )
+            SELECT * FROM sweep s
+            "#,
+        );
+
+        if let Some(budget) = budget {
+            query_builder
+                .push(" WHERE s.cumulative_price <= ")
+                .push_bind(budget);
+        }
+
+        let res = query_builder
+            .push(" ORDER BY s.price ASC, s.id LIMIT ")
+            .push_bind(count)
+            .build_query_as::<ListingSchema>()
+            .fetch_all(&*self.pool)
+            .await
+            .context("Failed to fetch sweep listings")?;
+
+        Ok(res)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::DateTime;
+
+    fn listing(tx_index: i64, listed: bool, price: Option<i64>) -> DbListing {
+        DbListing {
+            id: Uuid::nil(),
+            // The collection floor trigger needs a collection
+            collection_id: Some(Uuid::nil()),
+            block_time: DateTime::from_timestamp(tx_index, 0).map(|ts| ts.naive_utc()),
+            listed: Some(listed),
+            price,
+            tx_index: Some(tx_index),
+            ..Default::default()
+        }
+    }
+
+    async fn insert_listings(pool: &PgPool, listings: Vec<DbListing>) {
+        let db = Listings::new(Arc::new(pool.clone()));
+        let mut tx = pool.begin().await.unwrap();
+        db.tx_insert_listings(&mut tx, listings).await.unwrap();
+        tx.commit().await.unwrap();
+    }
+
+    async fn fetch_listing(pool: &PgPool) -> (Option<bool>, Option<i64>, Option<i64>) {
+        sqlx::query_as("SELECT listed, price, tx_index FROM listings WHERE id = $1")
+            .bind(Uuid::nil())
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    #[sqlx::test]
+    async fn older_listing_keeps_the_newer_state(pool: PgPool) {
+        insert_listings(&pool, vec![listing(200, true, Some(150))]).await;
+        insert_listings(&pool, vec![listing(100, false, None)]).await;
+
+        let (listed, price, tx_index) = fetch_listing(&pool).await;
+        assert_eq!(listed, Some(true));
+        assert_eq!(price, Some(150));
+        assert_eq!(tx_index, Some(200));
+    }
+
+    #[sqlx::test]
+    async fn replayed_listing_is_applied_again(pool: PgPool) {
+        insert_listings(&pool, vec![listing(100, true, Some(100))]).await;
+        insert_listings(&pool, vec![listing(100, true, Some(120))]).await;
+
+        let (listed, price, tx_index) = fetch_listing(&pool).await;
+        assert_eq!(listed, Some(true));
+        assert_eq!(price, Some(120));
+        assert_eq!(tx_index, Some(100));
+    }
 }
@@ -0,0 +1,109 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+use sqlx::{PgPool, Postgres, QueryBuilder, postgres::PgQueryResult};
+
+use crate::models::db::marketplace_event::DbMarketplaceEvent;
+
+#[async_trait::async_trait]
+pub trait IMarketplaceEvents: Send + Sync {
+    async fn insert_marketplace_events(
+        &self,
+        items: Vec<DbMarketplaceEvent>,
+    ) -> anyhow::Result<PgQueryResult>;
+
+    async fn fetch_marketplace_events(
+        &self,
+        contract_address: &str,
+        start_version: i64,
+        end_version: i64,
+    ) -> anyhow::Result<Vec<DbMarketplaceEvent>>;
+}
+
+pub struct MarketplaceEvents {
+    pool: Arc<PgPool>,
+}
+
+impl MarketplaceEvents {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait::async_trait]
+impl IMarketplaceEvents for MarketplaceEvents {
+    async fn insert_marketplace_events(
+        &self,
+        items: Vec<DbMarketplaceEvent>,
+    ) -> anyhow::Result<PgQueryResult> {
+        if items.is_empty() {
+            return Ok(PgQueryResult::default());
+        }
+
+        let res = QueryBuilder::<Postgres>::new(
+            r#"
+            INSERT INTO marketplace_events (
+                contract_address,
+                tx_version,
+                event_index,
+                tx_id,
+                account_address,
+                creation_number,
+                sequence_number,
+                type_str,
+                data,
+                block_time,
+                block_height
+            )
+            "#,
+        )
+        .push_values(items, |mut b, item| {
+            b.push_bind(item.contract_address);
+            b.push_bind(item.tx_version);
+            b.push_bind(item.event_index);
+            b.push_bind(item.tx_id);
+            b.push_bind(item.account_address);
+            b.push_bind(item.creation_number);
+            b.push_bind(item.sequence_number);
+            b.push_bind(item.type_str);
+            b.push_bind(item.data);
+            b.push_bind(item.block_time);
+            b.push_bind(item.block_height);
+        })
+        .push(
+            r#"
+            ON CONFLICT (contract_address, tx_version, event_index) DO NOTHING
+            "#,
+        )
+        .build()
+        .execute(&*self.pool)
+        .await
+        .context("Failed to insert marketplace events")?;
+
+        Ok(res)
+    }
+
+    async fn fetch_marketplace_events(
+        &self,
+        contract_address: &str,
+        start_version: i64,
+        end_version: i64,
+    ) -> anyhow::Result<Vec<DbMarketplaceEvent>> {
+        let res = sqlx::query_as::<_, DbMarketplaceEvent>(
+            r#"
+            SELECT * FROM marketplace_events
+            WHERE contract_address = $1
+                AND tx_version BETWEEN $2 AND $3
+            ORDER BY tx_version, event_index
+            "#,
+        )
+        .bind(contract_address)
+        .bind(start_version)
+        .bind(end_version)
+        .fetch_all(&*self.pool)
+        .await
+        .context("Failed to fetch marketplace events")?;
+
+        Ok(res)
+    }
+}
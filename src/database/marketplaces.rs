@@ -39,6 +39,25 @@ impl IMarketplaces for Marketplaces {
             return Ok(PgQueryResult::default());
         }
 
+        let ids = items
+            .iter()
+            .map(|item| generate_marketplace_id(item.name.as_str(), item.contract_address.as_str()))
+            .collect::<Vec<_>>();
+        let names = items
+            .iter()
+            .map(|item| item.name.clone())
+            .collect::<Vec<_>>();
+
+        let mut tx = self.pool.begin().await?;
+
+        // Rows of earlier deployments or contracts of the same marketplace
+        sqlx::query("DELETE FROM marketplaces WHERE name = ANY($1) AND NOT id = ANY($2)")
+            .bind(&names)
+            .bind(&ids)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to remove stale marketplaces")?;
+
         let res = QueryBuilder::<Postgres>::new(
             r#"
             INSERT INTO marketplaces (id, name, contract_address)
@@ -58,10 +77,12 @@ impl IMarketplaces for Marketplaces {
             "#,
         )
         .build()
-        .execute(&*self.pool)
+        .execute(&mut *tx)
         .await
         .context("Failed to insert marketplaces")?;
 
+        tx.commit().await?;
+
         Ok(res)
     }
 
@@ -1,11 +1,16 @@
 pub mod activities;
 pub mod api_keys;
 pub mod attributes;
+pub mod auctions;
 pub mod bids;
 pub mod collections;
+pub mod failed_events;
 pub mod listings;
+pub mod marketplace_events;
 pub mod marketplaces;
+pub mod nft_balances;
 pub mod nft_metadata;
+pub mod nft_ownerships;
 pub mod nfts;
 pub mod processor_status;
 pub mod request_logs;
@@ -23,11 +28,16 @@ use crate::database::{
     activities::{Activities, IActivities},
     api_keys::{ApiKeys, IApiKeys},
     attributes::{Attributes, IAttributes},
+    auctions::{Auctions, IAuctions},
     bids::{Bids, IBids},
     collections::{Collections, ICollections},
+    failed_events::{FailedEvents, IFailedEvents},
     listings::{IListings, Listings},
+    marketplace_events::{IMarketplaceEvents, MarketplaceEvents},
     marketplaces::{IMarketplaces, Marketplaces},
+    nft_balances::{INftBalances, NftBalances},
     nft_metadata::{INFTMetadata, NFTMetadata},
+    nft_ownerships::{INftOwnerships, NftOwnerships},
     nfts::{INfts, Nfts},
     processor_status::{IProcessorStatus, ProcessorStatus},
     request_logs::{IRequestLogs, RequestLogs},
@@ -52,6 +62,11 @@ pub trait IDatabase: Send + Sync + 'static {
     type TUsers: IUsers;
     type TRequestLogs: IRequestLogs;
     type TApiKeys: IApiKeys;
+    type TFailedEvents: IFailedEvents;
+    type TMarketplaceEvents: IMarketplaceEvents;
+    type TAuctions: IAuctions;
+    type TNftOwnerships: INftOwnerships;
+    type TNftBalances: INftBalances;
 
     async fn is_healthy(&self) -> bool;
 
@@ -70,6 +85,11 @@ pub trait IDatabase: Send + Sync + 'static {
     fn users(&self) -> Arc<Self::TUsers>;
     fn request_logs(&self) -> Arc<Self::TRequestLogs>;
     fn api_keys(&self) -> Arc<Self::TApiKeys>;
+    fn failed_events(&self) -> Arc<Self::TFailedEvents>;
+    fn marketplace_events(&self) -> Arc<Self::TMarketplaceEvents>;
+    fn auctions(&self) -> Arc<Self::TAuctions>;
+    fn nft_ownerships(&self) -> Arc<Self::TNftOwnerships>;
+    fn nft_balances(&self) -> Arc<Self::TNftBalances>;
 }
 
 pub struct Database {
@@ -88,6 +108,11 @@ pub struct Database {
     users: Arc<Users>,
     request_logs: Arc<RequestLogs>,
     api_keys: Arc<ApiKeys>,
+    failed_events: Arc<FailedEvents>,
+    marketplace_events: Arc<MarketplaceEvents>,
+    auctions: Arc<Auctions>,
+    nft_ownerships: Arc<NftOwnerships>,
+    nft_balances: Arc<NftBalances>,
 }
 
 impl Database {
@@ -107,6 +132,11 @@ impl Database {
         users: Arc<Users>,
         request_logs: Arc<RequestLogs>,
         api_keys: Arc<ApiKeys>,
+        failed_events: Arc<FailedEvents>,
+        marketplace_events: Arc<MarketplaceEvents>,
+        auctions: Arc<Auctions>,
+        nft_ownerships: Arc<NftOwnerships>,
+        nft_balances: Arc<NftBalances>,
     ) -> Self {
         Self {
             pool,
@@ -124,6 +154,11 @@ impl Database {
             users,
             request_logs,
             api_keys,
+            failed_events,
+            marketplace_events,
+            auctions,
+            nft_ownerships,
+            nft_balances,
         }
     }
 
@@ -157,6 +192,11 @@ impl IDatabase for Database {
     type TUsers = Users;
     type TRequestLogs = RequestLogs;
     type TApiKeys = ApiKeys;
+    type TFailedEvents = FailedEvents;
+    type TMarketplaceEvents = MarketplaceEvents;
+    type TAuctions = Auctions;
+    type TNftOwnerships = NftOwnerships;
+    type TNftBalances = NftBalances;
 
     async fn is_healthy(&self) -> bool {
         sqlx::query("SELECT 1").fetch_one(&*self.pool).await.is_ok()
@@ -221,6 +261,26 @@ impl IDatabase for Database {
     fn api_keys(&self) -> Arc<Self::TApiKeys> {
         Arc::clone(&self.api_keys)
     }
+
+    fn failed_events(&self) -> Arc<Self::TFailedEvents> {
+        Arc::clone(&self.failed_events)
+    }
+
+    fn marketplace_events(&self) -> Arc<Self::TMarketplaceEvents> {
+        Arc::clone(&self.marketplace_events)
+    }
+
+    fn auctions(&self) -> Arc<Self::TAuctions> {
+        Arc::clone(&self.auctions)
+    }
+
+    fn nft_ownerships(&self) -> Arc<Self::TNftOwnerships> {
+        Arc::clone(&self.nft_ownerships)
+    }
+
+    fn nft_balances(&self) -> Arc<Self::TNftBalances> {
+        Arc::clone(&self.nft_balances)
+    }
 }
 
 #[derive(Debug, Clone, EnumString, Display, Serialize, Deserialize)]
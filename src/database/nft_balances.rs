@@ -0,0 +1,240 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+use sqlx::{PgPool, Postgres, QueryBuilder, Transaction, postgres::PgQueryResult};
+use uuid::Uuid;
+
+use crate::models::{TX_INDEX_MULTIPLIER, db::nft_balance_change::DbNftBalanceChange};
+
+#[async_trait::async_trait]
+pub trait INftBalances: Send + Sync {
+    /// Records the edition changes of v1 tokens, once each, and sums the balances of their
+    /// nfts again from every recorded change
+    async fn tx_apply_balances(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        items: Vec<DbNftBalanceChange>,
+    ) -> anyhow::Result<PgQueryResult>;
+
+    /// Replaces the balances of single edition nfts with one edition for their current
+    /// owner, none once burned
+    async fn tx_sync_balances(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        nft_ids: &[Uuid],
+    ) -> anyhow::Result<PgQueryResult>;
+}
+
+pub struct NftBalances {
+    pool: Arc<PgPool>,
+}
+
+impl NftBalances {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait::async_trait]
+impl INftBalances for NftBalances {
+    async fn tx_apply_balances(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        items: Vec<DbNftBalanceChange>,
+    ) -> anyhow::Result<PgQueryResult> {
+        if items.is_empty() {
+            return Ok(PgQueryResult::default());
+        }
+
+        let mut nft_ids = items.iter().map(|item| item.nft_id).collect::<Vec<_>>();
+        nft_ids.sort();
+        nft_ids.dedup();
+
+        QueryBuilder::<Postgres>::new(
+            r#"
+            INSERT INTO nft_balance_changes (
+                nft_id,
+                owner,
+                tx_index,
+                collection_id,
+                amount
+            )
+            "#,
+        )
+        .push_values(items, |mut b, item| {
+            b.push_bind(item.nft_id);
+            b.push_bind(item.owner);
+            b.push_bind(item.tx_index);
+            b.push_bind(item.collection_id);
+            b.push_bind(item.amount);
+        })
+        .push(
+            r#"
+            ON CONFLICT (nft_id, owner, tx_index) DO NOTHING
+            "#,
+        )
+        .build()
+        .execute(&mut **tx)
+        .await
+        .context("Failed to insert nft balance changes")?;
+
+        sqlx::query("DELETE FROM nft_balances WHERE nft_id = ANY($1)")
+            .bind(&nft_ids)
+            .execute(&mut **tx)
+            .await
+            .context("Failed to remove nft balances")?;
+
+        // Seeded balances stand for the history up to the version they were taken at, and
+        // give way to it once any of it is indexed again. Owners of editions the seed
+        // missed can sum below 0 until then, they hold none.
+        let res = sqlx::query(
+            r#"
+            INSERT INTO nft_balances (nft_id, owner, collection_id, amount, last_version)
+            SELECT
+                c.nft_id,
+                c.owner,
+                (ARRAY_AGG(c.collection_id) FILTER (WHERE c.collection_id IS NOT NULL))[1],
+                SUM(c.amount)::BIGINT,
+                MAX(c.tx_index) / $2
+            FROM nft_balance_changes c
+            WHERE c.nft_id = ANY($1)
+                AND (
+                    NOT c.seeded
+                    OR NOT EXISTS (
+                        SELECT 1 FROM nft_balance_changes r
+                        WHERE r.nft_id = c.nft_id AND NOT r.seeded AND r.tx_index <= c.tx_index
+                    )
+                )
+            GROUP BY c.nft_id, c.owner
+            HAVING SUM(c.amount) > 0
+            "#,
+        )
+        .bind(&nft_ids)
+        .bind(TX_INDEX_MULTIPLIER)
+        .execute(&mut **tx)
+        .await
+        .context("Failed to apply nft balances")?;
+
+        Ok(res)
+    }
+
+    async fn tx_sync_balances(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        nft_ids: &[Uuid],
+    ) -> anyhow::Result<PgQueryResult> {
+        if nft_ids.is_empty() {
+            return Ok(PgQueryResult::default());
+        }
+
+        sqlx::query("DELETE FROM nft_balances WHERE nft_id = ANY($1)")
+            .bind(nft_ids)
+            .execute(&mut **tx)
+            .await
+            .context("Failed to remove nft balances")?;
+
+        let res = sqlx::query(
+            r#"
+            INSERT INTO nft_balances (nft_id, owner, collection_id, amount, last_version)
+            SELECT o.nft_id, o.owner, o.collection_id, 1, o.from_version
+            FROM nft_ownerships o
+            WHERE o.nft_id = ANY($1) AND o.to_version IS NULL
+            ON CONFLICT (nft_id, owner) DO NOTHING
+            "#,
+        )
+        .bind(nft_ids)
+        .execute(&mut **tx)
+        .await
+        .context("Failed to sync nft balances")?;
+
+        Ok(res)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(owner: &str, version: i64, event_index: i64, amount: i64) -> DbNftBalanceChange {
+        DbNftBalanceChange {
+            nft_id: Uuid::nil(),
+            owner: owner.to_string(),
+            tx_index: version * TX_INDEX_MULTIPLIER + event_index,
+            collection_id: None,
+            amount,
+        }
+    }
+
+    async fn apply_balances(pool: &PgPool, changes: Vec<DbNftBalanceChange>) {
+        let db = NftBalances::new(Arc::new(pool.clone()));
+        let mut tx = pool.begin().await.unwrap();
+        db.tx_apply_balances(&mut tx, changes).await.unwrap();
+        tx.commit().await.unwrap();
+    }
+
+    async fn fetch_balances(pool: &PgPool) -> Vec<(String, i64)> {
+        sqlx::query_as("SELECT owner, amount FROM nft_balances WHERE nft_id = $1 ORDER BY owner")
+            .bind(Uuid::nil())
+            .fetch_all(pool)
+            .await
+            .unwrap()
+    }
+
+    #[sqlx::test]
+    async fn overlapping_batches_count_each_change_once(pool: PgPool) {
+        apply_balances(
+            &pool,
+            vec![
+                change("0x1", 10, 0, 1),
+                change("0x1", 20, 0, -1),
+                change("0x2", 20, 1, 1),
+            ],
+        )
+        .await;
+        apply_balances(
+            &pool,
+            vec![
+                change("0x1", 20, 0, -1),
+                change("0x2", 20, 1, 1),
+                change("0x2", 30, 0, -1),
+                change("0x3", 30, 1, 1),
+            ],
+        )
+        .await;
+
+        assert_eq!(fetch_balances(&pool).await, vec![("0x3".to_string(), 1)]);
+    }
+
+    #[sqlx::test]
+    async fn seeded_balance_gives_way_to_the_indexed_history(pool: PgPool) {
+        sqlx::query(
+            "INSERT INTO nft_balance_changes (nft_id, owner, tx_index, amount, seeded) VALUES ($1, '0x1', $2, 1, TRUE)",
+        )
+        .bind(Uuid::nil())
+        .bind(16 * TX_INDEX_MULTIPLIER - 1)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        apply_balances(
+            &pool,
+            vec![change("0x1", 20, 0, -1), change("0x2", 20, 1, 1)],
+        )
+        .await;
+        assert_eq!(fetch_balances(&pool).await, vec![("0x2".to_string(), 1)]);
+
+        apply_balances(
+            &pool,
+            vec![
+                change("0x1", 10, 0, 2),
+                change("0x1", 20, 0, -1),
+                change("0x2", 20, 1, 1),
+            ],
+        )
+        .await;
+        assert_eq!(
+            fetch_balances(&pool).await,
+            vec![("0x1".to_string(), 1), ("0x2".to_string(), 1)]
+        );
+    }
+}
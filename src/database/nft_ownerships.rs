@@ -0,0 +1,327 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use sqlx::{PgPool, Postgres, QueryBuilder, Transaction, postgres::PgQueryResult};
+use uuid::Uuid;
+
+use crate::models::{
+    TX_INDEX_MULTIPLIER, api::responses::holder_snapshot::SnapshotHolder,
+    db::nft_ownership::DbNftOwnership, schema::nft_ownership::NftOwnershipSchema,
+};
+
+/// Ownerships with the activity they were acquired by, preferring a sale of the same
+/// transaction to the token movement
+const OWNERSHIP_QUERY: &str = r#"
+    SELECT
+        o.nft_id,
+        o.collection_id,
+        o.owner,
+        o.tx_id,
+        o.from_version,
+        o.from_time,
+        o.to_version,
+        o.to_time,
+        o.activity_id,
+        a.tx_type       AS acquired_by,
+        a.price,
+        a.usd_price
+    FROM nft_ownerships o
+        LEFT JOIN LATERAL (
+            SELECT a.tx_type, a.price, a.usd_price FROM activities a
+            WHERE a.nft_id = o.nft_id AND a.tx_id = o.tx_id AND a.receiver = o.owner
+            ORDER BY a.tx_type IN ('buy', 'accept-bid', 'accept-collection-bid') DESC, a.tx_index
+            LIMIT 1
+        ) a ON TRUE
+"#;
+
+#[async_trait::async_trait]
+pub trait INftOwnerships: Send + Sync {
+    async fn tx_insert_ownerships(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        items: Vec<DbNftOwnership>,
+    ) -> anyhow::Result<PgQueryResult>;
+
+    /// Merges ownerships into the previous one of the same owner, then ends every ownership
+    /// of the nfts at the next one, or at a later burn
+    async fn tx_close_ownerships(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        nft_ids: &[Uuid],
+    ) -> anyhow::Result<PgQueryResult>;
+
+    /// Owners of an nft from its mint on
+    async fn fetch_provenance(
+        &self,
+        nft_id: Uuid,
+        limit: i64,
+        offset: i64,
+    ) -> anyhow::Result<Vec<NftOwnershipSchema>>;
+
+    /// Ownership of an nft at a version or a time, none before its mint or after its burn
+    async fn fetch_ownership_at(
+        &self,
+        nft_id: Uuid,
+        version: Option<i64>,
+        time: Option<DateTime<Utc>>,
+    ) -> anyhow::Result<Option<NftOwnershipSchema>>;
+
+    /// Token count of every holder of the collections at a version or a time, the current
+    /// holders when neither is set. Current holders count the editions they hold, past ones
+    /// count one per nft as ownerships don't keep amounts
+    async fn fetch_holder_snapshot(
+        &self,
+        collection_ids: &[Uuid],
+        version: Option<i64>,
+        time: Option<DateTime<Utc>>,
+        excluded: &[String],
+    ) -> anyhow::Result<Vec<SnapshotHolder>>;
+}
+
+pub struct NftOwnerships {
+    pool: Arc<PgPool>,
+}
+
+impl NftOwnerships {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait::async_trait]
+impl INftOwnerships for NftOwnerships {
+    async fn tx_insert_ownerships(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        items: Vec<DbNftOwnership>,
+    ) -> anyhow::Result<PgQueryResult> {
+        if items.is_empty() {
+            return Ok(PgQueryResult::default());
+        }
+
+        let res = QueryBuilder::<Postgres>::new(
+            r#"
+            INSERT INTO nft_ownerships (
+                nft_id,
+                tx_index,
+                collection_id,
+                owner,
+                tx_id,
+                activity_id,
+                from_version,
+                from_time
+            )
+            "#,
+        )
+        .push_values(items, |mut b, item| {
+            b.push_bind(item.nft_id);
+            b.push_bind(item.tx_index);
+            b.push_bind(item.collection_id);
+            b.push_bind(item.owner.clone());
+            b.push_bind(item.tx_id.clone());
+            b.push_bind(item.activity_id);
+            b.push_bind(item.get_version());
+            b.push_bind(item.block_time);
+        })
+        .push(
+            r#"
+            ON CONFLICT (nft_id, tx_index) DO NOTHING
+            "#,
+        )
+        .build()
+        .execute(&mut **tx)
+        .await
+        .context("Failed to insert nft ownerships")?;
+
+        Ok(res)
+    }
+
+    async fn tx_close_ownerships(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        nft_ids: &[Uuid],
+    ) -> anyhow::Result<PgQueryResult> {
+        if nft_ids.is_empty() {
+            return Ok(PgQueryResult::default());
+        }
+
+        // A v2 mint is followed by the transfer to the same receiver, and a deposit can
+        // follow one already stored from an earlier batch
+        sqlx::query(
+            r#"
+            DELETE FROM nft_ownerships o
+            USING (
+                SELECT
+                    o.nft_id,
+                    o.tx_index,
+                    o.owner,
+                    LAG(o.owner) OVER (PARTITION BY o.nft_id ORDER BY o.tx_index) AS previous_owner
+                FROM nft_ownerships o
+                WHERE o.nft_id = ANY($1)
+            ) p
+            WHERE o.nft_id = p.nft_id
+                AND o.tx_index = p.tx_index
+                AND o.owner = p.previous_owner
+            "#,
+        )
+        .bind(nft_ids)
+        .execute(&mut **tx)
+        .await
+        .context("Failed to merge nft ownerships")?;
+
+        // Recomputed over the whole history of the nfts, so replays and batches out of
+        // order still close every ownership at the right place
+        let res = sqlx::query(
+            r#"
+            UPDATE nft_ownerships o
+            SET
+                to_version = c.to_tx_index / $2,
+                to_time = c.to_time
+            FROM (
+                SELECT
+                    o.nft_id,
+                    o.tx_index,
+                    COALESCE(LEAD(o.tx_index) OVER w, burn.tx_index) AS to_tx_index,
+                    COALESCE(LEAD(o.from_time) OVER w, burn.block_time) AS to_time
+                FROM nft_ownerships o
+                    LEFT JOIN LATERAL (
+                        SELECT a.tx_index, a.block_time FROM activities a
+                        WHERE a.nft_id = o.nft_id AND a.tx_type = 'burn' AND a.tx_index > o.tx_index
+                        ORDER BY a.tx_index
+                        LIMIT 1
+                    ) burn ON TRUE
+                WHERE o.nft_id = ANY($1)
+                WINDOW w AS (PARTITION BY o.nft_id ORDER BY o.tx_index)
+            ) c
+            WHERE o.nft_id = c.nft_id
+                AND o.tx_index = c.tx_index
+                AND (o.to_version IS DISTINCT FROM c.to_tx_index / $2 OR o.to_time IS DISTINCT FROM c.to_time)
+            "#,
+        )
+        .bind(nft_ids)
+        .bind(TX_INDEX_MULTIPLIER)
+        .execute(&mut **tx)
+        .await
+        .context("Failed to close nft ownerships")?;
+
+        Ok(res)
+    }
+
+    async fn fetch_provenance(
+        &self,
+        nft_id: Uuid,
+        limit: i64,
+        offset: i64,
+    ) -> anyhow::Result<Vec<NftOwnershipSchema>> {
+        QueryBuilder::<Postgres>::new(OWNERSHIP_QUERY)
+            .push(" WHERE o.nft_id = ")
+            .push_bind(nft_id)
+            .push(" ORDER BY o.tx_index LIMIT ")
+            .push_bind(limit)
+            .push(" OFFSET ")
+            .push_bind(offset)
+            .build_query_as::<NftOwnershipSchema>()
+            .fetch_all(&*self.pool)
+            .await
+            .context("Failed to fetch nft provenance")
+    }
+
+    async fn fetch_ownership_at(
+        &self,
+        nft_id: Uuid,
+        version: Option<i64>,
+        time: Option<DateTime<Utc>>,
+    ) -> anyhow::Result<Option<NftOwnershipSchema>> {
+        let mut query_builder = QueryBuilder::<Postgres>::new(OWNERSHIP_QUERY);
+        query_builder.push(" WHERE o.nft_id = ").push_bind(nft_id);
+
+        if let Some(version) = version {
+            query_builder
+                .push(" AND o.from_version <= ")
+                .push_bind(version)
+                .push(" AND (o.to_version IS NULL OR o.to_version > ")
+                .push_bind(version)
+                .push(")");
+        }
+
+        if let Some(time) = time {
+            query_builder
+                .push(" AND o.from_time <= ")
+                .push_bind(time)
+                .push(" AND (o.to_time IS NULL OR o.to_time > ")
+                .push_bind(time)
+                .push(")");
+        }
+
+        query_builder
+            .push(" ORDER BY o.tx_index DESC LIMIT 1")
+            .build_query_as::<NftOwnershipSchema>()
+            .fetch_optional(&*self.pool)
+            .await
+            .context("Failed to fetch nft ownership")
+    }
+
+    async fn fetch_holder_snapshot(
+        &self,
+        collection_ids: &[Uuid],
+        version: Option<i64>,
+        time: Option<DateTime<Utc>>,
+        excluded: &[String],
+    ) -> anyhow::Result<Vec<SnapshotHolder>> {
+        if version.is_none() && time.is_none() {
+            return sqlx::query_as(
+                r#"
+                SELECT b.owner AS address, SUM(b.amount)::BIGINT AS token_count
+                FROM nft_balances b
+                WHERE b.collection_id = ANY($1) AND NOT b.owner = ANY($2) AND b.amount > 0
+                GROUP BY b.owner
+                ORDER BY token_count DESC, b.owner
+                "#,
+            )
+            .bind(collection_ids)
+            .bind(excluded)
+            .fetch_all(&*self.pool)
+            .await
+            .context("Failed to fetch holder snapshot");
+        }
+
+        let mut query_builder = QueryBuilder::<Postgres>::new(
+            r#"
+            SELECT o.owner AS address, COUNT(*) AS token_count
+            FROM nft_ownerships o
+            WHERE o.collection_id = ANY(
+            "#,
+        );
+        query_builder
+            .push_bind(collection_ids)
+            .push(") AND NOT o.owner = ANY(")
+            .push_bind(excluded)
+            .push(")");
+
+        if let Some(version) = version {
+            query_builder
+                .push(" AND o.from_version <= ")
+                .push_bind(version)
+                .push(" AND (o.to_version IS NULL OR o.to_version > ")
+                .push_bind(version)
+                .push(")");
+        }
+
+        if let Some(time) = time {
+            query_builder
+                .push(" AND o.from_time <= ")
+                .push_bind(time)
+                .push(" AND (o.to_time IS NULL OR o.to_time > ")
+                .push_bind(time)
+                .push(")");
+        }
+
+        query_builder
+            .push(" GROUP BY o.owner ORDER BY token_count DESC, o.owner")
+            .build_query_as::<SnapshotHolder>()
+            .fetch_all(&*self.pool)
+            .await
+            .context("Failed to fetch holder snapshot")
+    }
+}
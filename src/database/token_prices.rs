@@ -3,14 +3,32 @@ use std::sync::Arc;
 use crate::models::db::token_price::DbTokenPrice;
 use anyhow::Context;
 use bigdecimal::BigDecimal;
-use sqlx::{PgPool, postgres::PgQueryResult};
+use chrono::{DateTime, Utc};
+use sqlx::{PgPool, Postgres, QueryBuilder, postgres::PgQueryResult};
 
 #[async_trait::async_trait]
 pub trait ITokenPrices: Send + Sync {
     async fn insert_token_price(&self, token_price: &DbTokenPrice)
     -> anyhow::Result<PgQueryResult>;
 
+    async fn insert_token_prices(&self, items: Vec<DbTokenPrice>) -> anyhow::Result<PgQueryResult>;
+
+    /// Fetches the times of the stored prices of a token in `[start_time, end_time]`
+    async fn fetch_token_price_times(
+        &self,
+        token_addr: &str,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+    ) -> anyhow::Result<Vec<DateTime<Utc>>>;
+
     async fn fetch_token_price(&self, token_addr: &str) -> anyhow::Result<BigDecimal>;
+
+    /// Fetches the price nearest to `at`, along with its distance from `at` in seconds
+    async fn fetch_token_price_at(
+        &self,
+        token_addr: &str,
+        at: DateTime<Utc>,
+    ) -> anyhow::Result<Option<(BigDecimal, i64)>>;
 }
 
 pub struct TokenPrices {
@@ -46,6 +64,56 @@ impl ITokenPrices for TokenPrices {
         Ok(res)
     }
 
+    async fn insert_token_prices(&self, items: Vec<DbTokenPrice>) -> anyhow::Result<PgQueryResult> {
+        if items.is_empty() {
+            return Ok(PgQueryResult::default());
+        }
+
+        let res = QueryBuilder::<Postgres>::new(
+            r#"
+            INSERT INTO token_prices (token_address, price, created_at)
+            "#,
+        )
+        .push_values(items, |mut b, item| {
+            b.push_bind(item.token_address);
+            b.push_bind(item.price);
+            b.push_bind(item.created_at);
+        })
+        .push(
+            r#"
+            ON CONFLICT (token_address, created_at) DO NOTHING
+            "#,
+        )
+        .build()
+        .execute(&*self.pool)
+        .await
+        .context("Failed to insert token prices")?;
+
+        Ok(res)
+    }
+
+    async fn fetch_token_price_times(
+        &self,
+        token_addr: &str,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+    ) -> anyhow::Result<Vec<DateTime<Utc>>> {
+        sqlx::query_scalar::<_, DateTime<Utc>>(
+            r#"
+            SELECT tp.created_at FROM token_prices tp
+            WHERE tp.token_address = $1
+                AND tp.created_at BETWEEN $2 AND $3
+            ORDER BY tp.created_at
+            "#,
+        )
+        .bind(token_addr)
+        .bind(start_time)
+        .bind(end_time)
+        .fetch_all(&*self.pool)
+        .await
+        .context("Failed to fetch token price times")
+    }
+
     async fn fetch_token_price(&self, token_addr: &str) -> anyhow::Result<BigDecimal> {
         let res = sqlx::query!(
             r#"
@@ -62,4 +130,42 @@ impl ITokenPrices for TokenPrices {
 
         Ok(res.price)
     }
+
+    async fn fetch_token_price_at(
+        &self,
+        token_addr: &str,
+        at: DateTime<Utc>,
+    ) -> anyhow::Result<Option<(BigDecimal, i64)>> {
+        let res = sqlx::query_as::<_, (BigDecimal, i64)>(
+            r#"
+            SELECT
+                nearest.price,
+                ABS(EXTRACT(EPOCH FROM nearest.created_at - $2))::BIGINT AS gap
+            FROM (
+                (
+                    SELECT tp.price, tp.created_at FROM token_prices tp
+                    WHERE tp.token_address = $1 AND tp.created_at <= $2
+                    ORDER BY tp.created_at DESC
+                    LIMIT 1
+                )
+                UNION ALL
+                (
+                    SELECT tp.price, tp.created_at FROM token_prices tp
+                    WHERE tp.token_address = $1 AND tp.created_at > $2
+                    ORDER BY tp.created_at ASC
+                    LIMIT 1
+                )
+            ) AS nearest
+            ORDER BY gap
+            LIMIT 1
+            "#,
+        )
+        .bind(token_addr)
+        .bind(at)
+        .fetch_optional(&*self.pool)
+        .await
+        .context("Failed to fetch token price at time")?;
+
+        Ok(res)
+    }
 }
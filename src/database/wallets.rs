@@ -76,9 +76,9 @@ impl IWallets for Wallets {
                     ORDER BY tp.token_address, tp.created_at DESC
                 ),
                 wallet_nfts AS (
-                    SELECT n.owner, COUNT(*) FROM nfts n
-                    WHERE n.owner = $1
-                    GROUP BY n.owner
+                    SELECT b.owner, COUNT(*) FROM nft_balances b
+                    WHERE b.owner = $1 AND b.amount > 0
+                    GROUP BY b.owner
                 ),
                 traded_activities AS (
                         SELECT
@@ -143,17 +143,17 @@ impl IWallets for Wallets {
         &self,
         address: &str,
     ) -> anyhow::Result<Vec<NftHoldingPeriodSchema>> {
+        // Every ownership counts on its own, an nft coming back to the wallet included, and
+        // nfts still held count up to now rather than as 0
         let res = sqlx::query_as!(
             NftHoldingPeriodSchema,
             r#"
             SELECT
-                ra.collection_id,
-                ra.nft_id,
-                COALESCE(EXTRACT(EPOCH FROM sa.block_time), EXTRACT(EPOCH FROM ra.block_time)) -
-                    EXTRACT(EPOCH FROM ra.block_time) AS period 
-            FROM activities ra
-                LEFT JOIN activities sa ON ra.receiver = sa.sender AND ra.nft_id = sa.nft_id AND ra.collection_id = sa.collection_id
-            WHERE ra.receiver IS NOT NULL AND ra.receiver = $1 AND ra.tx_type IN ('transfer', 'buy', 'mint', 'accept-bid', 'accept-collection-bid')
+                o.collection_id,
+                o.nft_id,
+                EXTRACT(EPOCH FROM COALESCE(o.to_time, NOW()) - o.from_time) AS period
+            FROM nft_ownerships o
+            WHERE o.owner = $1
             ORDER BY period DESC
             LIMIT 10
             "#,
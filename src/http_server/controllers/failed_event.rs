@@ -0,0 +1,209 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    response::{IntoResponse, Response},
+};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    cache::ICache,
+    config::{
+        PriceHistoryConfig, currency_config::CurrencyConfig,
+        marketplace_config::NFTMarketplaceConfig,
+    },
+    database::{IDatabase, failed_events::IFailedEvents},
+    http_server::{
+        controllers::{InternalState, user::ADMIN_TAG},
+        utils::{
+            err_handler::{
+                response_400_with_message, response_404_unhandled_err, response_404_with_message,
+                response_429_unhandled_err,
+            },
+            validator::QueryValidator,
+        },
+    },
+    models::{
+        api::{
+            requests::failed_event::{FailedEventQuery, RedriveFailedEvents},
+            responses::failed_event::{RedriveError, RedriveFailedEventsResponse},
+        },
+        db::failed_event::{DbFailedEvent, FailedEventStatus},
+    },
+    workers::steps::marketplace::redrive::redrive_failed_event,
+};
+
+#[utoipa::path(
+    get,
+    path = "/failed-events",
+    tag = ADMIN_TAG,
+    params(
+        ("marketplace" = Option<String>, Query),
+        ("status" = Option<String>, Query, description = "pending or resolved"),
+        ("step" = Option<String>, Query, description = "remapping or reduction"),
+        ("limit" = Option<i64>, Query),
+        ("offset" = Option<i64>, Query)
+    ),
+    responses(
+        (status = 200, description = "Returns a list of events that failed to process", body = [DbFailedEvent])
+    ),
+    security(
+        ("BearerAuth" = [])
+    )
+)]
+pub async fn fetch_failed_events<TDb: IDatabase, TCache: ICache>(
+    State(state): InternalState<TDb, TCache>,
+    QueryValidator(query): QueryValidator<FailedEventQuery>,
+) -> Response {
+    match state.db.failed_events().fetch_failed_events(&query).await {
+        Ok(data) => Json(data).into_response(),
+        Err(e) => response_404_unhandled_err(e),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/failed-events/{id}",
+    tag = ADMIN_TAG,
+    params(
+        ("id" = Uuid, Path, description = "Failed event id")
+    ),
+    responses(
+        (status = 200, description = "Returns the failed event with its raw event", body = DbFailedEvent)
+    ),
+    security(
+        ("BearerAuth" = [])
+    )
+)]
+pub async fn fetch_failed_event<TDb: IDatabase, TCache: ICache>(
+    State(state): InternalState<TDb, TCache>,
+    Path(id): Path<Uuid>,
+) -> Response {
+    match state.db.failed_events().fetch_failed_event(&id).await {
+        Ok(Some(data)) => Json(data).into_response(),
+        Ok(None) => response_404_with_message("Failed event not found"),
+        Err(e) => response_404_unhandled_err(e),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/failed-events/{id}/redrive",
+    tag = ADMIN_TAG,
+    params(
+        ("id" = Uuid, Path, description = "Failed event id")
+    ),
+    responses(
+        (status = 200, description = "Re-runs the event through the current marketplace config", body = RedriveFailedEventsResponse)
+    ),
+    security(
+        ("BearerAuth" = [])
+    )
+)]
+pub async fn redrive_event<TDb: IDatabase, TCache: ICache>(
+    State(state): InternalState<TDb, TCache>,
+    Path(id): Path<Uuid>,
+) -> Response {
+    let failed_event = match state.db.failed_events().fetch_failed_event(&id).await {
+        Ok(Some(failed_event)) => failed_event,
+        Ok(None) => return response_404_with_message("Failed event not found"),
+        Err(e) => return response_404_unhandled_err(e),
+    };
+
+    if failed_event.status != FailedEventStatus::Pending.to_string() {
+        return response_400_with_message("Failed event is already resolved");
+    }
+
+    Json(
+        redrive(
+            state.db.as_ref(),
+            &state.config.nft_marketplace_configs,
+            &state.config.currencies,
+            &state.config.price_history_config,
+            vec![failed_event],
+        )
+        .await,
+    )
+    .into_response()
+}
+
+#[utoipa::path(
+    post,
+    path = "/failed-events/redrive",
+    tag = ADMIN_TAG,
+    responses(
+        (status = 200, description = "Re-runs the pending events through the current marketplace configs", body = RedriveFailedEventsResponse)
+    ),
+    security(
+        ("BearerAuth" = [])
+    )
+)]
+pub async fn redrive_events<TDb: IDatabase, TCache: ICache>(
+    State(state): InternalState<TDb, TCache>,
+    Json(req): Json<RedriveFailedEvents>,
+) -> Response {
+    if let Err(e) = req.validate() {
+        return response_400_with_message(&e.to_string());
+    }
+
+    let query = FailedEventQuery {
+        marketplace: req.marketplace,
+        status: Some(FailedEventStatus::Pending.to_string()),
+        step: None,
+        limit: req.limit,
+        offset: 0,
+    };
+
+    let failed_events = match state.db.failed_events().fetch_failed_events(&query).await {
+        Ok(failed_events) => failed_events,
+        Err(e) => return response_429_unhandled_err(e),
+    };
+
+    Json(
+        redrive(
+            state.db.as_ref(),
+            &state.config.nft_marketplace_configs,
+            &state.config.currencies,
+            &state.config.price_history_config,
+            failed_events,
+        )
+        .await,
+    )
+    .into_response()
+}
+
+async fn redrive<TDb: IDatabase>(
+    db: &TDb,
+    configs: &[NFTMarketplaceConfig],
+    currencies: &[CurrencyConfig],
+    price_history: &PriceHistoryConfig,
+    failed_events: Vec<DbFailedEvent>,
+) -> RedriveFailedEventsResponse {
+    let mut res = RedriveFailedEventsResponse {
+        resolved: Vec::new(),
+        failed: Vec::new(),
+    };
+
+    for failed_event in failed_events {
+        match redrive_failed_event(db, configs, currencies, price_history, &failed_event).await {
+            Ok(()) => res.resolved.push(failed_event.id),
+            Err(e) => {
+                let error = format!("{e:#}");
+                if let Err(e) = db
+                    .failed_events()
+                    .record_failed_attempt(&failed_event.id, &error)
+                    .await
+                {
+                    tracing::error!("Failed to record failed event attempt: {e:#}");
+                }
+
+                res.failed.push(RedriveError {
+                    id: failed_event.id,
+                    error,
+                });
+            }
+        }
+    }
+
+    res
+}
@@ -0,0 +1,214 @@
+use std::collections::HashMap;
+
+use axum::{
+    Json,
+    extract::State,
+    response::{IntoResponse, Response},
+};
+use validator::Validate;
+
+use crate::{
+    cache::ICache,
+    config::validation::IssueSeverity,
+    database::IDatabase,
+    http_server::{
+        controllers::{InternalState, user::ADMIN_TAG},
+        utils::err_handler::{
+            response_400_with_message, response_404_with_message, response_429_unhandled_err,
+        },
+    },
+    models::{
+        api::{
+            requests::{dry_run_remap::DryRunRemap, rederive_events::RederiveEvents},
+            responses::{
+                dry_run_remap::DryRunRemapResponse, rederive_events::RederiveEventsResponse,
+            },
+        },
+        db::failed_event::DbFailedEvent,
+    },
+    workers::steps::marketplace::{
+        rederive::{deployment_versions, rederive_events},
+        reduction_step::{NFTAccumulator, apply_currency, apply_resource_updates},
+        remapping_step::RemappingStep,
+        royalties::apply_royalty,
+    },
+};
+
+#[utoipa::path(
+    post,
+    path = "/marketplace/dry-run",
+    tag = ADMIN_TAG,
+    responses(
+        (status = 200, description = "Returns the resolved config, the remapped activities and derived rows without writing them", body = DryRunRemapResponse)
+    ),
+    security(
+        ("BearerAuth" = [])
+    )
+)]
+pub async fn dry_run_remap<TDb: IDatabase, TCache: ICache>(
+    State(state): InternalState<TDb, TCache>,
+    Json(req): Json<DryRunRemap>,
+) -> Response {
+    if let Err(e) = req.validate() {
+        return response_400_with_message(&e.to_string());
+    }
+
+    if req.transactions.iter().any(|txn| txn.timestamp.is_none()) {
+        return response_400_with_message("Every transaction must have a timestamp");
+    }
+
+    let config = match req.config.resolve(&state.config.nft_marketplace_configs) {
+        Ok(config) => config,
+        Err(e) => return response_400_with_message(&format!("{e:#}")),
+    };
+
+    let issues = config.validate();
+    let errors = issues
+        .iter()
+        .filter(|issue| issue.severity == IssueSeverity::Error)
+        .map(|issue| issue.to_string())
+        .collect::<Vec<_>>();
+
+    if !errors.is_empty() {
+        return response_400_with_message(&errors.join("; "));
+    }
+
+    let remapping_step = match RemappingStep::new(config.clone()) {
+        Ok(step) => step,
+        Err(e) => return response_429_unhandled_err(e),
+    };
+
+    let (activities, resource_updates, mut failed_events) =
+        match remapping_step.remap(&req.transactions) {
+            Ok(result) => result,
+            Err(e) => return response_429_unhandled_err(e),
+        };
+
+    let mut accumulator = NFTAccumulator::default();
+    let activities = activities
+        .into_iter()
+        .map(|mut activity| {
+            apply_resource_updates(&mut activity, &resource_updates);
+            apply_currency(&mut activity, &state.config.currencies);
+            // Without the collection royalties, the remainder of the price is the royalty
+            apply_royalty(&mut activity, &HashMap::new());
+            if let Err(e) = accumulator.fold(&activity) {
+                failed_events.push(DbFailedEvent::from_activity(&activity, &e));
+            }
+            activity
+        })
+        .collect::<Vec<_>>();
+
+    let (_, bids, bid_fills, listings, auctions, collections, nfts) = accumulator.drain();
+
+    Json(DryRunRemapResponse {
+        config,
+        issues,
+        activities,
+        listings,
+        bids,
+        bid_fills,
+        auctions,
+        collections,
+        nfts,
+        failed_events,
+    })
+    .into_response()
+}
+
+#[utoipa::path(
+    post,
+    path = "/marketplace/re-derive",
+    tag = ADMIN_TAG,
+    responses(
+        (status = 200, description = "Starts re-deriving the archived events of a marketplace with its current config", body = RederiveEventsResponse)
+    ),
+    security(
+        ("BearerAuth" = [])
+    )
+)]
+pub async fn rederive_marketplace_events<TDb: IDatabase, TCache: ICache>(
+    State(state): InternalState<TDb, TCache>,
+    Json(req): Json<RederiveEvents>,
+) -> Response {
+    if let Err(e) = req.validate() {
+        return response_400_with_message(&e.to_string());
+    }
+
+    if req.start_version > req.end_version {
+        return response_400_with_message("start_version must not be greater than end_version");
+    }
+
+    let deployments = state
+        .config
+        .nft_marketplace_configs
+        .iter()
+        .flat_map(|config| config.get_deployments())
+        .filter(|(_, config)| {
+            config.name == req.marketplace
+                && req
+                    .contract_address
+                    .as_ref()
+                    .is_none_or(|addr| *addr == config.contract_address)
+        })
+        .collect::<Vec<_>>();
+
+    if deployments.is_empty() {
+        return response_404_with_message("Marketplace not found");
+    }
+
+    // Each deployment only re-derives the versions it indexed
+    let deployments = deployments
+        .into_iter()
+        .filter_map(|(name, config)| {
+            let versions = deployment_versions(&config, req.start_version, req.end_version)?;
+            Some((name, config, versions))
+        })
+        .collect::<Vec<_>>();
+
+    if deployments.is_empty() {
+        return response_400_with_message("No deployment indexed versions in the range");
+    }
+
+    let names = deployments
+        .iter()
+        .map(|(name, _, _)| name.clone())
+        .collect::<Vec<_>>();
+
+    // Re-deriving a large range outlives the request timeout, so run it in the background
+    let db = state.db.clone();
+    let currencies = state.config.currencies.clone();
+    let price_history = state.config.price_history_config.clone();
+    tokio::spawn(async move {
+        for (processor_name, config, (start_version, end_version)) in deployments {
+            match rederive_events(
+                db.as_ref(),
+                &config,
+                &currencies,
+                &price_history,
+                start_version,
+                end_version,
+            )
+            .await
+            {
+                Ok(stats) => tracing::info!(
+                    processor = %processor_name,
+                    events = stats.events,
+                    activities = stats.activities,
+                    failed_events = stats.failed_events,
+                    "Re-derived versions [{start_version}, {end_version}]"
+                ),
+                Err(e) => tracing::error!(
+                    processor = %processor_name,
+                    "Failed to re-derive versions [{start_version}, {end_version}]: {e:#}"
+                ),
+            }
+        }
+    });
+
+    Json(RederiveEventsResponse {
+        deployments: names,
+        message: "Re-derive started".to_string(),
+    })
+    .into_response()
+}
@@ -9,8 +9,12 @@ use std::sync::Arc;
 
 pub mod api_key;
 pub mod auth;
+pub mod failed_event;
 pub mod health;
+pub mod marketplace;
+pub mod price;
 pub mod request_log;
+pub mod snapshot;
 pub mod user;
 
 type InternalState<TDb, TCache> = State<Arc<HttpServer<TDb, TCache>>>;
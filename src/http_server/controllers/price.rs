@@ -0,0 +1,158 @@
+use axum::{
+    Json,
+    extract::State,
+    response::{IntoResponse, Response},
+};
+use chrono::DateTime;
+use validator::Validate;
+
+use crate::{
+    cache::ICache,
+    database::IDatabase,
+    http_server::{
+        controllers::{InternalState, user::ADMIN_TAG},
+        utils::err_handler::{response_400_with_message, response_404_with_message},
+    },
+    models::api::{
+        requests::{
+            backfill_token_prices::BackfillTokenPrices, recompute_usd_prices::RecomputeUsdPrices,
+        },
+        responses::{
+            backfill_token_prices::BackfillTokenPricesResponse,
+            recompute_usd_prices::RecomputeUsdPricesResponse,
+        },
+    },
+    workers::{
+        price_indexer::backfill_token_prices, steps::marketplace::usd_pricing::recompute_usd_prices,
+    },
+};
+
+#[utoipa::path(
+    post,
+    path = "/prices/backfill",
+    tag = ADMIN_TAG,
+    responses(
+        (status = 200, description = "Starts filling the gaps of the price history from the price sources that keep one", body = BackfillTokenPricesResponse)
+    ),
+    security(
+        ("BearerAuth" = [])
+    )
+)]
+pub async fn backfill<TDb: IDatabase, TCache: ICache>(
+    State(state): InternalState<TDb, TCache>,
+    Json(req): Json<BackfillTokenPrices>,
+) -> Response {
+    if let Err(e) = req.validate() {
+        return response_400_with_message(&e.to_string());
+    }
+
+    let (start_time, end_time) = match (
+        DateTime::from_timestamp(req.start_time, 0),
+        DateTime::from_timestamp(req.end_time, 0),
+    ) {
+        (Some(start_time), Some(end_time)) if start_time <= end_time => (start_time, end_time),
+        _ => return response_400_with_message("start_time must not be greater than end_time"),
+    };
+
+    let currencies = state
+        .config
+        .currencies
+        .iter()
+        .filter(|currency| {
+            req.currency
+                .as_ref()
+                .is_none_or(|c| c.eq_ignore_ascii_case(&currency.symbol) || currency.matches(c))
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+
+    if currencies.is_empty() {
+        return response_404_with_message("Currency not found");
+    }
+
+    let symbols = currencies
+        .iter()
+        .map(|currency| currency.symbol.clone())
+        .collect::<Vec<_>>();
+
+    // Fetching a long history outlives the request timeout, so run it in the background
+    let db = state.db.clone();
+    let config = state.config.price_indexer_config.clone();
+    tokio::spawn(async move {
+        match backfill_token_prices(db.as_ref(), &config, &currencies, start_time, end_time).await {
+            Ok(stats) => tracing::info!(
+                currencies = stats.currencies,
+                prices = stats.prices,
+                "Backfilled token prices [{start_time}, {end_time}]"
+            ),
+            Err(e) => {
+                tracing::error!("Failed to backfill token prices [{start_time}, {end_time}]: {e:#}")
+            }
+        }
+    });
+
+    Json(BackfillTokenPricesResponse {
+        currencies: symbols,
+        message: "Price backfill started".to_string(),
+    })
+    .into_response()
+}
+
+#[utoipa::path(
+    post,
+    path = "/prices/recompute-usd",
+    tag = ADMIN_TAG,
+    responses(
+        (status = 200, description = "Starts recomputing the usd prices of activities and the usd volume of collections from the price history", body = RecomputeUsdPricesResponse)
+    ),
+    security(
+        ("BearerAuth" = [])
+    )
+)]
+pub async fn recompute_usd<TDb: IDatabase, TCache: ICache>(
+    State(state): InternalState<TDb, TCache>,
+    Json(req): Json<RecomputeUsdPrices>,
+) -> Response {
+    if let Err(e) = req.validate() {
+        return response_400_with_message(&e.to_string());
+    }
+
+    let start_time = req
+        .start_time
+        .and_then(|ts| DateTime::from_timestamp(ts, 0));
+    let end_time = req.end_time.and_then(|ts| DateTime::from_timestamp(ts, 0));
+
+    if let (Some(start_time), Some(end_time)) = (start_time, end_time) {
+        if start_time > end_time {
+            return response_400_with_message("start_time must not be greater than end_time");
+        }
+    }
+
+    // Re-pricing the whole history outlives the request timeout, so run it in the background
+    let db = state.db.clone();
+    let currencies = state.config.currencies.clone();
+    let price_history = state.config.price_history_config.clone();
+    tokio::spawn(async move {
+        match recompute_usd_prices(
+            db.as_ref(),
+            &currencies,
+            &price_history,
+            start_time,
+            end_time,
+        )
+        .await
+        {
+            Ok(stats) => tracing::info!(
+                activities = stats.activities,
+                collections = stats.collections,
+                "Recomputed usd prices"
+            ),
+            Err(e) => tracing::error!("Failed to recompute usd prices: {e:#}"),
+        }
+    });
+
+    Json(RecomputeUsdPricesResponse {
+        message: "Usd price recompute started".to_string(),
+    })
+    .into_response()
+}
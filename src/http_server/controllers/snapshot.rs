@@ -0,0 +1,125 @@
+use std::str::FromStr;
+
+use aptos_indexer_processor_sdk::utils::convert::standardize_address;
+use axum::{
+    Json,
+    extract::State,
+    http::header,
+    response::{IntoResponse, Response},
+};
+use chrono::DateTime;
+use uuid::Uuid;
+
+use crate::{
+    cache::ICache,
+    database::{IDatabase, nft_ownerships::INftOwnerships},
+    http_server::{
+        controllers::{InternalState, api_key::USER_TAG},
+        utils::{
+            err_handler::{response_400_with_message, response_500_with_const},
+            validator::QueryValidator,
+        },
+    },
+    models::api::{
+        requests::holder_snapshot::{HolderSnapshotQuery, SnapshotFormat},
+        responses::holder_snapshot::HolderSnapshotResponse,
+    },
+};
+
+/// Addresses tokens are sent to when they are thrown away
+const BURN_ADDRESSES: [&str; 2] = ["0x0", "0xdead"];
+
+#[utoipa::path(
+  get,
+  path = "/snapshots/holders",
+  tag = USER_TAG,
+  params(
+      ("collectionIds" = String, Query, description = "Comma separated collection ids"),
+      ("version" = Option<i64>, Query),
+      ("time" = Option<i64>, Query, description = "Unix time in milliseconds"),
+      ("format" = Option<String>, Query, description = "json or csv"),
+      ("excludeEscrow" = Option<bool>, Query),
+      ("excludeBurn" = Option<bool>, Query)
+  ),
+  responses(
+    (status = 200, description = "Returns the token count of every holder of the collections at a version or a time", body = HolderSnapshotResponse)
+  ),
+  security(
+    ("BearerAuth" = [])
+  )
+)]
+pub async fn fetch_holder_snapshot<TDb: IDatabase, TCache: ICache>(
+    State(state): InternalState<TDb, TCache>,
+    QueryValidator(query): QueryValidator<HolderSnapshotQuery>,
+) -> Response {
+    let collection_ids = match query
+        .collection_ids
+        .split(',')
+        .map(|id| Uuid::from_str(id.trim()))
+        .collect::<Result<Vec<_>, _>>()
+    {
+        Ok(collection_ids) => collection_ids,
+        Err(_) => return response_400_with_message("Invalid collection id"),
+    };
+
+    let time = match query.time {
+        Some(ts) => match DateTime::from_timestamp_millis(ts) {
+            Some(time) => Some(time),
+            None => return response_400_with_message("Invalid time"),
+        },
+        None => None,
+    };
+
+    let mut excluded = Vec::new();
+    if query.exclude_escrow {
+        for config in state.config.nft_marketplace_configs.iter() {
+            excluded.extend(config.get_escrow_accounts());
+        }
+    }
+
+    if query.exclude_burn {
+        excluded.extend(BURN_ADDRESSES.iter().map(|addr| standardize_address(addr)));
+    }
+
+    let holders = match state
+        .db
+        .nft_ownerships()
+        .fetch_holder_snapshot(&collection_ids, query.version, time, &excluded)
+        .await
+    {
+        Ok(holders) => holders,
+        Err(e) => {
+            tracing::error!("Failed to fetch holder snapshot: {e:#}");
+            return response_500_with_const();
+        }
+    };
+
+    match query.format {
+        SnapshotFormat::Json => Json(HolderSnapshotResponse {
+            version: query.version,
+            time,
+            total_holders: holders.len(),
+            total_tokens: holders.iter().map(|holder| holder.token_count).sum(),
+            holders,
+        })
+        .into_response(),
+        SnapshotFormat::Csv => {
+            let mut body = String::from("address,token_count\n");
+            for holder in holders.iter() {
+                body.push_str(&format!("{},{}\n", holder.address, holder.token_count));
+            }
+
+            (
+                [
+                    (header::CONTENT_TYPE, "text/csv"),
+                    (
+                        header::CONTENT_DISPOSITION,
+                        "attachment; filename=\"holders.csv\"",
+                    ),
+                ],
+                body,
+            )
+                .into_response()
+        }
+    }
+}
@@ -4,10 +4,11 @@ pub mod http;
 use std::sync::Arc;
 
 use crate::{
+    config::marketplace_config::{NFTMarketplaceConfig, get_fee_bps},
     database::{
-        Database, IDatabase, activities::IActivities, attributes::IAttributes, bids::IBids,
-        collections::ICollections, listings::IListings, marketplaces::IMarketplaces, nfts::INfts,
-        wallets::IWallets,
+        Database, IDatabase, activities::IActivities, attributes::IAttributes, auctions::IAuctions,
+        bids::IBids, collections::ICollections, listings::IListings, marketplaces::IMarketplaces,
+        nft_ownerships::INftOwnerships, nfts::INfts, wallets::IWallets,
     },
     http_server::graphql::{guard::UserGuard, http::graphiql_v2_source::GraphiQLSource},
     models::schema::{
@@ -20,7 +21,11 @@ use crate::{
             AggregateAttributeSchema, AttributeSchema, DistinctAttributeSchema,
             OrderAttributeSchema, QueryAttributeSchema,
         },
-        bid::{AggregateBidSchema, BidSchema, DistinctBidSchema, OrderBidSchema, QueryBidSchema},
+        auction::{AuctionSchema, AuctionState},
+        bid::{
+            AggregateBidSchema, BidSchema, DistinctBidSchema, OrderBidSchema, QueryBidSchema,
+            SellQuoteSchema,
+        },
         collection::{
             AggregateCollectionSchema, CollectionSchema, DistinctCollectionSchema,
             OrderCollectionSchema, QueryCollectionSchema,
@@ -38,11 +43,13 @@ use crate::{
         data_point::{DataPointSchema, validate_data_set},
         get_aggregate_selection,
         listing::{
-            AggregateListingSchema, DistinctListingSchema, ListingSchema, OrderListingSchema,
-            QueryListingSchema,
+            AggregateListingSchema, AttributeFilterSchema, DistinctListingSchema, ListingSchema,
+            MAX_SWEEP_COUNT, OrderListingSchema, QueryListingSchema, SweepQuoteSchema,
         },
         marketplace::MarketplaceSchema,
         nft::{AggregateNftSchema, DistinctNftSchema, NftSchema, OrderNftSchema, QueryNftSchema},
+        nft_ownership::NftOwnershipSchema,
+        payload::{EntryFunctionPayloadSchema, PayloadBuilder},
         wallet::{nft_holding_period::NftHoldingPeriodSchema, stats::StatsSchema},
     },
     utils::string_utils,
@@ -695,6 +702,110 @@ impl Query {
             .map_err(|e| FieldError::from(e))
     }
 
+    #[graphql(name = "collection_auctions", guard = "UserGuard")]
+    async fn collection_auctions(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default = 10)] limit: i64,
+        #[graphql(default = 0)] offset: i64,
+        #[graphql(
+            default_with = "AuctionState::default()",
+            desc = "`live` auctions are still taking bids, `ended` ones are past their end time, settled or cancelled"
+        )]
+        state: AuctionState,
+        #[graphql(name = "collection_id")] collection_id: Uuid,
+    ) -> FieldResult<Vec<AuctionSchema>> {
+        ctx.data::<Arc<Database>>()
+            .map_err(|e| FieldError::from(e))?
+            .auctions()
+            .fetch_collection_auctions(collection_id, state, limit, offset)
+            .await
+            .map_err(|e| FieldError::from(e))
+    }
+
+    #[graphql(name = "collection_sweep_quote", guard = "UserGuard")]
+    async fn collection_sweep_quote(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(name = "collection_id")] collection_id: Uuid,
+        #[graphql(desc = "Number of nfts to buy, at most 100")] count: Option<i64>,
+        #[graphql(desc = "Most to spend in total, in octas")] budget: Option<i64>,
+        #[graphql(default)] attributes: Vec<AttributeFilterSchema>,
+    ) -> FieldResult<SweepQuoteSchema> {
+        if count.is_none() && budget.is_none() {
+            return Err(FieldError::new("Either a count or a budget is required"));
+        }
+
+        let count = count.unwrap_or(MAX_SWEEP_COUNT);
+        if count <= 0 || count > MAX_SWEEP_COUNT {
+            return Err(FieldError::new(format!(
+                "The count must be between 1 and {MAX_SWEEP_COUNT}"
+            )));
+        }
+
+        let listings = ctx
+            .data::<Arc<Database>>()
+            .map_err(|e| FieldError::from(e))?
+            .listings()
+            .fetch_sweep_listings(collection_id, count, budget, &attributes)
+            .await
+            .map_err(|e| FieldError::from(e))?;
+
+        Ok(listings.into())
+    }
+
+    #[graphql(name = "nft_sell_quote", guard = "UserGuard")]
+    async fn nft_sell_quote(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(name = "nft_id")] nft_id: Uuid,
+    ) -> FieldResult<Option<SellQuoteSchema>> {
+        let fee_bps = get_fee_bps(ctx.data::<Vec<NFTMarketplaceConfig>>()?);
+
+        ctx.data::<Arc<Database>>()
+            .map_err(|e| FieldError::from(e))?
+            .bids()
+            .fetch_sell_quote(nft_id, &fee_bps)
+            .await
+            .map_err(|e| FieldError::from(e))
+    }
+
+    #[graphql(name = "nft_provenance", guard = "UserGuard")]
+    async fn nft_provenance(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default = 10)] limit: i64,
+        #[graphql(default = 0)] offset: i64,
+        #[graphql(name = "nft_id")] nft_id: Uuid,
+    ) -> FieldResult<Vec<NftOwnershipSchema>> {
+        ctx.data::<Arc<Database>>()
+            .map_err(|e| FieldError::from(e))?
+            .nft_ownerships()
+            .fetch_provenance(nft_id, limit, offset)
+            .await
+            .map_err(|e| FieldError::from(e))
+    }
+
+    #[graphql(name = "nft_owner_at", guard = "UserGuard")]
+    async fn nft_owner_at(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(name = "nft_id")] nft_id: Uuid,
+        #[graphql(desc = "Owner after this transaction version")] version: Option<i64>,
+        #[graphql(desc = "Date string or unix millis")] time: Option<Wrapper<DateTime<Utc>>>,
+    ) -> FieldResult<Option<NftOwnershipSchema>> {
+        if version.is_none() && time.is_none() {
+            return Err(FieldError::new("Either a version or a time is required"));
+        }
+
+        ctx.data::<Arc<Database>>()
+            .map_err(|e| FieldError::from(e))?
+            .nft_ownerships()
+            .fetch_ownership_at(nft_id, version, time.map(|time| time.0))
+            .await
+            .map_err(|e| FieldError::from(e))
+    }
+
     #[graphql(name = "collection_attributes", guard = "UserGuard")]
     async fn collection_attributes(
         &self,
@@ -769,4 +880,126 @@ impl Query {
             .await
             .map_err(|e| FieldError::from(e))
     }
+
+    // ================================================
+
+    // ==================== Payloads ==================
+    #[graphql(name = "buy_payload", guard = "UserGuard")]
+    async fn buy_payload(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(name = "listing_id")] listing_id: Uuid,
+    ) -> FieldResult<EntryFunctionPayloadSchema> {
+        let mut payloads = PayloadBuilder::new(ctx)?
+            .buy(&[listing_id])
+            .await
+            .map_err(|e| FieldError::from(e))?;
+
+        Ok(payloads.remove(0))
+    }
+
+    #[graphql(name = "sweep_payloads", guard = "UserGuard")]
+    async fn sweep_payloads(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(name = "listing_ids")] listing_ids: Vec<Uuid>,
+    ) -> FieldResult<Vec<EntryFunctionPayloadSchema>> {
+        if listing_ids.is_empty() || listing_ids.len() as i64 > MAX_SWEEP_COUNT {
+            return Err(FieldError::new(format!(
+                "Between 1 and {MAX_SWEEP_COUNT} listings can be swept"
+            )));
+        }
+
+        PayloadBuilder::new(ctx)?
+            .buy(&listing_ids)
+            .await
+            .map_err(|e| FieldError::from(e))
+    }
+
+    #[graphql(name = "list_payload", guard = "UserGuard")]
+    async fn list_payload(
+        &self,
+        ctx: &Context<'_>,
+        marketplace: String,
+        #[graphql(name = "nft_id")] nft_id: Uuid,
+        price: i64,
+        #[graphql(desc = "Currency address, defaults to APT")] currency: Option<String>,
+        #[graphql(name = "expiration_time", desc = "Unix timestamp in seconds")]
+        expiration_time: Option<i64>,
+    ) -> FieldResult<EntryFunctionPayloadSchema> {
+        PayloadBuilder::new(ctx)?
+            .list(
+                &marketplace,
+                nft_id,
+                price,
+                currency.as_deref(),
+                expiration_time,
+            )
+            .await
+            .map_err(|e| FieldError::from(e))
+    }
+
+    #[graphql(name = "bid_payload", guard = "UserGuard")]
+    async fn bid_payload(
+        &self,
+        ctx: &Context<'_>,
+        marketplace: String,
+        #[graphql(name = "nft_id")] nft_id: Uuid,
+        price: i64,
+        #[graphql(desc = "Currency address, defaults to APT")] currency: Option<String>,
+        #[graphql(name = "expiration_time", desc = "Unix timestamp in seconds")]
+        expiration_time: Option<i64>,
+    ) -> FieldResult<EntryFunctionPayloadSchema> {
+        PayloadBuilder::new(ctx)?
+            .bid(
+                &marketplace,
+                nft_id,
+                price,
+                currency.as_deref(),
+                expiration_time,
+            )
+            .await
+            .map_err(|e| FieldError::from(e))
+    }
+
+    #[graphql(name = "collection_bid_payload", guard = "UserGuard")]
+    async fn collection_bid_payload(
+        &self,
+        ctx: &Context<'_>,
+        marketplace: String,
+        #[graphql(name = "collection_id")] collection_id: Uuid,
+        price: i64,
+        #[graphql(default = 1)] amount: i64,
+        #[graphql(desc = "Currency address, defaults to APT")] currency: Option<String>,
+        #[graphql(name = "expiration_time", desc = "Unix timestamp in seconds")]
+        expiration_time: Option<i64>,
+    ) -> FieldResult<EntryFunctionPayloadSchema> {
+        if amount <= 0 {
+            return Err(FieldError::new("The amount must be positive"));
+        }
+
+        PayloadBuilder::new(ctx)?
+            .collection_bid(
+                &marketplace,
+                collection_id,
+                price,
+                amount,
+                currency.as_deref(),
+                expiration_time,
+            )
+            .await
+            .map_err(|e| FieldError::from(e))
+    }
+
+    #[graphql(name = "cancel_bid_payload", guard = "UserGuard")]
+    async fn cancel_bid_payload(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(name = "bid_id")] bid_id: Uuid,
+    ) -> FieldResult<EntryFunctionPayloadSchema> {
+        PayloadBuilder::new(ctx)?
+            .cancel_bid(bid_id)
+            .await
+            .map_err(|e| FieldError::from(e))
+    }
 }
@@ -11,7 +11,7 @@ use crate::{
         controllers::{
             api_key::{self, USER_TAG},
             auth::{self, AUTH_TAG},
-            graphql_handler, health, request_log,
+            failed_event, graphql_handler, health, marketplace, price, request_log, snapshot,
             user::{self, ADMIN_TAG},
         },
         graphql::{Query, graphql},
@@ -56,6 +56,14 @@ struct AuthApi;
     user::create_user,
     user::update_user,
     user::fetch_user_summaries,
+    marketplace::dry_run_remap,
+    marketplace::rederive_marketplace_events,
+    failed_event::fetch_failed_events,
+    failed_event::fetch_failed_event,
+    failed_event::redrive_event,
+    failed_event::redrive_events,
+    price::backfill,
+    price::recompute_usd,
 ))]
 struct AdminApi;
 
@@ -67,6 +75,7 @@ struct AdminApi;
     api_key::remove_api_key,
     request_log::fetch_logs,
     request_log::fetch_summaries,
+    snapshot::fetch_holder_snapshot,
 ))]
 struct UserApi;
 
@@ -115,6 +124,8 @@ where
     pub fn new(db: Arc<TDb>, _cache: Arc<TCache>, config: Arc<Config>) -> Self {
         let schema = Schema::build(Query, EmptyMutation, EmptySubscription)
             .data(Arc::clone(&db))
+            .data(config.currencies.clone())
+            .data(config.nft_marketplace_configs.clone())
             .finish();
 
         Self {
@@ -222,6 +233,29 @@ where
                                             ),
                                     ),
                             )
+                            .nest(
+                                "/marketplace",
+                                OpenApiRouter::new()
+                                    .route("/dry-run", post(marketplace::dry_run_remap))
+                                    .route(
+                                        "/re-derive",
+                                        post(marketplace::rederive_marketplace_events),
+                                    ),
+                            )
+                            .nest(
+                                "/failed-events",
+                                OpenApiRouter::new()
+                                    .route("/", get(failed_event::fetch_failed_events))
+                                    .route("/redrive", post(failed_event::redrive_events))
+                                    .route("/{id}", get(failed_event::fetch_failed_event))
+                                    .route("/{id}/redrive", post(failed_event::redrive_event)),
+                            )
+                            .nest(
+                                "/prices",
+                                OpenApiRouter::new()
+                                    .route("/backfill", post(price::backfill))
+                                    .route("/recompute-usd", post(price::recompute_usd)),
+                            )
                             .layer(middleware::from_fn(authorize::authorize_admin)),
                     )
                     .nest(
@@ -246,6 +280,11 @@ where
                                     .route("/chart", get(request_log::fetch_logs))
                                     .route("/summaries", get(request_log::fetch_summaries)),
                             )
+                            .nest(
+                                "/snapshots",
+                                OpenApiRouter::new()
+                                    .route("/holders", get(snapshot::fetch_holder_snapshot)),
+                            )
                             .layer(middleware::from_fn(authorize::authorize_user)),
                     )
                     .layer(middleware::from_fn(move |req, next| {
@@ -7,7 +7,7 @@ use jsonwebtoken::errors::Error;
 use validator::ValidationErrors;
 
 use crate::models::api::responses::{
-    BAD_REQUEST_ERR, HttpResponse, HttpResponseErr, UNAUTHORIZED_ERR,
+    BAD_REQUEST_ERR, HttpResponse, HttpResponseErr, INTERNAL_SERVER_ERR, UNAUTHORIZED_ERR,
 };
 
 pub fn response_400_with_const() -> Response {
@@ -54,6 +54,10 @@ pub fn response_429_unhandled_err(e: anyhow::Error) -> Response {
     (StatusCode::UNPROCESSABLE_ENTITY, Json(error)).into_response()
 }
 
+pub fn response_500_with_const() -> Response {
+    (StatusCode::INTERNAL_SERVER_ERROR, Json(INTERNAL_SERVER_ERR)).into_response()
+}
+
 pub fn response_validation_err(e: ValidationErrors) -> (StatusCode, Json<HttpResponse<String>>) {
     let msg = e
         .field_errors()
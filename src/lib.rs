@@ -7,24 +7,28 @@ pub mod utils;
 pub mod workers;
 
 use anyhow::Context;
-use reqwest::Client;
 use sqlx::postgres::PgPoolOptions;
 use std::{sync::Arc, time::Duration};
 use tracing_subscriber::{EnvFilter, prelude::*};
 
 use crate::{
     cache::Cache,
-    config::Config,
+    config::{Config, currency_config::CurrencyConfig, price_config::PriceIndexerConfig},
     database::{
         Database, IDatabase,
         activities::Activities,
         api_keys::ApiKeys,
         attributes::Attributes,
+        auctions::Auctions,
         bids::Bids,
         collections::Collections,
+        failed_events::FailedEvents,
         listings::Listings,
+        marketplace_events::MarketplaceEvents,
         marketplaces::Marketplaces,
+        nft_balances::NftBalances,
         nft_metadata::NFTMetadata,
+        nft_ownerships::NftOwnerships,
         nfts::Nfts,
         processor_status::ProcessorStatus,
         request_logs::RequestLogs,
@@ -69,6 +73,11 @@ pub async fn init() -> anyhow::Result<(Arc<Worker<Database, Cache>>, HttpServer<
         Arc::new(Users::new(Arc::clone(&pool))),
         Arc::new(RequestLogs::new(Arc::clone(&pool))),
         Arc::new(ApiKeys::new(Arc::clone(&pool))),
+        Arc::new(FailedEvents::new(Arc::clone(&pool))),
+        Arc::new(MarketplaceEvents::new(Arc::clone(&pool))),
+        Arc::new(Auctions::new(Arc::clone(&pool))),
+        Arc::new(NftOwnerships::new(Arc::clone(&pool))),
+        Arc::new(NftBalances::new(Arc::clone(&pool))),
     ));
 
     init_admin(
@@ -79,9 +88,17 @@ pub async fn init() -> anyhow::Result<(Arc<Worker<Database, Cache>>, HttpServer<
     .await
     .context("Failed to initialize admin")?;
 
-    init_price(&config.tapp_url, Arc::clone(&db), Arc::clone(&cache))
-        .await
-        .context("Failed to initialize price")?;
+    // Stored prices keep valuations going while every source is down, so startup goes on
+    if let Err(e) = init_price(
+        config.price_indexer_config.clone(),
+        config.currencies.clone(),
+        Arc::clone(&db),
+        Arc::clone(&cache),
+    )
+    .await
+    {
+        tracing::error!("Failed to initialize price: {e:#}");
+    }
 
     tokio::spawn(shutdown_utils::poll_for_shutdown_signal());
 
@@ -96,23 +113,27 @@ pub async fn init() -> anyhow::Result<(Arc<Worker<Database, Cache>>, HttpServer<
 }
 
 fn init_config() -> anyhow::Result<Config> {
-    let config = Config::load().context("Failed to load configuration")?;
-
     tracing_subscriber::registry()
         .with(tracing_subscriber::fmt::layer().pretty())
         .with(EnvFilter::from_default_env())
         .init();
 
+    let config = Config::load().context("Failed to load configuration")?;
+
     tracing::trace!("config: {:#?}", config);
 
     Ok(config)
 }
 
-async fn init_price(tapp_url: &str, db: Arc<Database>, cache: Arc<Cache>) -> anyhow::Result<()> {
-    let client = Client::new();
-    let price_indexer = PriceIndexer::new(tapp_url.to_string(), db, cache);
+async fn init_price(
+    config: PriceIndexerConfig,
+    currencies: Vec<CurrencyConfig>,
+    db: Arc<Database>,
+    cache: Arc<Cache>,
+) -> anyhow::Result<()> {
+    let price_indexer = PriceIndexer::new(config, currencies, db, cache);
 
-    price_indexer.fetch_and_store_token_prices(&client).await?;
+    price_indexer.fetch_and_store_token_prices().await?;
 
     Ok(())
 }
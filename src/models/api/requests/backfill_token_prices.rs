@@ -0,0 +1,15 @@
+use serde::Deserialize;
+use utoipa::ToSchema;
+use validator::Validate;
+
+#[derive(Deserialize, Validate, ToSchema)]
+pub struct BackfillTokenPrices {
+    /// Only backfill this currency, by symbol or address, every configured one otherwise
+    pub currency: Option<String>,
+    /// Unix timestamp in seconds
+    #[validate(range(min = 0))]
+    pub start_time: i64,
+    /// Unix timestamp in seconds
+    #[validate(range(min = 0))]
+    pub end_time: i64,
+}
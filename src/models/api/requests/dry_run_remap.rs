@@ -0,0 +1,17 @@
+use aptos_indexer_processor_sdk::aptos_protos::transaction::v1::Transaction;
+use serde::Deserialize;
+use utoipa::ToSchema;
+use validator::Validate;
+
+use crate::config::marketplace_config::NFTMarketplaceConfig;
+
+#[derive(Deserialize, Validate, ToSchema)]
+pub struct DryRunRemap {
+    /// Candidate marketplace config, in the same shape as `nft_marketplace_configs` entries
+    #[schema(value_type = Object)]
+    pub config: NFTMarketplaceConfig,
+    /// Raw transactions in the indexer gRPC JSON encoding
+    #[validate(length(min = 1, max = 100))]
+    #[schema(value_type = Vec<Object>)]
+    pub transactions: Vec<Transaction>,
+}
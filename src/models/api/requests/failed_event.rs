@@ -0,0 +1,30 @@
+use serde::Deserialize;
+use utoipa::ToSchema;
+use validator::Validate;
+
+#[derive(Deserialize, Validate, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FailedEventQuery {
+    pub marketplace: Option<String>,
+    pub status: Option<String>,
+    pub step: Option<String>,
+    #[serde(default = "default_limit")]
+    #[validate(range(min = 1, max = 100))]
+    pub limit: i64,
+    #[serde(default)]
+    #[validate(range(min = 0))]
+    pub offset: i64,
+}
+
+#[derive(Deserialize, Validate, ToSchema)]
+pub struct RedriveFailedEvents {
+    /// Only re-drive the pending events of this marketplace
+    pub marketplace: Option<String>,
+    #[serde(default = "default_limit")]
+    #[validate(range(min = 1, max = 100))]
+    pub limit: i64,
+}
+
+fn default_limit() -> i64 {
+    100
+}
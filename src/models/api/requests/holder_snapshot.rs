@@ -0,0 +1,32 @@
+use serde::Deserialize;
+use strum::{Display, EnumString};
+use validator::Validate;
+
+#[derive(Deserialize, Validate, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct HolderSnapshotQuery {
+    /// Comma separated collection ids
+    #[validate(length(min = 1))]
+    pub collection_ids: String,
+    /// Holders right after this transaction version
+    pub version: Option<i64>,
+    /// Holders at this unix time in milliseconds
+    pub time: Option<i64>,
+    #[serde(default)]
+    pub format: SnapshotFormat,
+    /// Leaves out the tokens held by marketplace contracts, fee and escrow accounts
+    #[serde(default)]
+    pub exclude_escrow: bool,
+    /// Leaves out the tokens sent to burn addresses
+    #[serde(default)]
+    pub exclude_burn: bool,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, Display, EnumString)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum SnapshotFormat {
+    #[default]
+    Json,
+    Csv,
+}
@@ -1,6 +1,12 @@
+pub mod backfill_token_prices;
 pub mod create_api_key;
 pub mod create_user;
+pub mod dry_run_remap;
+pub mod failed_event;
+pub mod holder_snapshot;
 pub mod login;
+pub mod recompute_usd_prices;
+pub mod rederive_events;
 pub mod time_range;
 pub mod update_api_key;
 pub mod update_user;
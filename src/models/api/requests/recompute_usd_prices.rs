@@ -0,0 +1,14 @@
+use serde::Deserialize;
+use utoipa::ToSchema;
+use validator::Validate;
+
+#[derive(Deserialize, Validate, ToSchema)]
+pub struct RecomputeUsdPrices {
+    /// Unix timestamp in seconds of the earliest block time to re-price, from the start
+    /// of the history otherwise
+    #[validate(range(min = 0))]
+    pub start_time: Option<i64>,
+    /// Unix timestamp in seconds of the latest block time to re-price, up to now otherwise
+    #[validate(range(min = 0))]
+    pub end_time: Option<i64>,
+}
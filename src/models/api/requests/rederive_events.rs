@@ -0,0 +1,15 @@
+use serde::Deserialize;
+use utoipa::ToSchema;
+use validator::Validate;
+
+#[derive(Deserialize, Validate, ToSchema)]
+pub struct RederiveEvents {
+    /// Marketplace name, as in `nft_marketplace_configs`
+    pub marketplace: String,
+    /// Only re-derive the deployment at this contract address, all of them otherwise
+    pub contract_address: Option<String>,
+    #[validate(range(min = 0))]
+    pub start_version: i64,
+    #[validate(range(min = 0))]
+    pub end_version: i64,
+}
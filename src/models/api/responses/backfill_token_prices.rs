@@ -0,0 +1,9 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+
+#[derive(Serialize, ToSchema)]
+pub struct BackfillTokenPricesResponse {
+    /// Symbols of the currencies being backfilled
+    pub currencies: Vec<String>,
+    pub message: String,
+}
@@ -0,0 +1,41 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::{
+    config::{marketplace_config::NFTMarketplaceConfig, validation::ConfigIssue},
+    models::{
+        db::{
+            auction::DbAuction,
+            bid::{DbBid, DbBidFill},
+            collection::DbCollection,
+            failed_event::DbFailedEvent,
+            listing::DbListing,
+            nft::DbNft,
+        },
+        marketplace::NftMarketplaceActivity,
+    },
+};
+
+#[derive(Serialize, ToSchema)]
+pub struct DryRunRemapResponse {
+    /// The config after resolving `extends` and templates
+    #[schema(value_type = Object)]
+    pub config: NFTMarketplaceConfig,
+    pub issues: Vec<ConfigIssue>,
+    #[schema(value_type = Vec<Object>)]
+    pub activities: Vec<NftMarketplaceActivity>,
+    #[schema(value_type = Vec<Object>)]
+    pub listings: Vec<DbListing>,
+    #[schema(value_type = Vec<Object>)]
+    pub bids: Vec<DbBid>,
+    #[schema(value_type = Vec<Object>)]
+    pub bid_fills: Vec<DbBidFill>,
+    #[schema(value_type = Vec<Object>)]
+    pub auctions: Vec<DbAuction>,
+    #[schema(value_type = Vec<Object>)]
+    pub collections: Vec<DbCollection>,
+    #[schema(value_type = Vec<Object>)]
+    pub nfts: Vec<DbNft>,
+    /// Events that would be written to the dead-letter store
+    pub failed_events: Vec<DbFailedEvent>,
+}
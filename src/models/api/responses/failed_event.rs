@@ -0,0 +1,15 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+#[derive(Serialize, ToSchema)]
+pub struct RedriveFailedEventsResponse {
+    pub resolved: Vec<Uuid>,
+    pub failed: Vec<RedriveError>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct RedriveError {
+    pub id: Uuid,
+    pub error: String,
+}
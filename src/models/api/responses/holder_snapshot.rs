@@ -0,0 +1,19 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::prelude::FromRow;
+use utoipa::ToSchema;
+
+#[derive(Serialize, ToSchema)]
+pub struct HolderSnapshotResponse {
+    pub version: Option<i64>,
+    pub time: Option<DateTime<Utc>>,
+    pub total_holders: usize,
+    pub total_tokens: i64,
+    pub holders: Vec<SnapshotHolder>,
+}
+
+#[derive(Clone, Debug, Serialize, ToSchema, FromRow)]
+pub struct SnapshotHolder {
+    pub address: String,
+    pub token_count: i64,
+}
@@ -1,7 +1,13 @@
 pub mod access_token;
 pub mod api_key;
 pub mod auth_user;
+pub mod backfill_token_prices;
+pub mod dry_run_remap;
+pub mod failed_event;
+pub mod holder_snapshot;
 pub mod log;
+pub mod recompute_usd_prices;
+pub mod rederive_events;
 pub mod user;
 
 use serde::Serialize;
@@ -28,6 +34,11 @@ pub const BAD_REQUEST_ERR: HttpResponseConstErr = HttpResponseConstErr {
     msg: "Bad Request",
 };
 
+pub const INTERNAL_SERVER_ERR: HttpResponseConstErr = HttpResponseConstErr {
+    code: "ERR_500",
+    msg: "Internal Server Error",
+};
+
 #[derive(Serialize, ToSchema)]
 pub struct HttpResponseErr {
     pub code: String,
@@ -0,0 +1,9 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+
+#[derive(Serialize, ToSchema)]
+pub struct RederiveEventsResponse {
+    /// Processor names of the deployments being re-derived
+    pub deployments: Vec<String>,
+    pub message: String,
+}
@@ -11,8 +11,9 @@ use ahash::AHashMap;
 use anyhow::Context;
 use aptos_indexer_processor_sdk::utils::convert::standardize_address;
 use bigdecimal::BigDecimal;
-use chrono::NaiveDateTime;
+use chrono::{DateTime, NaiveDateTime, Utc};
 use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
 use uuid::Uuid;
 
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
@@ -32,6 +33,19 @@ pub struct DbActivity {
     pub market_contract_id: Option<String>,
     pub usd_price: Option<BigDecimal>,
     pub amount: Option<i64>,
+    pub currency: Option<String>,
+    pub seller_proceeds: Option<i64>,
+    pub marketplace_fee: Option<i64>,
+    pub royalty_amount: Option<i64>,
+}
+
+/// The fields of a stored activity its usd price is computed from
+#[derive(Clone, Debug, FromRow)]
+pub struct DbActivityPrice {
+    pub id: Uuid,
+    pub price: i64,
+    pub currency: Option<String>,
+    pub block_time: DateTime<Utc>,
 }
 
 impl DbActivity {
@@ -0,0 +1,30 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// A timed auction of a token, reduced from its create, bid, settle and cancel events
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct DbAuction {
+    pub id: Uuid,
+    pub market_contract_id: Option<String>,
+    pub market_name: Option<String>,
+    pub collection_id: Option<Uuid>,
+    pub nft_id: Option<Uuid>,
+    pub nonce: Option<String>,
+    pub seller: Option<String>,
+    pub reserve_price: Option<i64>,
+    pub highest_bid: Option<i64>,
+    pub highest_bidder: Option<String>,
+    pub currency: Option<String>,
+    pub start_time: Option<NaiveDateTime>,
+    pub end_time: Option<NaiveDateTime>,
+    pub status: Option<String>,
+    pub created_tx_id: Option<String>,
+    pub settled_tx_id: Option<String>,
+    pub cancelled_tx_id: Option<String>,
+    /// Latest create of the auction, the events before it belong to an earlier auction
+    /// under the same key
+    pub created_tx_index: Option<i64>,
+    pub tx_index: Option<i64>,
+    pub block_time: Option<NaiveDateTime>,
+}
@@ -20,4 +20,28 @@ pub struct DbBid {
     pub remaining_count: Option<i64>,
     pub status: Option<String>,
     pub bid_type: Option<String>,
+    pub tx_index: Option<i64>,
+    /// Latest create of the bid, bidders can create a bid again under the same id and
+    /// only the fills after it count
+    pub created_tx_index: Option<i64>,
+    /// Number of NFTs asked for when the bid was created
+    pub amount: Option<i64>,
+    pub currency: Option<String>,
+    /// Trait a collection bid is restricted to
+    pub attr_type: Option<String>,
+    pub attr_value: Option<String>,
+}
+
+/// An accept event filling part or all of a bid
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct DbBidFill {
+    pub activity_id: Uuid,
+    pub bid_id: Uuid,
+    pub tx_id: String,
+    pub tx_index: i64,
+    pub nft_id: Option<Uuid>,
+    pub seller: Option<String>,
+    pub price: Option<i64>,
+    pub amount: i64,
+    pub block_time: NaiveDateTime,
 }
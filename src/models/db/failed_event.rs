@@ -0,0 +1,256 @@
+use aptos_indexer_processor_sdk::aptos_protos::transaction::v1::Event;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use strum::{Display, EnumString};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::{
+    models::{
+        EventModel, db::marketplace_event::DbMarketplaceEvent, marketplace::NftMarketplaceActivity,
+    },
+    utils::generate_failed_event_id,
+};
+
+#[derive(Clone, Copy, Debug, Display, EnumString, PartialEq, Eq)]
+#[strum(serialize_all = "snake_case")]
+pub enum FailedEventStep {
+    /// The raw event couldn't be parsed or remapped into an activity
+    Remapping,
+    /// The activity couldn't be reduced into its bid or listing
+    Reduction,
+    /// The write set changes of the transaction couldn't be remapped
+    Resources,
+}
+
+#[derive(Clone, Copy, Debug, Display, EnumString, PartialEq, Eq)]
+#[strum(serialize_all = "snake_case")]
+pub enum FailedEventStatus {
+    Pending,
+    Resolved,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, FromRow, ToSchema)]
+pub struct DbFailedEvent {
+    pub id: Uuid,
+    pub marketplace: String,
+    pub contract_address: String,
+    pub tx_id: String,
+    pub tx_version: i64,
+    pub event_index: i64,
+    pub event_type: String,
+    pub step: String,
+    pub error: String,
+    /// The event as emitted on chain, or as parsed once it reached the reduction step
+    #[schema(value_type = Object)]
+    pub raw_event: serde_json::Value,
+    pub status: String,
+    pub attempts: i32,
+    pub block_time: DateTime<Utc>,
+    pub block_height: i64,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl DbFailedEvent {
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        marketplace: &str,
+        contract_address: &str,
+        tx_id: &str,
+        tx_version: i64,
+        event_index: i64,
+        event_type: &str,
+        step: FailedEventStep,
+        error: &anyhow::Error,
+        raw_event: serde_json::Value,
+        block_time: NaiveDateTime,
+        block_height: i64,
+    ) -> Self {
+        Self {
+            id: generate_failed_event_id(marketplace, contract_address, tx_version, event_index),
+            marketplace: marketplace.to_string(),
+            contract_address: contract_address.to_string(),
+            tx_id: tx_id.to_string(),
+            tx_version,
+            event_index,
+            event_type: event_type.to_string(),
+            step: step.to_string(),
+            error: format!("{error:#}"),
+            raw_event,
+            status: FailedEventStatus::Pending.to_string(),
+            attempts: 1,
+            block_time: block_time.and_utc(),
+            block_height,
+            created_at: None,
+            updated_at: None,
+        }
+    }
+
+    /// An on chain event that couldn't be parsed or remapped
+    #[allow(clippy::too_many_arguments)]
+    pub fn from_raw_event(
+        marketplace: &str,
+        contract_address: &str,
+        tx_id: &str,
+        tx_version: i64,
+        event_index: i64,
+        event: &Event,
+        error: &anyhow::Error,
+        block_time: NaiveDateTime,
+        block_height: i64,
+    ) -> Self {
+        Self::new(
+            marketplace,
+            contract_address,
+            tx_id,
+            tx_version,
+            event_index,
+            &event.type_str,
+            FailedEventStep::Remapping,
+            error,
+            serde_json::to_value(event).unwrap_or_default(),
+            block_time,
+            block_height,
+        )
+    }
+
+    /// An archived event that couldn't be remapped
+    pub fn from_event_model(
+        marketplace: &str,
+        contract_address: &str,
+        tx_id: &str,
+        event: &EventModel,
+        error: &anyhow::Error,
+    ) -> Self {
+        Self::new(
+            marketplace,
+            contract_address,
+            tx_id,
+            event.transaction_version,
+            event.event_index,
+            &event.type_str,
+            FailedEventStep::Remapping,
+            error,
+            serde_json::to_value(event).unwrap_or_default(),
+            event.block_timestamp,
+            event.transaction_block_height,
+        )
+    }
+
+    /// An archived event that couldn't be parsed back
+    pub fn from_archived_event(
+        marketplace: &str,
+        event: &DbMarketplaceEvent,
+        error: &anyhow::Error,
+    ) -> Self {
+        Self::new(
+            marketplace,
+            &event.contract_address,
+            &event.tx_id,
+            event.tx_version,
+            event.event_index,
+            &event.type_str,
+            FailedEventStep::Remapping,
+            error,
+            serde_json::to_value(event).unwrap_or_default(),
+            event.block_time.naive_utc(),
+            event.block_height,
+        )
+    }
+
+    /// A transaction whose write set changes couldn't be remapped, it isn't tied to an event
+    pub fn from_transaction(
+        marketplace: &str,
+        contract_address: &str,
+        tx_id: &str,
+        tx_version: i64,
+        error: &anyhow::Error,
+        block_time: NaiveDateTime,
+        block_height: i64,
+    ) -> Self {
+        Self::new(
+            marketplace,
+            contract_address,
+            tx_id,
+            tx_version,
+            -1,
+            "write_set",
+            FailedEventStep::Resources,
+            error,
+            serde_json::Value::Null,
+            block_time,
+            block_height,
+        )
+    }
+
+    /// A remapped activity that couldn't be reduced
+    pub fn from_activity(activity: &NftMarketplaceActivity, error: &anyhow::Error) -> Self {
+        Self::new(
+            activity.marketplace.as_deref().unwrap_or_default(),
+            activity.contract_address.as_deref().unwrap_or_default(),
+            &activity.txn_id,
+            activity.txn_version,
+            activity.index,
+            &activity.raw_event_type,
+            FailedEventStep::Reduction,
+            error,
+            activity.json_data.clone(),
+            activity.block_timestamp,
+            activity.block_height,
+        )
+    }
+
+    /// Rebuilds the parsed event, whichever form it was stored in
+    pub fn to_event_model(&self) -> anyhow::Result<Option<EventModel>> {
+        if let Ok(event) = serde_json::from_value::<EventModel>(self.raw_event.clone()) {
+            return Ok(Some(event));
+        }
+
+        if let Ok(event) = serde_json::from_value::<DbMarketplaceEvent>(self.raw_event.clone()) {
+            return event.to_event_model().map(Some);
+        }
+
+        let event = serde_json::from_value::<Event>(self.raw_event.clone())?;
+
+        EventModel::from_event(
+            &event,
+            self.tx_version,
+            self.block_height,
+            self.event_index,
+            self.block_time.naive_utc(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn archived_event_is_read_back() {
+        let archived = DbMarketplaceEvent {
+            contract_address: "0x1".to_string(),
+            tx_version: 10,
+            event_index: 2,
+            tx_id: "0xabc".to_string(),
+            account_address: "0x2".to_string(),
+            creation_number: 0,
+            sequence_number: 0,
+            type_str: "0x1::events::ListEvent".to_string(),
+            data: serde_json::json!({ "price": "100" }),
+            block_time: Utc::now(),
+            block_height: 5,
+        };
+
+        let failed_event =
+            DbFailedEvent::from_archived_event("market", &archived, &anyhow::anyhow!("bad"));
+        let event = failed_event.to_event_model().unwrap().unwrap();
+
+        assert_eq!(event.transaction_version, 10);
+        assert_eq!(event.event_index, 2);
+        assert_eq!(event.type_str, archived.type_str);
+        assert_eq!(event.data, archived.data);
+    }
+}
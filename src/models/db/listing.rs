@@ -1,7 +1,12 @@
+use std::{collections::HashMap, str::FromStr};
+
 use chrono::NaiveDateTime;
 use serde::{Deserialize, Serialize};
+use strum::{Display, EnumString};
 use uuid::Uuid;
 
+use crate::{config::marketplace_config::MarketplaceEventType, models::db::activity::DbActivity};
+
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
 pub struct DbListing {
     pub id: Uuid,
@@ -16,4 +21,85 @@ pub struct DbListing {
     pub price: Option<i64>,
     pub seller: Option<String>,
     pub tx_index: Option<i64>,
+    pub expired_at: Option<NaiveDateTime>,
+    pub currency: Option<String>,
+}
+
+/// Why a listing was closed without an event of its own marketplace
+#[derive(Debug, Clone, PartialEq, Display, EnumString)]
+#[strum(serialize_all = "snake_case")]
+pub enum ListingInvalidReason {
+    Transferred,
+    Burned,
+    SoldElsewhere,
+}
+
+/// A token movement that invalidates the open listings of the token made before it.
+/// Movements aren't counted per edition, a v1 transfer of one edition out of many closes
+/// the listing too.
+#[derive(Clone, Debug)]
+pub struct DbListingInvalidation {
+    pub nft_id: Uuid,
+    pub tx_index: i64,
+    pub reason: String,
+    /// New owner of the token, listings of this seller stay open
+    pub owner: Option<String>,
+    /// Marketplace the token was sold on, its own listings follow its events
+    pub market_contract_id: Option<String>,
+}
+
+impl DbListingInvalidation {
+    pub fn from_activity(activity: &DbActivity) -> Option<Self> {
+        let nft_id = activity.nft_id?;
+        let tx_type = MarketplaceEventType::from_str(activity.tx_type.as_deref()?).ok()?;
+
+        let (reason, owner, market_contract_id) = match tx_type {
+            MarketplaceEventType::Transfer => (
+                ListingInvalidReason::Transferred,
+                activity.receiver.clone(),
+                None,
+            ),
+            MarketplaceEventType::Burn => (ListingInvalidReason::Burned, None, None),
+            MarketplaceEventType::Buy
+            | MarketplaceEventType::AcceptBid
+            | MarketplaceEventType::AcceptCollectionBid => (
+                ListingInvalidReason::SoldElsewhere,
+                activity.receiver.clone(),
+                activity.market_contract_id.clone(),
+            ),
+            // Auctions settled without a winner return the token to the seller
+            MarketplaceEventType::AuctionSettle if activity.receiver.is_some() => (
+                ListingInvalidReason::SoldElsewhere,
+                activity.receiver.clone(),
+                activity.market_contract_id.clone(),
+            ),
+            _ => return None,
+        };
+
+        Some(Self {
+            nft_id,
+            tx_index: activity.tx_index,
+            reason: reason.to_string(),
+            owner,
+            market_contract_id,
+        })
+    }
+
+    /// Invalidations of a batch, keeping the latest movement of every token
+    pub fn from_activities(activities: &[DbActivity]) -> Vec<Self> {
+        let mut invalidations: HashMap<Uuid, Self> = HashMap::new();
+
+        for invalidation in activities.iter().filter_map(Self::from_activity) {
+            invalidations
+                .entry(invalidation.nft_id)
+                .and_modify(|existing| {
+                    if invalidation.tx_index > existing.tx_index {
+                        *existing = invalidation.clone();
+                    }
+                })
+                .or_insert(invalidation);
+        }
+
+        invalidations.into_values().collect()
+    }
 }
@@ -0,0 +1,55 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+use crate::{config::marketplace_config::EventType, models::EventModel};
+
+/// An archived event of a marketplace transaction, either emitted by the marketplace
+/// contract or a coin event the remapping depends on
+#[derive(Clone, Debug, Deserialize, Serialize, FromRow)]
+pub struct DbMarketplaceEvent {
+    pub contract_address: String,
+    pub tx_version: i64,
+    pub event_index: i64,
+    pub tx_id: String,
+    pub account_address: String,
+    pub creation_number: i64,
+    pub sequence_number: i64,
+    pub type_str: String,
+    pub data: serde_json::Value,
+    pub block_time: DateTime<Utc>,
+    pub block_height: i64,
+}
+
+impl DbMarketplaceEvent {
+    pub fn new(contract_address: &str, tx_id: &str, event: &EventModel) -> Self {
+        Self {
+            contract_address: contract_address.to_string(),
+            tx_version: event.transaction_version,
+            event_index: event.event_index,
+            tx_id: tx_id.to_string(),
+            account_address: event.account_address.clone(),
+            creation_number: event.creation_number,
+            sequence_number: event.sequence_number,
+            type_str: event.type_str.clone(),
+            data: event.data.clone(),
+            block_time: event.block_timestamp.and_utc(),
+            block_height: event.transaction_block_height,
+        }
+    }
+
+    pub fn to_event_model(&self) -> anyhow::Result<EventModel> {
+        Ok(EventModel {
+            sequence_number: self.sequence_number,
+            creation_number: self.creation_number,
+            account_address: self.account_address.clone(),
+            transaction_version: self.tx_version,
+            transaction_block_height: self.block_height,
+            event_type: EventType::try_from(self.type_str.as_str())?,
+            type_str: self.type_str.clone(),
+            data: self.data.clone(),
+            event_index: self.event_index,
+            block_timestamp: self.block_time.naive_utc(),
+        })
+    }
+}
@@ -1,11 +1,16 @@
 pub mod activity;
 pub mod api_key;
 pub mod attribute;
+pub mod auction;
 pub mod bid;
 pub mod collection;
+pub mod failed_event;
 pub mod listing;
+pub mod marketplace_event;
 pub mod nft;
+pub mod nft_balance_change;
 pub mod nft_metadata;
+pub mod nft_ownership;
 pub mod processor_status;
 pub mod token_price;
 pub mod wallet;
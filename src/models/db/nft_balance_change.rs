@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Change of the editions of a v1 token an owner holds by one deposit or withdrawal
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct DbNftBalanceChange {
+    pub nft_id: Uuid,
+    pub owner: String,
+    /// Index of the event, a change is only recorded once however often it is indexed
+    pub tx_index: i64,
+    pub collection_id: Option<Uuid>,
+    pub amount: i64,
+}
@@ -0,0 +1,50 @@
+use std::str::FromStr;
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    config::marketplace_config::MarketplaceEventType,
+    models::{TX_INDEX_MULTIPLIER, db::activity::DbActivity},
+};
+
+/// A token landing in a wallet, opening an ownership interval that the next one, or a burn,
+/// closes
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct DbNftOwnership {
+    pub nft_id: Uuid,
+    pub tx_index: i64,
+    pub collection_id: Option<Uuid>,
+    pub owner: String,
+    pub tx_id: String,
+    /// Token activity the owner acquired the nft by, v1 deposits outside a mint have none
+    pub activity_id: Option<Uuid>,
+    pub block_time: NaiveDateTime,
+}
+
+impl DbNftOwnership {
+    pub fn from_activity(activity: &DbActivity) -> Option<Self> {
+        let tx_type = MarketplaceEventType::from_str(activity.tx_type.as_deref()?).ok()?;
+        if !matches!(
+            tx_type,
+            MarketplaceEventType::Mint | MarketplaceEventType::Transfer
+        ) {
+            return None;
+        }
+
+        Some(Self {
+            nft_id: activity.nft_id?,
+            tx_index: activity.tx_index,
+            collection_id: activity.collection_id,
+            owner: activity.receiver.clone()?,
+            tx_id: activity.tx_id.clone(),
+            activity_id: Some(activity.id),
+            block_time: activity.block_time?,
+        })
+    }
+
+    pub fn get_version(&self) -> i64 {
+        self.tx_index / TX_INDEX_MULTIPLIER
+    }
+}
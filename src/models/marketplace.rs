@@ -1,21 +1,36 @@
 use crate::models::db::collection::{DbCollection, get_collection_slug};
 use crate::models::db::nft::DbNft;
 use crate::utils::{
-    generate_activity_id, generate_bid_id, generate_collection_id, generate_listing_id,
-    generate_nft_id,
+    generate_activity_id, generate_auction_id, generate_bid_id, generate_collection_id,
+    generate_listing_id, generate_nft_id,
 };
 use crate::{
     config::marketplace_config::MarketplaceEventType,
-    models::db::{activity::DbActivity, bid::DbBid, listing::DbListing},
+    models::{
+        TX_INDEX_MULTIPLIER,
+        db::{
+            activity::DbActivity,
+            auction::DbAuction,
+            bid::{DbBid, DbBidFill},
+            listing::DbListing,
+        },
+        schema::{auction::AuctionStatus, bid::BidStatus},
+    },
 };
 use anyhow::Context;
 use bigdecimal::BigDecimal;
-use chrono::{DateTime, NaiveDateTime};
+use chrono::{DateTime, NaiveDateTime, TimeDelta};
 use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, str::FromStr};
 use strum::{Display, EnumString};
 use uuid::Uuid;
 
 pub const NFT_MARKETPLACE_ACTIVITIES_TABLE_NAME: &str = "nft_marketplace_activities";
+pub const BIDS_TABLE_NAME: &str = "bids";
+pub const LISTINGS_TABLE_NAME: &str = "listings";
+pub const AUCTIONS_TABLE_NAME: &str = "auctions";
+pub const COLLECTIONS_TABLE_NAME: &str = "collections";
+pub const NFTS_TABLE_NAME: &str = "nfts";
 pub const APT_DECIMAL: i32 = 100_000_000;
 
 /**
@@ -49,6 +64,23 @@ pub struct NftMarketplaceActivity {
     pub bid_key: Option<i64>,
     pub start_time: Option<i64>,
     pub duration: Option<i64>,
+    /// Coin type or fungible asset metadata address the price is paid in
+    pub currency: Option<String>,
+    /// Part of the price of a sale deposited to the seller
+    pub seller_proceeds: Option<i64>,
+    /// Part of the price of a sale deposited to the marketplace fee account
+    pub marketplace_fee: Option<i64>,
+    /// Part of the price of a sale paid to the creator
+    pub royalty_amount: Option<i64>,
+    /// Other deposits of a sale, one of them is the royalty when the collection has one
+    #[serde(skip)]
+    pub royalty_deposits: Vec<i64>,
+    /// Values remapped directly into the bids, listings, auctions, collections and nfts tables
+    #[serde(default)]
+    pub table_fields: HashMap<String, HashMap<String, String>>,
+    /// Mapped json paths that couldn't be extracted from the event
+    #[serde(default)]
+    pub missing_paths: Vec<String>,
 }
 
 impl From<NftMarketplaceActivity> for DbActivity {
@@ -69,6 +101,10 @@ impl From<NftMarketplaceActivity> for DbActivity {
             block_height: Some(value.block_height),
             usd_price: value.usd_price,
             amount: value.token_amount,
+            currency: value.currency,
+            seller_proceeds: value.seller_proceeds,
+            marketplace_fee: value.marketplace_fee,
+            royalty_amount: value.royalty_amount,
         }
     }
 }
@@ -76,9 +112,15 @@ impl From<NftMarketplaceActivity> for DbActivity {
 impl TryFrom<NftMarketplaceActivity> for DbBid {
     type Error = anyhow::Error;
 
-    fn try_from(value: NftMarketplaceActivity) -> anyhow::Result<Self> {
-        Ok(Self {
+    fn try_from(mut value: NftMarketplaceActivity) -> anyhow::Result<Self> {
+        let table_fields = value.take_table_fields(BIDS_TABLE_NAME);
+        // The amount of accept and cancel events is not what remains on the bid
+        let amount = value.get_created_txn_id().and(value.token_amount);
+
+        let mut bid = Self {
             id: value.get_bid_id().context("Invalid bid")?,
+            tx_index: Some(value.get_tx_index()),
+            created_tx_index: value.get_created_txn_id().map(|_| value.get_tx_index()),
             created_tx_id: value.get_created_txn_id(),
             accepted_tx_id: value.get_accepted_txn_id(),
             cancelled_tx_id: value.get_cancelled_txn_id(),
@@ -92,17 +134,51 @@ impl TryFrom<NftMarketplaceActivity> for DbBid {
             market_name: value.marketplace,
             nonce: value.offer_id,
             bidder: value.buyer,
-            remaining_count: value.token_amount,
+            remaining_count: amount,
+            amount,
             receiver: value.seller,
-        })
+            currency: value.currency,
+            attr_type: None,
+            attr_value: None,
+        };
+
+        for (column, value) in table_fields {
+            if let Ok(field) = BidField::from_str(&column) {
+                bid.set_field(field, value);
+            }
+        }
+
+        Ok(bid)
     }
 }
 
-impl TryFrom<NftMarketplaceActivity> for DbListing {
+impl TryFrom<NftMarketplaceActivity> for DbBidFill {
     type Error = anyhow::Error;
 
     fn try_from(value: NftMarketplaceActivity) -> anyhow::Result<Self> {
+        let tx_id = value.get_accepted_txn_id().context("Not an accept event")?;
+
         Ok(Self {
+            activity_id: generate_activity_id(value.get_tx_index()),
+            bid_id: value.get_bid_id().context("Invalid bid")?,
+            tx_id,
+            tx_index: value.get_tx_index(),
+            nft_id: value.get_nft_id(),
+            seller: value.seller,
+            price: Some(value.price),
+            amount: value.token_amount.unwrap_or(1),
+            block_time: value.block_timestamp,
+        })
+    }
+}
+
+impl TryFrom<NftMarketplaceActivity> for DbListing {
+    type Error = anyhow::Error;
+
+    fn try_from(mut value: NftMarketplaceActivity) -> anyhow::Result<Self> {
+        let table_fields = value.take_table_fields(LISTINGS_TABLE_NAME);
+
+        let mut listing = Self {
             id: value.get_listing_id().context("Invalid listing")?,
             tx_index: Some(value.get_tx_index()),
             listed: value.get_listing_status(),
@@ -115,44 +191,228 @@ impl TryFrom<NftMarketplaceActivity> for DbListing {
             block_time: Some(value.block_timestamp),
             nonce: value.listing_id,
             block_height: Some(value.block_height),
-        })
+            expired_at: None,
+            currency: value.currency,
+        };
+
+        for (column, value) in table_fields {
+            if let Ok(field) = ListingField::from_str(&column) {
+                listing.set_field(field, value);
+            }
+        }
+
+        Ok(listing)
+    }
+}
+
+impl TryFrom<NftMarketplaceActivity> for DbAuction {
+    type Error = anyhow::Error;
+
+    fn try_from(mut value: NftMarketplaceActivity) -> anyhow::Result<Self> {
+        let table_fields = value.take_table_fields(AUCTIONS_TABLE_NAME);
+        // Create events carry the reserve, bid and settle events the bid and its bidder
+        let is_bid = matches!(
+            value.standard_event_type,
+            MarketplaceEventType::AuctionBid | MarketplaceEventType::AuctionSettle
+        );
+        let highest_bidder = value.buyer.clone().filter(|_| is_bid);
+
+        let mut auction = Self {
+            id: value.get_auction_id().context("Invalid auction")?,
+            tx_index: Some(value.get_tx_index()),
+            status: value.get_auction_status(),
+            created_tx_id: value.get_auction_created_txn_id(),
+            created_tx_index: value
+                .get_auction_created_txn_id()
+                .map(|_| value.get_tx_index()),
+            settled_tx_id: value.get_auction_settled_txn_id(),
+            cancelled_tx_id: value.get_auction_cancelled_txn_id(),
+            start_time: value.get_auction_start_time(),
+            end_time: value.get_auction_end_time(),
+            collection_id: value.get_collection_id(),
+            nft_id: value.get_nft_id(),
+            reserve_price: value.get_auction_created_txn_id().map(|_| value.price),
+            highest_bid: highest_bidder.as_ref().map(|_| value.price),
+            highest_bidder,
+            market_contract_id: value.contract_address,
+            market_name: value.marketplace,
+            nonce: value.listing_id,
+            seller: value.seller,
+            currency: value.currency,
+            block_time: Some(value.block_timestamp),
+        };
+
+        for (column, value) in table_fields {
+            if let Ok(field) = AuctionField::from_str(&column) {
+                auction.set_field(field, value);
+            }
+        }
+
+        Ok(auction)
     }
 }
 
 impl TryFrom<NftMarketplaceActivity> for DbCollection {
     type Error = anyhow::Error;
 
-    fn try_from(value: NftMarketplaceActivity) -> anyhow::Result<Self> {
-        Ok(Self {
+    fn try_from(mut value: NftMarketplaceActivity) -> anyhow::Result<Self> {
+        let table_fields = value.take_table_fields(COLLECTIONS_TABLE_NAME);
+
+        let mut collection = Self {
             id: value.get_collection_id().context("Invalid collection")?,
             slug: value.get_slug(),
             title: value.collection_name,
             creator_address: value.creator_address,
             ..Default::default()
-        })
+        };
+
+        for (column, value) in table_fields {
+            if let Ok(field) = CollectionField::from_str(&column) {
+                collection.set_field(field, value);
+            }
+        }
+
+        Ok(collection)
     }
 }
 
 impl TryFrom<NftMarketplaceActivity> for DbNft {
     type Error = anyhow::Error;
 
-    fn try_from(value: NftMarketplaceActivity) -> anyhow::Result<Self> {
-        Ok(Self {
+    fn try_from(mut value: NftMarketplaceActivity) -> anyhow::Result<Self> {
+        let table_fields = value.take_table_fields(NFTS_TABLE_NAME);
+
+        let mut nft = Self {
             id: value.get_nft_id().context("Invalid nft")?,
             collection_id: value.get_collection_id(),
             token_id: value.token_addr,
             name: value.token_name,
             ..Default::default()
-        })
+        };
+
+        for (column, value) in table_fields {
+            if let Ok(field) = NftField::from_str(&column) {
+                nft.set_field(field, value);
+            }
+        }
+
+        Ok(nft)
     }
 }
 
 impl NftMarketplaceActivity {
     pub fn get_tx_index(&self) -> i64 {
-        self.txn_version * 100_000 + self.index
+        self.txn_version * TX_INDEX_MULTIPLIER + self.index
+    }
+
+    /// Whether the activity sells the nft, counted in the sales of its collection
+    pub fn is_sale(&self) -> bool {
+        matches!(
+            self.standard_event_type,
+            MarketplaceEventType::Buy
+                | MarketplaceEventType::AcceptBid
+                | MarketplaceEventType::AcceptCollectionBid
+        )
+    }
+
+    pub fn set_table_field(&mut self, table: &str, column: &str, value: String) {
+        if value.is_empty() {
+            tracing::debug!("Empty value for {}.{}", table, column);
+            return;
+        }
+
+        self.table_fields
+            .entry(table.to_string())
+            .or_default()
+            .insert(column.to_string(), value);
+    }
+
+    fn take_table_fields(&mut self, table: &str) -> HashMap<String, String> {
+        self.table_fields.remove(table).unwrap_or_default()
+    }
+}
+
+impl DbBid {
+    pub fn set_field(&mut self, field: BidField, value: String) {
+        match field {
+            BidField::Price => self.price = value.parse().ok().or(self.price),
+            BidField::ExpiredAt => {
+                self.expired_at = parse_timestamp_secs(&value).or(self.expired_at)
+            }
+            BidField::RemainingCount => {
+                self.remaining_count = value.parse().ok().or(self.remaining_count)
+            }
+            BidField::Nonce => self.nonce = Some(value),
+            BidField::Bidder => self.bidder = Some(value),
+            BidField::Receiver => self.receiver = Some(value),
+            BidField::AttrType => self.attr_type = Some(value),
+            BidField::AttrValue => self.attr_value = Some(value),
+        }
     }
 }
 
+impl DbListing {
+    pub fn set_field(&mut self, field: ListingField, value: String) {
+        match field {
+            ListingField::Price => self.price = value.parse().ok().or(self.price),
+            ListingField::ExpiredAt => {
+                self.expired_at = parse_timestamp_secs(&value).or(self.expired_at)
+            }
+            ListingField::Nonce => self.nonce = Some(value),
+            ListingField::Seller => self.seller = Some(value),
+        }
+    }
+}
+
+impl DbAuction {
+    pub fn set_field(&mut self, field: AuctionField, value: String) {
+        match field {
+            AuctionField::ReservePrice => {
+                self.reserve_price = value.parse().ok().or(self.reserve_price)
+            }
+            AuctionField::EndTime => self.end_time = parse_timestamp_secs(&value).or(self.end_time),
+        }
+    }
+}
+
+impl DbCollection {
+    pub fn set_field(&mut self, field: CollectionField, value: String) {
+        match field {
+            CollectionField::Title => self.title = Some(value),
+            CollectionField::Description => self.description = Some(value),
+            CollectionField::Supply => self.supply = value.parse().ok().or(self.supply),
+            CollectionField::Royalty => {
+                self.royalty = BigDecimal::from_str(&value).ok().or(self.royalty.take())
+            }
+            CollectionField::CoverUrl => self.cover_url = Some(value),
+            CollectionField::Website => self.website = Some(value),
+            CollectionField::Discord => self.discord = Some(value),
+            CollectionField::Twitter => self.twitter = Some(value),
+        }
+    }
+}
+
+impl DbNft {
+    pub fn set_field(&mut self, field: NftField, value: String) {
+        match field {
+            NftField::Name => self.name = Some(value),
+            NftField::Description => self.description = Some(value),
+            NftField::Uri => self.uri = Some(value),
+            NftField::Royalty => {
+                self.royalty = BigDecimal::from_str(&value).ok().or(self.royalty.take())
+            }
+        }
+    }
+}
+
+fn parse_timestamp_secs(value: &str) -> Option<NaiveDateTime> {
+    value
+        .parse::<i64>()
+        .ok()
+        .and_then(|ts| DateTime::from_timestamp(ts, 0))
+        .map(|e| e.naive_utc())
+}
+
 impl MarketplaceModel for NftMarketplaceActivity {
     fn set_field(&mut self, field: MarketplaceField, value: String) {
         if value.is_empty() {
@@ -183,6 +443,7 @@ impl MarketplaceModel for NftMarketplaceActivity {
                 self.block_timestamp = value.parse().unwrap_or(NaiveDateTime::default())
             }
             MarketplaceField::BidKey => self.bid_key = value.parse().ok(),
+            MarketplaceField::Currency => self.currency = Some(value),
             _ => tracing::debug!("Unknown field: {:?}", field),
         }
     }
@@ -221,6 +482,7 @@ impl MarketplaceModel for NftMarketplaceActivity {
             MarketplaceField::ContractAddress => self.contract_address.clone(),
             MarketplaceField::BlockTimestamp => Some(self.block_timestamp.to_string()),
             MarketplaceField::BidKey => self.bid_key.map(|val| val.to_string()),
+            MarketplaceField::Currency => self.currency.clone(),
             _ => None,
         }
     }
@@ -258,13 +520,13 @@ impl BidModel for NftMarketplaceActivity {
     fn get_bid_status(&self) -> Option<String> {
         match self.standard_event_type {
             MarketplaceEventType::SoloBid | MarketplaceEventType::CollectionBid => {
-                Some("active".to_string())
+                Some(BidStatus::Active.to_string())
             }
             MarketplaceEventType::AcceptBid | MarketplaceEventType::AcceptCollectionBid => {
-                Some("matched".to_string())
+                Some(BidStatus::Matched.to_string())
             }
             MarketplaceEventType::UnlistBid | MarketplaceEventType::CancelCollectionBid => {
-                Some("cancelled".to_string())
+                Some(BidStatus::Cancelled.to_string())
             }
             _ => None,
         }
@@ -380,6 +642,75 @@ impl ListingModel for NftMarketplaceActivity {
     }
 }
 
+impl AuctionModel for NftMarketplaceActivity {
+    /// Auctions are keyed by their `listing_id` when the marketplace has one, otherwise by
+    /// the token, so every event of an auction must map the same key
+    fn get_auction_id(&self) -> Option<Uuid> {
+        self.get_auction_status()?;
+
+        let key = self.listing_id.as_ref().or(self.token_addr.as_ref());
+
+        self.contract_address
+            .as_ref()
+            .zip(key)
+            .map(|(contract_address, key)| generate_auction_id(contract_address, key))
+    }
+
+    fn get_auction_status(&self) -> Option<String> {
+        match self.standard_event_type {
+            MarketplaceEventType::AuctionCreate | MarketplaceEventType::AuctionBid => {
+                Some(AuctionStatus::Active.to_string())
+            }
+            MarketplaceEventType::AuctionSettle => Some(AuctionStatus::Settled.to_string()),
+            MarketplaceEventType::AuctionCancel => Some(AuctionStatus::Cancelled.to_string()),
+            _ => None,
+        }
+    }
+
+    fn get_auction_created_txn_id(&self) -> Option<String> {
+        match self.standard_event_type {
+            MarketplaceEventType::AuctionCreate => Some(self.txn_id.clone()),
+            _ => None,
+        }
+    }
+
+    fn get_auction_settled_txn_id(&self) -> Option<String> {
+        match self.standard_event_type {
+            MarketplaceEventType::AuctionSettle => Some(self.txn_id.clone()),
+            _ => None,
+        }
+    }
+
+    fn get_auction_cancelled_txn_id(&self) -> Option<String> {
+        match self.standard_event_type {
+            MarketplaceEventType::AuctionCancel => Some(self.txn_id.clone()),
+            _ => None,
+        }
+    }
+
+    /// The mapped `start_time` in seconds, create events without one start at their block
+    fn get_auction_start_time(&self) -> Option<NaiveDateTime> {
+        match self.start_time {
+            Some(start_time) => DateTime::from_timestamp(start_time, 0).map(|e| e.naive_utc()),
+            None => self
+                .get_auction_created_txn_id()
+                .map(|_| self.block_timestamp),
+        }
+    }
+
+    /// `start_time + duration` in seconds. Bid events mapping them move the end time, e.g.
+    /// when a late bid extends the auction
+    fn get_auction_end_time(&self) -> Option<NaiveDateTime> {
+        let start_time = self
+            .get_auction_start_time()
+            .unwrap_or(self.block_timestamp);
+
+        self.duration
+            .and_then(TimeDelta::try_seconds)
+            .map(|duration| start_time + duration)
+    }
+}
+
 impl CollectionModel for NftMarketplaceActivity {
     fn get_collection_id(&self) -> Option<Uuid> {
         self.collection_addr
@@ -433,6 +764,63 @@ pub enum MarketplaceField {
     BidKey,
     StartTime,
     Duration,
+    Currency,
+}
+
+/// Columns of the bids table that can be remapped directly
+#[derive(Debug, Clone, PartialEq, Display, EnumString)]
+#[strum(serialize_all = "snake_case")]
+pub enum BidField {
+    Price,
+    ExpiredAt,
+    RemainingCount,
+    Nonce,
+    Bidder,
+    Receiver,
+    AttrType,
+    AttrValue,
+}
+
+/// Columns of the listings table that can be remapped directly
+#[derive(Debug, Clone, PartialEq, Display, EnumString)]
+#[strum(serialize_all = "snake_case")]
+pub enum ListingField {
+    Price,
+    ExpiredAt,
+    Nonce,
+    Seller,
+}
+
+/// Columns of the auctions table that can be remapped directly
+#[derive(Debug, Clone, PartialEq, Display, EnumString)]
+#[strum(serialize_all = "snake_case")]
+pub enum AuctionField {
+    ReservePrice,
+    EndTime,
+}
+
+/// Columns of the collections table that can be remapped directly
+#[derive(Debug, Clone, PartialEq, Display, EnumString)]
+#[strum(serialize_all = "snake_case")]
+pub enum CollectionField {
+    Title,
+    Description,
+    Supply,
+    Royalty,
+    CoverUrl,
+    Website,
+    Discord,
+    Twitter,
+}
+
+/// Columns of the nfts table that can be remapped directly
+#[derive(Debug, Clone, PartialEq, Display, EnumString)]
+#[strum(serialize_all = "snake_case")]
+pub enum NftField {
+    Name,
+    Description,
+    Uri,
+    Royalty,
 }
 
 pub trait MarketplaceModel {
@@ -462,6 +850,16 @@ pub trait ListingModel {
     fn get_price(&self) -> Option<i64>;
 }
 
+pub trait AuctionModel {
+    fn get_auction_id(&self) -> Option<Uuid>;
+    fn get_auction_status(&self) -> Option<String>;
+    fn get_auction_created_txn_id(&self) -> Option<String>;
+    fn get_auction_settled_txn_id(&self) -> Option<String>;
+    fn get_auction_cancelled_txn_id(&self) -> Option<String>;
+    fn get_auction_start_time(&self) -> Option<NaiveDateTime>;
+    fn get_auction_end_time(&self) -> Option<NaiveDateTime>;
+}
+
 pub trait CollectionModel {
     fn get_collection_id(&self) -> Option<Uuid>;
     fn get_slug(&self) -> Option<String>;
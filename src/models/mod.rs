@@ -14,6 +14,9 @@ pub mod nft_metadata;
 pub mod resources;
 pub mod schema;
 
+/// Events are ordered by `tx_index = version * TX_INDEX_MULTIPLIER + event_index`
+pub const TX_INDEX_MULTIPLIER: i64 = 100_000;
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct EventModel {
     pub sequence_number: i64,
@@ -99,6 +102,6 @@ impl EventModel {
     }
 
     pub fn get_tx_index(&self) -> i64 {
-        self.transaction_version * 100_000 + self.event_index
+        self.transaction_version * TX_INDEX_MULTIPLIER + self.event_index
     }
 }
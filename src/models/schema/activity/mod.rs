@@ -39,6 +39,14 @@ pub struct ActivitySchema {
     pub block_time: Option<DateTime<Utc>>,
     pub block_height: Option<i64>,
     pub amount: Option<i64>,
+    /// Metadata address of the currency the activity was paid in
+    pub currency: Option<String>,
+    /// Part of the sale price received by the seller
+    pub seller_proceeds: Option<i64>,
+    /// Part of the sale price taken by the marketplace
+    pub marketplace_fee: Option<i64>,
+    /// Part of the sale price paid to the creator
+    pub royalty_amount: Option<i64>,
 }
 
 #[ComplexObject]
@@ -0,0 +1,94 @@
+use std::{str::FromStr, sync::Arc};
+
+use crate::{
+    database::{Database, IDatabase, nfts::Nfts},
+    models::schema::{fetch_usd_price, nft::NftSchema},
+};
+use async_graphql::{ComplexObject, Context, Enum, SimpleObject, dataloader::DataLoader};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+use strum::{Display, EnumString};
+use uuid::Uuid;
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, SimpleObject, FromRow)]
+#[graphql(complex, name = "Auction", rename_fields = "snake_case")]
+pub struct AuctionSchema {
+    pub id: Uuid,
+    pub market_contract_id: Option<String>,
+    pub market_name: Option<String>,
+    pub collection_id: Option<Uuid>,
+    pub nft_id: Option<Uuid>,
+    pub nonce: Option<String>,
+    pub seller: Option<String>,
+    pub reserve_price: Option<i64>,
+    pub highest_bid: Option<i64>,
+    pub highest_bidder: Option<String>,
+    /// Metadata address of the currency the auction is priced in
+    pub currency: Option<String>,
+    pub start_time: Option<DateTime<Utc>>,
+    pub end_time: Option<DateTime<Utc>>,
+    #[graphql(skip)]
+    pub status: Option<String>,
+    pub created_tx_id: Option<String>,
+    pub settled_tx_id: Option<String>,
+    pub cancelled_tx_id: Option<String>,
+    pub tx_index: Option<i64>,
+    pub block_time: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[ComplexObject]
+impl AuctionSchema {
+    async fn status(&self) -> Option<AuctionStatus> {
+        self.status
+            .as_deref()
+            .and_then(|status| AuctionStatus::from_str(status).ok())
+    }
+
+    #[graphql(name = "highest_bid_usd")]
+    async fn highest_bid_usd(&self, ctx: &Context<'_>) -> Option<String> {
+        fetch_usd_price(ctx, self.currency.as_deref(), self.highest_bid?).await
+    }
+
+    async fn nft(&self, ctx: &Context<'_>) -> Option<NftSchema> {
+        let db = ctx
+            .data::<Arc<Database>>()
+            .expect("Missing database in the context");
+
+        let data_loader = DataLoader::new(Nfts::new(Arc::new(db.get_pool().clone())), tokio::spawn);
+
+        if let Some(nft_id) = self.nft_id.as_ref() {
+            data_loader.load_one(nft_id.clone()).await.ok().flatten()
+        } else {
+            None
+        }
+    }
+}
+
+/// Status of an auction, an `active` auction past its `end_time` is waiting to be settled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Enum, Serialize, Deserialize, Display, EnumString)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+#[graphql(name = "AuctionStatus", rename_items = "snake_case")]
+pub enum AuctionStatus {
+    Active,
+    Settled,
+    Cancelled,
+}
+
+/// `live` auctions are active and still taking bids, every other auction has `ended`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Enum, Serialize, Deserialize, Display, EnumString)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+#[graphql(name = "AuctionState", rename_items = "snake_case")]
+pub enum AuctionState {
+    Live,
+    Ended,
+}
+
+impl Default for AuctionState {
+    fn default() -> Self {
+        Self::Live
+    }
+}
@@ -1,15 +1,12 @@
-use std::sync::Arc;
+use std::{str::FromStr, sync::Arc};
 
 use crate::{
-    database::{Database, IDatabase, collections::Collections, nfts::Nfts},
-    models::{
-        marketplace::APT_DECIMAL,
-        schema::{
-            AggregateFieldsSchema, Date, OperatorSchema, OrderingType,
-            collection::{CollectionSchema, OrderCollectionSchema, QueryCollectionSchema},
-            fetch_token_price,
-            nft::{NftSchema, OrderNftSchema, QueryNftSchema},
-        },
+    database::{Database, IDatabase, bids::IBids, collections::Collections, nfts::Nfts},
+    models::schema::{
+        AggregateFieldsSchema, Date, OperatorSchema, OrderingType,
+        collection::{CollectionSchema, OrderCollectionSchema, QueryCollectionSchema},
+        fetch_usd_price,
+        nft::{NftSchema, OrderNftSchema, QueryNftSchema},
     },
 };
 use async_graphql::{
@@ -39,20 +36,57 @@ pub struct BidSchema {
     pub price: Option<i64>,
     pub receiver: Option<String>,
     pub remaining_count: Option<i64>,
+    #[graphql(skip)]
     pub status: Option<String>,
     #[graphql(name = "type")]
     pub bid_type: Option<String>,
     pub updated_at: Option<DateTime<Utc>>,
+    pub tx_index: Option<i64>,
+    /// Number of NFTs asked for when the bid was created
+    pub amount: Option<i64>,
+    /// Metadata address of the currency the bid is priced in
+    pub currency: Option<String>,
+    /// Trait a collection bid is restricted to
+    pub attr_type: Option<String>,
+    pub attr_value: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, SimpleObject, FromRow)]
+#[graphql(name = "BidFill", rename_fields = "snake_case")]
+pub struct BidFillSchema {
+    pub activity_id: Uuid,
+    pub bid_id: Uuid,
+    pub tx_id: String,
+    pub tx_index: i64,
+    pub nft_id: Option<Uuid>,
+    pub seller: Option<String>,
+    pub price: Option<i64>,
+    pub amount: i64,
+    pub block_time: DateTime<Utc>,
 }
 
 #[ComplexObject]
 impl BidSchema {
+    async fn status(&self) -> Option<BidStatus> {
+        self.status
+            .as_deref()
+            .and_then(|status| BidStatus::from_str(status).ok())
+    }
+
     #[graphql(name = "usd_price")]
     async fn usd_price(&self, ctx: &Context<'_>) -> Option<String> {
-        let token_price = fetch_token_price(ctx).await.unwrap_or_default();
+        fetch_usd_price(ctx, self.currency.as_deref(), self.price?).await
+    }
+
+    async fn fills(&self, ctx: &Context<'_>) -> Vec<BidFillSchema> {
+        let db = ctx
+            .data::<Arc<Database>>()
+            .expect("Missing database in the context");
 
-        self.price
-            .map(|e| (BigDecimal::from(e) * token_price / APT_DECIMAL).to_plain_string())
+        db.bids()
+            .fetch_bid_fills(&self.id)
+            .await
+            .unwrap_or_default()
     }
 
     async fn nft(&self, ctx: &Context<'_>) -> Option<NftSchema> {
@@ -145,6 +179,18 @@ pub struct OrderBidSchema {
     pub nft: Option<OrderNftSchema>,
 }
 
+/// Status of a bid, `expired` is set by the bid expiry worker once `expired_at` passes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Enum, Serialize, Deserialize, Display, EnumString)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+#[graphql(name = "BidStatus", rename_items = "snake_case")]
+pub enum BidStatus {
+    Active,
+    Matched,
+    Cancelled,
+    Expired,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Enum, Serialize, Deserialize, Display, EnumString)]
 #[serde(rename_all = "snake_case")]
 #[strum(serialize_all = "snake_case")]
@@ -178,3 +224,25 @@ pub struct AggregateBidFieldsSchema {
 }
 
 pub type AggregateBidSchema = AggregateFieldsSchema<AggregateBidFieldsSchema>;
+
+/// The bid an nft can be sold into right now for the most, and what the seller receives
+#[derive(Clone, Debug, Default, Deserialize, Serialize, SimpleObject, FromRow)]
+#[graphql(complex, name = "SellQuote", rename_fields = "snake_case")]
+pub struct SellQuoteSchema {
+    #[sqlx(flatten)]
+    pub bid: BidSchema,
+    /// Creator royalty taken off the bid price
+    pub royalty_amount: i64,
+    /// Marketplace fee taken off the bid price at the marketplace's `fee_bps`, None when it
+    /// isn't configured
+    pub marketplace_fee: Option<i64>,
+    pub net_proceeds: i64,
+}
+
+#[ComplexObject]
+impl SellQuoteSchema {
+    #[graphql(name = "net_proceeds_usd")]
+    async fn net_proceeds_usd(&self, ctx: &Context<'_>) -> Option<String> {
+        fetch_usd_price(ctx, self.bid.currency.as_deref(), self.net_proceeds).await
+    }
+}
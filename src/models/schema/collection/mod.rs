@@ -4,6 +4,7 @@ pub mod nft_change;
 pub mod nft_distribution;
 pub mod nft_holder;
 pub mod profit_leaderboard;
+pub mod royalty;
 pub mod stat;
 pub mod top_wallet;
 pub mod trending;
@@ -14,7 +15,7 @@ use std::sync::Arc;
 use crate::{
     database::{
         Database, IDatabase, activities::IActivities, attributes::IAttributes, bids::IBids,
-        nfts::INfts,
+        collections::ICollections, nfts::INfts,
     },
     models::schema::{
         AggregateFieldsSchema, AggregateSchema, OperatorSchema, OrderingType,
@@ -27,6 +28,7 @@ use crate::{
             OrderAttributeSchema, QueryAttributeSchema,
         },
         bid::{AggregateBidSchema, BidSchema, DistinctBidSchema, OrderBidSchema, QueryBidSchema},
+        collection::royalty::CollectionRoyaltySchema,
         get_aggregate_selection,
         nft::{AggregateNftSchema, DistinctNftSchema, NftSchema, OrderNftSchema, QueryNftSchema},
     },
@@ -65,6 +67,15 @@ pub struct CollectionSchema {
 
 #[ComplexObject]
 impl CollectionSchema {
+    async fn royalties(&self, ctx: &Context<'_>) -> FieldResult<CollectionRoyaltySchema> {
+        ctx.data::<Arc<Database>>()
+            .map_err(|e| FieldError::from(e))?
+            .collections()
+            .fetch_royalty_stats(self.id)
+            .await
+            .map_err(|e| FieldError::from(e))
+    }
+
     async fn activities(
         &self,
         ctx: &Context<'_>,
@@ -0,0 +1,17 @@
+use async_graphql::SimpleObject;
+use bigdecimal::BigDecimal;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// Royalties paid on the sales of a collection whose price breakdown is known. Amounts in
+/// octas only count the sales paid in APT.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, FromRow, SimpleObject)]
+#[graphql(name = "CollectionRoyalties", rename_fields = "snake_case")]
+pub struct CollectionRoyaltySchema {
+    pub sales: Option<i64>,
+    pub royalties_earned: Option<i64>,
+    pub royalties_earned_usd: Option<BigDecimal>,
+    pub marketplace_fees: Option<i64>,
+    /// Percentage of the sale volume paid as royalty
+    pub royalty_paid_rate: Option<BigDecimal>,
+}
@@ -2,12 +2,9 @@ use std::sync::Arc;
 
 use crate::{
     database::{Database, IDatabase, nfts::Nfts},
-    models::{
-        marketplace::APT_DECIMAL,
-        schema::{
-            AggregateFieldsSchema, Date, OperatorSchema, OrderingType, fetch_token_price,
-            nft::{NftSchema, OrderNftSchema, QueryNftSchema},
-        },
+    models::schema::{
+        AggregateFieldsSchema, Date, OperatorSchema, OrderingType, fetch_usd_price,
+        nft::{NftSchema, OrderNftSchema, QueryNftSchema},
     },
 };
 use async_graphql::{
@@ -34,16 +31,18 @@ pub struct ListingSchema {
     pub price: Option<i64>,
     pub seller: Option<String>,
     pub tx_index: Option<i64>,
+    pub expired_at: Option<DateTime<Utc>>,
+    /// Set when the listing was closed by a transfer, burn or sale outside its marketplace
+    pub invalid_reason: Option<String>,
+    /// Metadata address of the currency the listing is priced in
+    pub currency: Option<String>,
 }
 
 #[ComplexObject]
 impl ListingSchema {
     #[graphql(name = "usd_price")]
     async fn usd_price(&self, ctx: &Context<'_>) -> Option<String> {
-        let token_price = fetch_token_price(ctx).await.unwrap_or_default();
-
-        self.price
-            .map(|e| (BigDecimal::from(e) * token_price / APT_DECIMAL).to_plain_string())
+        fetch_usd_price(ctx, self.currency.as_deref(), self.price?).await
     }
 
     async fn nft(&self, ctx: &Context<'_>) -> Option<NftSchema> {
@@ -82,6 +81,7 @@ pub struct QueryListingSchema {
     pub price: Option<OperatorSchema<i64>>,
     pub seller: Option<OperatorSchema<String>>,
     pub tx_index: Option<OperatorSchema<i64>>,
+    pub expired_at: Option<OperatorSchema<Date>>,
     pub nft: Option<QueryNftSchema>,
 }
 
@@ -100,6 +100,7 @@ pub struct OrderListingSchema {
     pub price: Option<OrderingType>,
     pub seller: Option<OrderingType>,
     pub tx_index: Option<OrderingType>,
+    pub expired_at: Option<OrderingType>,
     pub nft: Option<OrderNftSchema>,
 }
 
@@ -120,6 +121,7 @@ pub enum DistinctListingSchema {
     Price,
     Seller,
     TxIndex,
+    ExpiredAt,
 }
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize, SimpleObject)]
@@ -133,3 +135,43 @@ pub struct AggregateListingFieldsSchema {
 }
 
 pub type AggregateListingSchema = AggregateFieldsSchema<AggregateListingFieldsSchema>;
+
+/// Most listings a sweep quote returns
+pub const MAX_SWEEP_COUNT: i64 = 100;
+
+/// Matches the nfts having one of `values` for the trait `type`, nfts must match every filter
+#[derive(Clone, Debug, Default, Serialize, Deserialize, InputObject)]
+#[graphql(name = "AttributeFilter", rename_fields = "snake_case")]
+pub struct AttributeFilterSchema {
+    #[graphql(name = "type")]
+    pub attr_type: String,
+    pub values: Vec<String>,
+}
+
+/// The cheapest valid APT listings of a collection, one per nft, and what buying all of
+/// them costs
+#[derive(Clone, Debug, Default, Serialize, Deserialize, SimpleObject)]
+#[graphql(complex, name = "SweepQuote", rename_fields = "snake_case")]
+pub struct SweepQuoteSchema {
+    pub listings: Vec<ListingSchema>,
+    pub count: i64,
+    pub total_price: i64,
+}
+
+#[ComplexObject]
+impl SweepQuoteSchema {
+    #[graphql(name = "total_usd_price")]
+    async fn total_usd_price(&self, ctx: &Context<'_>) -> Option<String> {
+        fetch_usd_price(ctx, None, self.total_price).await
+    }
+}
+
+impl From<Vec<ListingSchema>> for SweepQuoteSchema {
+    fn from(listings: Vec<ListingSchema>) -> Self {
+        Self {
+            count: listings.len() as i64,
+            total_price: listings.iter().filter_map(|listing| listing.price).sum(),
+            listings,
+        }
+    }
+}
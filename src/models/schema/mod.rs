@@ -1,4 +1,5 @@
 use crate::{
+    config::currency_config::{APT_TOKEN_ADDR, CurrencyConfig, resolve_currency},
     database::{Database, IDatabase, token_prices::ITokenPrices},
     models::schema::{
         activity::{ActivitySchema, AggregateActivityFieldsSchema, AggregateActivitySchema},
@@ -21,12 +22,15 @@ use uuid::Uuid;
 
 pub mod activity;
 pub mod attribute;
+pub mod auction;
 pub mod bid;
 pub mod collection;
 pub mod data_point;
 pub mod listing;
 pub mod marketplace;
 pub mod nft;
+pub mod nft_ownership;
+pub mod payload;
 pub mod wallet;
 
 pub type Date = DateTime<Utc>;
@@ -251,7 +255,26 @@ async fn fetch_token_price(ctx: &Context<'_>) -> Option<BigDecimal> {
         .expect("Missing database in the context");
 
     db.token_prices()
-        .fetch_token_price("0x000000000000000000000000000000000000000000000000000000000000000a")
+        .fetch_token_price(APT_TOKEN_ADDR)
         .await
         .ok()
 }
+
+/// Converts an amount paid in `currency` to USD, None for currencies that aren't configured
+async fn fetch_usd_price(ctx: &Context<'_>, currency: Option<&str>, amount: i64) -> Option<String> {
+    let currencies = ctx
+        .data::<Vec<CurrencyConfig>>()
+        .expect("Missing currencies in the context");
+    let db = ctx
+        .data::<Arc<Database>>()
+        .expect("Missing database in the context");
+
+    let currency = resolve_currency(currencies, currency)?;
+    let token_price = db
+        .token_prices()
+        .fetch_token_price(&currency.address)
+        .await
+        .unwrap_or_default();
+
+    Some(currency.to_usd(amount, &token_price).to_plain_string())
+}
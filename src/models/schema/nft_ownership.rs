@@ -0,0 +1,49 @@
+use std::sync::Arc;
+
+use async_graphql::{ComplexObject, Context, SimpleObject, dataloader::DataLoader};
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+use uuid::Uuid;
+
+use crate::{
+    database::{Database, IDatabase, nfts::Nfts},
+    models::schema::nft::NftSchema,
+};
+
+/// A wallet holding an nft from a version until the next owner, or a burn, took it
+#[derive(Clone, Debug, Default, Deserialize, Serialize, SimpleObject, FromRow)]
+#[graphql(complex, name = "NftOwnership", rename_fields = "snake_case")]
+pub struct NftOwnershipSchema {
+    pub nft_id: Uuid,
+    pub collection_id: Option<Uuid>,
+    pub owner: String,
+    pub tx_id: String,
+    pub from_version: i64,
+    pub from_time: DateTime<Utc>,
+    /// Unset while the owner still holds the nft
+    pub to_version: Option<i64>,
+    pub to_time: Option<DateTime<Utc>>,
+    /// Token activity the owner acquired the nft by
+    pub activity_id: Option<Uuid>,
+    /// Type of the activity the nft was acquired by, a sale in the same transaction wins
+    /// over the token transfer
+    pub acquired_by: Option<String>,
+    /// Price of the acquiring sale
+    pub price: Option<i64>,
+    pub usd_price: Option<BigDecimal>,
+}
+
+#[ComplexObject]
+impl NftOwnershipSchema {
+    async fn nft(&self, ctx: &Context<'_>) -> Option<NftSchema> {
+        let db = ctx
+            .data::<Arc<Database>>()
+            .expect("Missing database in the context");
+
+        let data_loader = DataLoader::new(Nfts::new(Arc::new(db.get_pool().clone())), tokio::spawn);
+
+        data_loader.load_one(self.nft_id).await.ok().flatten()
+    }
+}
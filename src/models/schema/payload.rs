@@ -0,0 +1,447 @@
+use std::{collections::HashMap, sync::Arc};
+
+use anyhow::Context as _;
+use async_graphql::{Context, SimpleObject, dataloader::Loader};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    config::{
+        currency_config::{CurrencyConfig, resolve_currency},
+        entry_function_config::{EntryFunctionAction, render_template, render_value},
+        marketplace_config::{NFTMarketplaceConfig, find_deployment},
+    },
+    database::{
+        Database, IDatabase, bids::IBids, collections::Collections, listings::IListings, nfts::Nfts,
+    },
+    models::schema::{
+        OperatorSchema,
+        bid::{BidSchema, BidStatus, OrderBidSchema, QueryBidSchema},
+        collection::CollectionSchema,
+        listing::{ListingSchema, OrderListingSchema, QueryListingSchema},
+        nft::NftSchema,
+    },
+};
+
+/// Ready to sign `entry_function_payload` of a marketplace action
+#[derive(Clone, Debug, Default, Deserialize, Serialize, SimpleObject)]
+#[graphql(name = "EntryFunctionPayload", rename_fields = "snake_case")]
+pub struct EntryFunctionPayloadSchema {
+    pub marketplace: String,
+    pub action: String,
+    #[graphql(name = "type")]
+    pub payload_type: String,
+    pub function: String,
+    pub type_arguments: Vec<String>,
+    pub arguments: Vec<serde_json::Value>,
+}
+
+/// Builds the payloads of marketplace actions from the entry functions of the marketplace
+/// configs and the indexed listings, bids, nfts and collections
+pub struct PayloadBuilder<'a> {
+    db: &'a Database,
+    marketplaces: &'a [NFTMarketplaceConfig],
+    currencies: &'a [CurrencyConfig],
+}
+
+type PayloadFields = HashMap<&'static str, String>;
+
+impl<'a> PayloadBuilder<'a> {
+    pub fn new(ctx: &Context<'a>) -> async_graphql::Result<Self> {
+        Ok(Self {
+            db: ctx.data::<Arc<Database>>()?,
+            marketplaces: ctx.data::<Vec<NFTMarketplaceConfig>>()?,
+            currencies: ctx.data::<Vec<CurrencyConfig>>()?,
+        })
+    }
+
+    /// One buy payload per listing, in the given order
+    pub async fn buy(
+        &self,
+        listing_ids: &[Uuid],
+    ) -> anyhow::Result<Vec<EntryFunctionPayloadSchema>> {
+        let query = QueryListingSchema {
+            id: Some(OperatorSchema {
+                _in: Some(listing_ids.to_vec()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let listings = self
+            .db
+            .listings()
+            .fetch_listings(
+                &query,
+                &OrderListingSchema::default(),
+                None,
+                listing_ids.len() as i64,
+                0,
+            )
+            .await?
+            .into_iter()
+            .map(|listing| (listing.id, listing))
+            .collect::<HashMap<_, _>>();
+
+        let nft_ids = listings
+            .values()
+            .filter_map(|listing| listing.nft_id)
+            .collect::<Vec<_>>();
+        let nfts = self.fetch_nfts(&nft_ids).await?;
+        let collections = self.fetch_collections(&nfts).await?;
+
+        let mut payloads = Vec::with_capacity(listing_ids.len());
+        for listing_id in listing_ids {
+            let listing = listings
+                .get(listing_id)
+                .with_context(|| format!("Unknown listing {listing_id}"))?;
+            let nft = listing
+                .nft_id
+                .and_then(|nft_id| nfts.get(&nft_id))
+                .with_context(|| format!("Unknown nft of listing {listing_id}"))?;
+
+            // Same validity as the listings a sweep picks
+            let is_expired = listing
+                .expired_at
+                .is_some_and(|expired_at| expired_at <= Utc::now());
+            if listing.listed != Some(true)
+                || listing.invalid_reason.is_some()
+                || listing.price.is_none()
+                || is_expired
+                || nft.burned == Some(true)
+            {
+                anyhow::bail!("Listing {listing_id} is not active");
+            }
+
+            let mut fields = PayloadFields::new();
+            insert_nft_fields(&mut fields, nft, &collections);
+            insert_listing_fields(&mut fields, listing);
+            self.insert_currency_fields(&mut fields, listing.currency.as_deref());
+
+            let marketplace = self.find_deployment(listing.market_contract_id.as_deref())?;
+            payloads.push(build_payload(
+                &marketplace,
+                EntryFunctionAction::Buy,
+                fields,
+            )?);
+        }
+
+        Ok(payloads)
+    }
+
+    pub async fn list(
+        &self,
+        marketplace: &str,
+        nft_id: Uuid,
+        price: i64,
+        currency: Option<&str>,
+        expiration_time: Option<i64>,
+    ) -> anyhow::Result<EntryFunctionPayloadSchema> {
+        let nfts = self.fetch_nfts(&[nft_id]).await?;
+        let nft = nfts
+            .get(&nft_id)
+            .with_context(|| format!("Unknown nft {nft_id}"))?;
+        let collections = self.fetch_collections(&nfts).await?;
+
+        let mut fields = PayloadFields::new();
+        insert_nft_fields(&mut fields, nft, &collections);
+        insert_order_fields(&mut fields, price, 1, expiration_time);
+        self.insert_currency_fields(&mut fields, currency);
+        if let Some(owner) = nft.owner.as_ref() {
+            fields.insert("seller", owner.clone());
+        }
+
+        let marketplace = self.find_marketplace(marketplace)?;
+        build_payload(&marketplace, EntryFunctionAction::List, fields)
+    }
+
+    pub async fn bid(
+        &self,
+        marketplace: &str,
+        nft_id: Uuid,
+        price: i64,
+        currency: Option<&str>,
+        expiration_time: Option<i64>,
+    ) -> anyhow::Result<EntryFunctionPayloadSchema> {
+        let nfts = self.fetch_nfts(&[nft_id]).await?;
+        let nft = nfts
+            .get(&nft_id)
+            .with_context(|| format!("Unknown nft {nft_id}"))?;
+        let collections = self.fetch_collections(&nfts).await?;
+
+        let mut fields = PayloadFields::new();
+        insert_nft_fields(&mut fields, nft, &collections);
+        insert_order_fields(&mut fields, price, 1, expiration_time);
+        self.insert_currency_fields(&mut fields, currency);
+
+        let marketplace = self.find_marketplace(marketplace)?;
+        build_payload(&marketplace, EntryFunctionAction::Bid, fields)
+    }
+
+    pub async fn collection_bid(
+        &self,
+        marketplace: &str,
+        collection_id: Uuid,
+        price: i64,
+        amount: i64,
+        currency: Option<&str>,
+        expiration_time: Option<i64>,
+    ) -> anyhow::Result<EntryFunctionPayloadSchema> {
+        let collection = self.fetch_collection(collection_id).await?;
+
+        let mut fields = PayloadFields::new();
+        insert_collection_fields(&mut fields, &collection);
+        insert_order_fields(&mut fields, price, amount, expiration_time);
+        self.insert_currency_fields(&mut fields, currency);
+
+        let marketplace = self.find_marketplace(marketplace)?;
+        build_payload(&marketplace, EntryFunctionAction::CollectionBid, fields)
+    }
+
+    /// Cancels a solo or a collection bid, depending on the bid type
+    pub async fn cancel_bid(&self, bid_id: Uuid) -> anyhow::Result<EntryFunctionPayloadSchema> {
+        let query = QueryBidSchema {
+            id: Some(OperatorSchema {
+                _eq: Some(bid_id),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let bid = self
+            .db
+            .bids()
+            .fetch_bids(&query, &OrderBidSchema::default(), None, 1, 0)
+            .await?
+            .into_iter()
+            .next()
+            .with_context(|| format!("Unknown bid {bid_id}"))?;
+        if bid.status != Some(BidStatus::Active.to_string()) {
+            anyhow::bail!("Bid {bid_id} is not active");
+        }
+
+        let mut fields = PayloadFields::new();
+        let action = match bid.bid_type.as_deref() {
+            Some("solo") => {
+                let nft_id = bid.nft_id.context("Solo bid without nft")?;
+                let nfts = self.fetch_nfts(&[nft_id]).await?;
+                let nft = nfts
+                    .get(&nft_id)
+                    .with_context(|| format!("Unknown nft {nft_id}"))?;
+                let collections = self.fetch_collections(&nfts).await?;
+
+                insert_nft_fields(&mut fields, nft, &collections);
+                EntryFunctionAction::CancelBid
+            }
+            Some("collection") => {
+                let collection_id = bid
+                    .collection_id
+                    .context("Collection bid without collection")?;
+                let collection = self.fetch_collection(collection_id).await?;
+
+                insert_collection_fields(&mut fields, &collection);
+                EntryFunctionAction::CancelCollectionBid
+            }
+            bid_type => anyhow::bail!("Unsupported bid type {bid_type:?}"),
+        };
+
+        insert_bid_fields(&mut fields, &bid);
+        self.insert_currency_fields(&mut fields, bid.currency.as_deref());
+
+        let marketplace = self.find_deployment(bid.market_contract_id.as_deref())?;
+        build_payload(&marketplace, action, fields)
+    }
+
+    async fn fetch_nfts(&self, nft_ids: &[Uuid]) -> anyhow::Result<HashMap<Uuid, NftSchema>> {
+        Nfts::new(Arc::new(self.db.get_pool().clone()))
+            .load(nft_ids)
+            .await
+            .map_err(|e| anyhow::anyhow!(e.message))
+            .context("Failed to fetch nfts")
+    }
+
+    async fn fetch_collections(
+        &self,
+        nfts: &HashMap<Uuid, NftSchema>,
+    ) -> anyhow::Result<HashMap<Uuid, CollectionSchema>> {
+        let collection_ids = nfts
+            .values()
+            .filter_map(|nft| nft.collection_id)
+            .collect::<Vec<_>>();
+
+        Collections::new(Arc::new(self.db.get_pool().clone()))
+            .load(&collection_ids)
+            .await
+            .map_err(|e| anyhow::anyhow!(e.message))
+            .context("Failed to fetch collections")
+    }
+
+    async fn fetch_collection(&self, collection_id: Uuid) -> anyhow::Result<CollectionSchema> {
+        Collections::new(Arc::new(self.db.get_pool().clone()))
+            .load(&[collection_id])
+            .await
+            .map_err(|e| anyhow::anyhow!(e.message))
+            .context("Failed to fetch collection")?
+            .remove(&collection_id)
+            .with_context(|| format!("Unknown collection {collection_id}"))
+    }
+
+    /// The deployment a listing or bid was made on
+    fn find_deployment(
+        &self,
+        contract_address: Option<&str>,
+    ) -> anyhow::Result<NFTMarketplaceConfig> {
+        let contract_address = contract_address.context("Missing marketplace contract")?;
+
+        find_deployment(self.marketplaces, contract_address)
+            .with_context(|| format!("Unknown marketplace contract {contract_address}"))
+    }
+
+    /// The latest deployment of a marketplace, new orders go to it
+    fn find_marketplace(&self, name: &str) -> anyhow::Result<NFTMarketplaceConfig> {
+        self.marketplaces
+            .iter()
+            .find(|config| config.name == name)
+            .map(|config| config.get_latest_deployment())
+            .with_context(|| format!("Unknown marketplace {name}"))
+    }
+
+    /// Orders without a currency are in APT, unconfigured currencies are passed as is
+    fn insert_currency_fields(&self, fields: &mut PayloadFields, currency: Option<&str>) {
+        match resolve_currency(self.currencies, currency) {
+            Some(config) => {
+                fields.insert("currency", config.address);
+                if let Some(coin_type) = config.coin_type {
+                    fields.insert("coin_type", coin_type);
+                }
+            }
+            None => {
+                if let Some(currency) = currency {
+                    fields.insert("currency", currency.to_string());
+                }
+            }
+        }
+    }
+}
+
+fn build_payload(
+    marketplace: &NFTMarketplaceConfig,
+    action: EntryFunctionAction,
+    mut fields: PayloadFields,
+) -> anyhow::Result<EntryFunctionPayloadSchema> {
+    let entry_function = marketplace
+        .entry_functions
+        .get(&action)
+        .with_context(|| format!("{} has no {action} entry function", marketplace.name))?;
+
+    fields.insert("contract_address", marketplace.contract_address.clone());
+
+    let type_arguments = entry_function
+        .type_args
+        .iter()
+        .map(|type_arg| render_template(type_arg, &fields))
+        .collect::<anyhow::Result<Vec<_>>>()
+        .with_context(|| format!("Failed to render {action} of {}", marketplace.name))?;
+
+    let arguments = entry_function
+        .args
+        .iter()
+        .map(|arg| render_value(arg, &fields))
+        .collect::<anyhow::Result<Vec<_>>>()
+        .with_context(|| format!("Failed to render {action} of {}", marketplace.name))?;
+
+    Ok(EntryFunctionPayloadSchema {
+        marketplace: marketplace.name.clone(),
+        action: action.to_string(),
+        payload_type: "entry_function_payload".to_string(),
+        function: entry_function.get_function(&marketplace.contract_address),
+        type_arguments,
+        arguments,
+    })
+}
+
+fn insert_nft_fields(
+    fields: &mut PayloadFields,
+    nft: &NftSchema,
+    collections: &HashMap<Uuid, CollectionSchema>,
+) {
+    if let Some(token_id) = nft.token_id.as_ref() {
+        fields.insert("token_addr", token_id.clone());
+    }
+    if let Some(name) = nft.name.as_ref() {
+        fields.insert("token_name", name.clone());
+    }
+    // v1 tokens are traded at their default property version
+    fields.insert("property_version", "0".to_string());
+
+    if let Some(collection) = nft
+        .collection_id
+        .and_then(|collection_id| collections.get(&collection_id))
+    {
+        insert_collection_fields(fields, collection);
+    }
+}
+
+fn insert_collection_fields(fields: &mut PayloadFields, collection: &CollectionSchema) {
+    // The slug of a v2 collection is its object address
+    if let Some(slug) = collection
+        .slug
+        .as_ref()
+        .filter(|slug| slug.starts_with("0x"))
+    {
+        fields.insert("collection_addr", slug.clone());
+    }
+    if let Some(title) = collection.title.as_ref() {
+        fields.insert("collection_name", title.clone());
+    }
+    if let Some(creator_address) = collection.creator_address.as_ref() {
+        fields.insert("creator_address", creator_address.clone());
+    }
+}
+
+fn insert_listing_fields(fields: &mut PayloadFields, listing: &ListingSchema) {
+    if let Some(price) = listing.price {
+        fields.insert("price", price.to_string());
+    }
+    if let Some(nonce) = listing.nonce.as_ref() {
+        fields.insert("nonce", nonce.clone());
+    }
+    if let Some(seller) = listing.seller.as_ref() {
+        fields.insert("seller", seller.clone());
+    }
+    if let Some(expired_at) = listing.expired_at {
+        fields.insert("expiration_time", expired_at.timestamp().to_string());
+    }
+}
+
+fn insert_bid_fields(fields: &mut PayloadFields, bid: &BidSchema) {
+    if let Some(price) = bid.price {
+        fields.insert("price", price.to_string());
+    }
+    if let Some(amount) = bid.remaining_count.or(bid.amount) {
+        fields.insert("amount", amount.to_string());
+    }
+    if let Some(nonce) = bid.nonce.as_ref() {
+        fields.insert("nonce", nonce.clone());
+    }
+    if let Some(bidder) = bid.bidder.as_ref() {
+        fields.insert("bidder", bidder.clone());
+    }
+    if let Some(expired_at) = bid.expired_at {
+        fields.insert("expiration_time", expired_at.timestamp().to_string());
+    }
+}
+
+fn insert_order_fields(
+    fields: &mut PayloadFields,
+    price: i64,
+    amount: i64,
+    expiration_time: Option<i64>,
+) {
+    fields.insert("price", price.to_string());
+    fields.insert("amount", amount.to_string());
+    if let Some(expiration_time) = expiration_time {
+        fields.insert("expiration_time", expiration_time.to_string());
+    }
+}
@@ -14,6 +14,7 @@ use uuid::Uuid;
 pub struct NftHoldingPeriodSchema {
     pub collection_id: Option<Uuid>,
     pub nft_id: Option<Uuid>,
+    /// Seconds the wallet held the nft, up to now while it still holds it
     pub period: Option<BigDecimal>,
 }
 
@@ -35,6 +35,12 @@ pub fn generate_bid_id(market_contract_address: &str, token_id: &str, bidder: &s
     )
 }
 
+pub fn generate_auction_id(market_contract_address: &str, auction_key: &str) -> Uuid {
+    generate_uuid_from_str(
+        format!("auction::{}::{}", market_contract_address, auction_key).as_str(),
+    )
+}
+
 pub fn generate_marketplace_id(market_contract_address: &str, market_name: &str) -> Uuid {
     generate_uuid_from_str(format!("{}::{}", market_contract_address, market_name).as_str())
 }
@@ -53,6 +59,21 @@ pub fn generate_attribute_id(
         .as_str(),
     )
 }
+pub fn generate_failed_event_id(
+    marketplace: &str,
+    contract_address: &str,
+    tx_version: i64,
+    event_index: i64,
+) -> Uuid {
+    generate_uuid_from_str(
+        format!(
+            "{}::{}::{}::{}",
+            marketplace, contract_address, tx_version, event_index
+        )
+        .as_str(),
+    )
+}
+
 pub fn generate_request_log_id(api_key_id: &str, ts: i64) -> Uuid {
     generate_uuid_from_str(format!("{}::{}", api_key_id, ts.to_string()).as_str())
 }
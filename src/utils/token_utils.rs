@@ -425,10 +425,72 @@ pub struct DepositEventType {
     pub amount: BigDecimal,
 }
 
+/// Withdraw module event of the coin module, which also tells the coin type
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CoinWithdrawEventType {
+    pub coin_type: String,
+    pub account: String,
+    #[serde(deserialize_with = "deserialize_from_string")]
+    pub amount: BigDecimal,
+}
+
+impl CoinWithdrawEventType {
+    pub fn get_account(&self) -> String {
+        standardize_address(&self.account)
+    }
+}
+
+/// Withdraw event of a fungible store, its owner and metadata are in the store's resources
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FungibleAssetWithdrawEventType {
+    pub store: String,
+    #[serde(deserialize_with = "deserialize_from_string")]
+    pub amount: BigDecimal,
+}
+
+impl FungibleAssetWithdrawEventType {
+    pub fn get_store(&self) -> String {
+        standardize_address(&self.store)
+    }
+}
+
+/// Deposit module event of the coin module, which also tells the coin type
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CoinDepositEventType {
+    pub coin_type: String,
+    pub account: String,
+    #[serde(deserialize_with = "deserialize_from_string")]
+    pub amount: BigDecimal,
+}
+
+impl CoinDepositEventType {
+    pub fn get_account(&self) -> String {
+        standardize_address(&self.account)
+    }
+}
+
+/// Deposit event of a fungible store, its owner and metadata are in the store's resources
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FungibleAssetDepositEventType {
+    pub store: String,
+    #[serde(deserialize_with = "deserialize_from_string")]
+    pub amount: BigDecimal,
+}
+
+impl FungibleAssetDepositEventType {
+    pub fn get_store(&self) -> String {
+        standardize_address(&self.store)
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum CoinEvent {
     WithdrawEvent(WithdrawEventType),
     DepositEvent(DepositEventType),
+    CoinWithdrawEvent(CoinWithdrawEventType),
+    FungibleAssetWithdrawEvent(FungibleAssetWithdrawEventType),
+    CoinDepositEvent(CoinDepositEventType),
+    FungibleAssetDepositEvent(FungibleAssetDepositEventType),
 }
 
 impl CoinEvent {
@@ -440,6 +502,17 @@ impl CoinEvent {
             "0x1::coin::DepositEvent" => {
                 serde_json::from_str(data).map(|inner| Some(Self::DepositEvent(inner)))
             }
+            "0x1::coin::CoinWithdraw" => {
+                serde_json::from_str(data).map(|inner| Some(Self::CoinWithdrawEvent(inner)))
+            }
+            "0x1::fungible_asset::Withdraw" => serde_json::from_str(data)
+                .map(|inner| Some(Self::FungibleAssetWithdrawEvent(inner))),
+            "0x1::coin::CoinDeposit" => {
+                serde_json::from_str(data).map(|inner| Some(Self::CoinDepositEvent(inner)))
+            }
+            "0x1::fungible_asset::Deposit" => {
+                serde_json::from_str(data).map(|inner| Some(Self::FungibleAssetDepositEvent(inner)))
+            }
             _ => Ok(None),
         }
         .context(format!(
@@ -0,0 +1,48 @@
+use std::sync::Arc;
+
+use crate::{
+    database::{IDatabase, bids::IBids},
+    utils::shutdown_utils,
+};
+
+/// Moves active bids past their `expired_at` to the `expired` status
+pub struct BidExpiryWorker<TDb: IDatabase> {
+    db: Arc<TDb>,
+}
+
+impl<TDb: IDatabase> BidExpiryWorker<TDb>
+where
+    TDb: IDatabase + Send + Sync + 'static,
+{
+    pub fn new(db: Arc<TDb>) -> Self {
+        Self { db }
+    }
+
+    pub async fn start(&self) -> anyhow::Result<()> {
+        let cancel_token = shutdown_utils::get_shutdown_token();
+        tokio::select! {
+            _ = async {
+                loop {
+                    if cancel_token.is_cancelled() {
+                        break;
+                    }
+
+                    match self.db.bids().expire_bids().await {
+                        Ok(res) if res.rows_affected() > 0 => {
+                            tracing::info!("Expired {} bids", res.rows_affected());
+                        }
+                        Ok(_) => {}
+                        Err(e) => tracing::error!("Failed to expire bids: {e:#}"),
+                    }
+
+                    tokio::time::sleep(std::time::Duration::from_secs(60)).await;
+                }
+            } => {},
+            _ = cancel_token.cancelled() => {
+                tracing::info!("Bid expiry worker finished");
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -3,13 +3,11 @@ use std::sync::Arc;
 use aptos_indexer_processor_sdk::{
     aptos_indexer_transaction_stream::{
         BooleanTransactionFilter, EventFilterBuilder, MoveStructTagFilterBuilder,
-        TransactionRootFilterBuilder, TransactionStreamConfig,
+        TransactionRootFilterBuilder,
     },
     aptos_protos::transaction::v1::transaction::TransactionType,
     builder::ProcessorBuilder,
-    common_steps::{
-        DEFAULT_UPDATE_PROCESSOR_STATUS_SECS, TransactionStreamStep, VersionTrackerStep,
-    },
+    common_steps::{DEFAULT_UPDATE_PROCESSOR_STATUS_SECS, VersionTrackerStep},
     traits::IntoRunnableStep,
 };
 use futures::future::join_all;
@@ -21,10 +19,11 @@ use crate::{
     utils::shutdown_utils,
     workers::steps::{
         marketplace::{
-            db_writing_step::DBWritingStep, reduction_step::NFTReductionStep,
-            remapping_step::RemappingStep,
+            archive_step::EventArchiveStep, db_writing_step::DBWritingStep,
+            reduction_step::NFTReductionStep, remapping_step::RemappingStep,
         },
         processor_status_saver_step::DbProcessorStatusSaver,
+        transaction_source_step::TransactionSourceStep,
     },
 };
 
@@ -48,17 +47,23 @@ where
             return Ok(());
         }
 
+        let deployments = self
+            .config
+            .nft_marketplace_configs
+            .iter()
+            .flat_map(|config| config.get_deployments())
+            .collect::<Vec<_>>();
+
+        // A marketplace is listed once, under its top level contract, whatever its deployments
         self.db
             .marketplaces()
             .insert_market_places(&self.config.nft_marketplace_configs)
             .await?;
 
-        let pool_futures = self
-            .config
-            .nft_marketplace_configs
+        let pool_futures = deployments
             .iter()
-            .map(|config| async move {
-                if let Err(e) = self.stream_marketplace_event(config).await {
+            .map(|(processor_name, config)| async move {
+                if let Err(e) = self.stream_marketplace_event(processor_name, config).await {
                     tracing::error!(
                         err = ?e,
                         marketplace = %config.name,
@@ -74,11 +79,15 @@ where
         Ok(())
     }
 
-    async fn stream_marketplace_event(&self, config: &NFTMarketplaceConfig) -> anyhow::Result<()> {
+    async fn stream_marketplace_event(
+        &self,
+        processor_name: &str,
+        config: &NFTMarketplaceConfig,
+    ) -> anyhow::Result<()> {
         let starting_version = self
             .db
             .processor_status()
-            .get_starting_version(&config.name)
+            .get_starting_version(processor_name)
             .await
             .unwrap_or(config.starting_version);
 
@@ -101,38 +110,36 @@ where
 
         let filter = BooleanTransactionFilter::from(tx_filter).and(sc_addr_filter);
 
-        let transaction_stream = TransactionStreamStep::new(TransactionStreamConfig {
-            indexer_grpc_data_service_address: url::Url::parse(
-                &self.config.stream_config.indexer_grpc,
-            )?,
-            starting_version: Some(starting_version as u64),
+        let transaction_stream = TransactionSourceStep::new(
+            &self.config.stream_config,
+            starting_version as u64,
             request_ending_version,
-            auth_token: self.config.stream_config.auth_token.clone(),
-            request_name_header: "marketplace-event-processor".to_string(),
-            additional_headers: Default::default(),
-            indexer_grpc_http2_ping_interval_secs: 30,
-            indexer_grpc_http2_ping_timeout_secs: 10,
-            indexer_grpc_reconnection_timeout_secs: 5,
-            indexer_grpc_response_item_timeout_secs: 60,
-            indexer_grpc_reconnection_max_retries: 5,
-            transaction_filter: Some(filter),
-        })
+            filter,
+            "marketplace-event-processor",
+        )
         .await?;
 
         let name = config.name.as_str();
 
+        let archive_step = EventArchiveStep::new(config, Arc::clone(&self.db));
         let remapping_step = RemappingStep::new(config.clone())?;
-        let reduction_step =
-            NFTReductionStep::new(name, Arc::clone(&self.db), Arc::clone(&self.cache));
+        let reduction_step = NFTReductionStep::new(
+            name,
+            Arc::clone(&self.db),
+            Arc::clone(&self.cache),
+            self.config.currencies.clone(),
+            self.config.price_history_config.clone(),
+        );
         let db_writing_step = DBWritingStep::new(name, Arc::clone(&self.db));
         let version_tracker_step = VersionTrackerStep::new(
-            DbProcessorStatusSaver::new(config.name.clone(), Arc::clone(&self.db)),
+            DbProcessorStatusSaver::new(processor_name.to_string(), Arc::clone(&self.db)),
             DEFAULT_UPDATE_PROCESSOR_STATUS_SECS,
         );
 
         let (_, buffer_receiver) = ProcessorBuilder::new_with_inputless_first_step(
             transaction_stream.into_runnable_step(),
         )
+        .connect_to(archive_step.into_runnable_step(), 10)
         .connect_to(remapping_step.into_runnable_step(), 10)
         .connect_to(reduction_step.into_runnable_step(), 10)
         .connect_to(db_writing_step.into_runnable_step(), 10)
@@ -163,7 +170,7 @@ where
                 }
             } => {},
             _ = cancel_token.cancelled() => {
-                tracing::info!("{} marketplace worker finished", config.name.as_str());
+                tracing::info!("{} marketplace worker finished", processor_name);
             }
         }
 
@@ -1,6 +1,8 @@
 pub mod attribute_worker;
+pub mod bid_expiry_worker;
 pub mod marketplace_processor;
 pub mod price_indexer;
+pub mod price_sources;
 pub mod steps;
 pub mod token_processor;
 
@@ -15,8 +17,9 @@ use crate::{
     database::IDatabase,
     utils::shutdown_utils,
     workers::{
-        attribute_worker::AttributeWorker, marketplace_processor::MarketplaceProcessor,
-        price_indexer::PriceIndexer, token_processor::TokenProcessor,
+        attribute_worker::AttributeWorker, bid_expiry_worker::BidExpiryWorker,
+        marketplace_processor::MarketplaceProcessor, price_indexer::PriceIndexer,
+        token_processor::TokenProcessor,
     },
 };
 
@@ -25,6 +28,7 @@ pub struct Worker<TDb: IDatabase, TCache: ICache> {
     token_processor: Arc<TokenProcessor<TDb>>,
     price_indexer: Arc<PriceIndexer<TDb, TCache>>,
     attribute_worker: Arc<AttributeWorker<TDb>>,
+    bid_expiry_worker: Arc<BidExpiryWorker<TDb>>,
 }
 
 impl<TDb, TCache> Worker<TDb, TCache>
@@ -41,11 +45,13 @@ where
             )),
             token_processor: Arc::new(TokenProcessor::new(Arc::clone(&config), Arc::clone(&db))),
             price_indexer: Arc::new(PriceIndexer::new(
-                config.tapp_url.clone(),
+                config.price_indexer_config.clone(),
+                config.currencies.clone(),
                 Arc::clone(&db),
                 Arc::clone(&cache),
             )),
             attribute_worker: Arc::new(AttributeWorker::new(Arc::clone(&db))),
+            bid_expiry_worker: Arc::new(BidExpiryWorker::new(Arc::clone(&db))),
         }
     }
 
@@ -62,6 +68,8 @@ where
         tracker.spawn(async move { tk_self.token_processor.start().await });
         let attr_self = Arc::clone(self);
         tracker.spawn(async move { attr_self.attribute_worker.start().await });
+        let bid_self = Arc::clone(self);
+        tracker.spawn(async move { bid_self.bid_expiry_worker.start().await });
 
         let cancel_token = shutdown_utils::get_shutdown_token();
         tokio::select! {
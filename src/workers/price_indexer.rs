@@ -1,40 +1,23 @@
 use crate::{
     cache::ICache,
+    config::{
+        currency_config::CurrencyConfig,
+        price_config::{PriceIndexerConfig, PriceSelection},
+    },
     database::{IDatabase, token_prices::ITokenPrices},
     models::db::token_price::DbTokenPrice,
     utils::shutdown_utils,
+    workers::price_sources::{PriceQuote, PriceSource, build_price_sources},
 };
-use aptos_indexer_processor_sdk::utils::convert::deserialize_from_string;
 use bigdecimal::BigDecimal;
-use chrono::{Datelike, TimeZone, Timelike, Utc};
-use reqwest::Client;
-use serde::{Deserialize, Serialize};
-use std::{sync::Arc, time::Duration};
+use chrono::{DateTime, DurationRound, TimeDelta, Utc};
+use std::{collections::BTreeSet, ops::Bound::Excluded, sync::Arc, time::Duration};
 use tokio::time::sleep;
 
-#[derive(Debug, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct Price {
-    base_currency: String,
-    name: String,
-    price_decimals: i32,
-    quote_currency: String,
-    #[serde(deserialize_with = "deserialize_from_string")]
-    price: BigDecimal,
-    #[serde(rename = "type")]
-    type_: String,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct PriceResponse {
-    id: i32,
-    jsonrpc: String,
-    method: String,
-    result: Price,
-}
-
 pub struct PriceIndexer<TDb: IDatabase, TCache: ICache> {
-    tapp_url: String,
+    config: PriceIndexerConfig,
+    sources: Vec<Box<dyn PriceSource>>,
+    currencies: Vec<CurrencyConfig>,
     db: Arc<TDb>,
     cache: Arc<TCache>,
 }
@@ -44,17 +27,22 @@ where
     TDb: IDatabase + Send + Sync + 'static,
     TCache: ICache + 'static,
 {
-    pub fn new(tapp_url: String, db: Arc<TDb>, cache: Arc<TCache>) -> Self {
+    pub fn new(
+        config: PriceIndexerConfig,
+        currencies: Vec<CurrencyConfig>,
+        db: Arc<TDb>,
+        cache: Arc<TCache>,
+    ) -> Self {
         Self {
-            tapp_url,
+            sources: build_price_sources(&config.sources),
+            config,
+            currencies,
             db,
             cache,
         }
     }
 
     pub async fn start(&self) -> anyhow::Result<()> {
-        let client = Client::new();
-
         let cancel_token = shutdown_utils::get_shutdown_token();
         tokio::select! {
             _ = async {
@@ -63,11 +51,11 @@ where
                         break;
                     }
 
-                    if let Err(e) = self.fetch_and_store_token_prices(&client).await {
+                    if let Err(e) = self.fetch_and_store_token_prices().await {
                         tracing::error!("Failed to fetch and store prices: {e:#}");
                     }
 
-                    sleep(Duration::from_secs(5 * 60)).await;
+                    sleep(Duration::from_secs(self.config.interval_secs)).await;
                 }
             } => {},
             _ = cancel_token.cancelled() => {
@@ -78,48 +66,208 @@ where
         Ok(())
     }
 
-    pub async fn fetch_and_store_token_prices(&self, client: &Client) -> anyhow::Result<()> {
-        let now = Utc::now();
-        let rounded = Utc
-            .with_ymd_and_hms(
-                now.year(),
-                now.month(),
-                now.day(),
-                now.hour(),
-                now.minute(),
-                0,
-            )
-            .unwrap();
-
-        let body = serde_json::json!({
-            "method": "public/get_index_price",
-            "jsonrpc": "2.0",
-            "id": now.timestamp(),
-            "params": {
-                "name": "0x000000000000000000000000000000000000000000000000000000000000000a_usd"
+    /// Fetches and stores the USD price of every configured currency. A currency that
+    /// fails doesn't stop the others from being priced.
+    pub async fn fetch_and_store_token_prices(&self) -> anyhow::Result<()> {
+        let mut failed = 0;
+        for currency in self.currencies.iter() {
+            if let Err(e) = self.fetch_and_store_token_price(currency).await {
+                tracing::error!("Failed to fetch and store {} price: {e:#}", currency.symbol);
+                failed += 1;
+
+                self.check_staleness(currency).await;
             }
-        });
+        }
 
-        let value = client
-            .post(&self.tapp_url)
-            .json(&body)
-            .send()
-            .await?
-            .json::<PriceResponse>()
-            .await?;
+        if failed == self.currencies.len() {
+            anyhow::bail!("Failed to fetch the price of every currency");
+        }
+
+        Ok(())
+    }
+
+    async fn fetch_and_store_token_price(&self, currency: &CurrencyConfig) -> anyhow::Result<()> {
+        let now = Utc::now();
+        let rounded = now.duration_trunc(TimeDelta::minutes(1))?;
 
+        let price = self
+            .select_price(currency, now)
+            .await
+            .ok_or_else(|| anyhow::anyhow!("No source has a fresh price"))?;
+
+        // Prices are looked up by the configured address, whatever form a source uses
         self.cache
-            .set_token_price(&value.result.base_currency, value.result.price.clone())
+            .set_token_price(&currency.address, price.clone())
             .await;
         self.db
             .token_prices()
             .insert_token_price(&DbTokenPrice {
-                token_address: value.result.base_currency,
-                price: value.result.price,
+                token_address: currency.address.clone(),
+                price,
                 created_at: rounded,
             })
             .await?;
 
         Ok(())
     }
+
+    /// Selects the price of a currency from the fresh quotes of the sources pricing it
+    async fn select_price(
+        &self,
+        currency: &CurrencyConfig,
+        now: DateTime<Utc>,
+    ) -> Option<BigDecimal> {
+        let mut prices = Vec::new();
+        for source in self.sources.iter() {
+            let id = match source.get_id(currency) {
+                Some(id) => id,
+                None => continue,
+            };
+
+            match source.fetch_price(&id).await {
+                Ok(quote) if self.is_fresh(&quote, now) => {
+                    if self.config.selection == PriceSelection::Fallback {
+                        return Some(quote.price);
+                    }
+
+                    prices.push(quote.price);
+                }
+                Ok(quote) => tracing::warn!(
+                    "Ignoring stale {} price from {}, last updated at {}",
+                    currency.symbol,
+                    source.name(),
+                    quote.timestamp
+                ),
+                Err(e) => tracing::warn!(
+                    "Failed to fetch {} price from {}: {e:#}",
+                    currency.symbol,
+                    source.name()
+                ),
+            }
+        }
+
+        median(prices)
+    }
+
+    fn is_fresh(&self, quote: &PriceQuote, now: DateTime<Utc>) -> bool {
+        (now - quote.timestamp).num_seconds() <= self.config.max_staleness_secs
+    }
+
+    /// Warns when the stored price of a currency is older than `max_staleness_secs`
+    async fn check_staleness(&self, currency: &CurrencyConfig) {
+        let now = Utc::now();
+        let latest = self
+            .db
+            .token_prices()
+            .fetch_token_price_at(&currency.address, now)
+            .await;
+
+        match latest {
+            Ok(Some((_, gap))) if gap <= self.config.max_staleness_secs => {}
+            Ok(Some((_, gap))) => {
+                tracing::warn!("{} price is stale, last stored {gap}s ago", currency.symbol)
+            }
+            Ok(None) => tracing::warn!("{} has no stored price", currency.symbol),
+            Err(e) => tracing::error!("Failed to check {} price staleness: {e:#}", currency.symbol),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct BackfillStats {
+    pub currencies: usize,
+    pub prices: usize,
+}
+
+/// Fills the gaps of the price history of the currencies in `[start_time, end_time]` from
+/// the sources that keep a history, in configured order. A quote fills a gap when no price
+/// is stored within `interval_secs` of it.
+pub async fn backfill_token_prices<TDb: IDatabase>(
+    db: &TDb,
+    config: &PriceIndexerConfig,
+    currencies: &[CurrencyConfig],
+    start_time: DateTime<Utc>,
+    end_time: DateTime<Utc>,
+) -> anyhow::Result<BackfillStats> {
+    let sources = build_price_sources(&config.sources);
+    // Prices are stored per minute, so a gap is at least a minute
+    let max_gap = TimeDelta::seconds(config.interval_secs.max(60) as i64);
+    let mut stats = BackfillStats::default();
+
+    for currency in currencies {
+        let mut stored = db
+            .token_prices()
+            .fetch_token_price_times(&currency.address, start_time, end_time)
+            .await?
+            .into_iter()
+            .collect::<BTreeSet<_>>();
+        let mut items = Vec::new();
+
+        for source in sources.iter() {
+            let id = match source.get_id(currency) {
+                Some(id) => id,
+                None => continue,
+            };
+
+            let quotes = match source.fetch_history(&id, start_time, end_time).await {
+                Ok(quotes) => quotes,
+                Err(e) => {
+                    tracing::warn!(
+                        "Failed to fetch {} price history from {}: {e:#}",
+                        currency.symbol,
+                        source.name()
+                    );
+                    continue;
+                }
+            };
+
+            for quote in quotes {
+                let created_at = quote.timestamp.duration_trunc(TimeDelta::minutes(1))?;
+                if created_at < start_time || created_at > end_time {
+                    continue;
+                }
+
+                let is_gap = stored
+                    .range((
+                        Excluded(created_at - max_gap),
+                        Excluded(created_at + max_gap),
+                    ))
+                    .next()
+                    .is_none();
+
+                if is_gap {
+                    stored.insert(created_at);
+                    items.push(DbTokenPrice {
+                        token_address: currency.address.clone(),
+                        price: quote.price,
+                        created_at,
+                    });
+                }
+            }
+        }
+
+        if !items.is_empty() {
+            stats.currencies += 1;
+            stats.prices += items.len();
+        }
+
+        for chunk in items.chunks(10_000) {
+            db.token_prices()
+                .insert_token_prices(chunk.to_vec())
+                .await?;
+        }
+    }
+
+    Ok(stats)
+}
+
+fn median(mut prices: Vec<BigDecimal>) -> Option<BigDecimal> {
+    prices.sort();
+
+    let mid = prices.len() / 2;
+    match prices.len() {
+        0 => None,
+        len if len % 2 == 0 => Some((&prices[mid - 1] + &prices[mid]) / 2),
+        _ => Some(prices[mid].clone()),
+    }
 }
@@ -0,0 +1,143 @@
+use std::{collections::HashMap, str::FromStr};
+
+use anyhow::Context;
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, TimeDelta, Utc};
+use reqwest::{Client, RequestBuilder};
+use serde::Deserialize;
+
+use crate::{
+    config::{
+        currency_config::{APT_TOKEN_ADDR, CurrencyConfig},
+        price_config::PriceSourceConfig,
+    },
+    workers::price_sources::{PriceQuote, PriceSource},
+};
+
+/// Longest range CoinGecko still returns hourly prices for
+const HISTORY_CHUNK_DAYS: i64 = 90;
+
+#[derive(Debug, Deserialize)]
+struct SimplePrice {
+    usd: serde_json::Number,
+    last_updated_at: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MarketChart {
+    /// `[timestamp in ms, price]` pairs
+    prices: Vec<(i64, serde_json::Number)>,
+}
+
+/// CoinGecko prices. Only APT has a default id, other currencies need theirs in
+/// `price_ids`.
+pub struct CoingeckoPriceSource {
+    name: String,
+    url: String,
+    api_key: Option<String>,
+    client: Client,
+}
+
+impl CoingeckoPriceSource {
+    pub fn new(config: &PriceSourceConfig) -> Self {
+        Self {
+            name: config.name.clone(),
+            url: config.url.trim_end_matches('/').to_string(),
+            api_key: config.api_key.clone(),
+            client: Client::new(),
+        }
+    }
+
+    fn get(&self, path: &str) -> RequestBuilder {
+        let req = self.client.get(format!("{}{}", self.url, path));
+
+        match self.api_key.as_ref() {
+            Some(api_key) if self.url.contains("pro-api") => {
+                req.header("x-cg-pro-api-key", api_key)
+            }
+            Some(api_key) => req.header("x-cg-demo-api-key", api_key),
+            None => req,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl PriceSource for CoingeckoPriceSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn default_id(&self, currency: &CurrencyConfig) -> Option<String> {
+        currency
+            .matches(APT_TOKEN_ADDR)
+            .then(|| "aptos".to_string())
+    }
+
+    async fn fetch_price(&self, id: &str) -> anyhow::Result<PriceQuote> {
+        let res = self
+            .get("/simple/price")
+            .query(&[
+                ("ids", id),
+                ("vs_currencies", "usd"),
+                ("include_last_updated_at", "true"),
+            ])
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<HashMap<String, SimplePrice>>()
+            .await?;
+
+        let price = res.get(id).with_context(|| format!("No price for {id}"))?;
+
+        Ok(PriceQuote {
+            price: BigDecimal::from_str(&price.usd.to_string())?,
+            timestamp: price
+                .last_updated_at
+                .and_then(|ts| DateTime::from_timestamp(ts, 0))
+                .unwrap_or_else(Utc::now),
+        })
+    }
+
+    async fn fetch_history(
+        &self,
+        id: &str,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+    ) -> anyhow::Result<Vec<PriceQuote>> {
+        let mut quotes = Vec::new();
+        let mut chunk_start = start_time;
+
+        while chunk_start < end_time {
+            let chunk_end = (chunk_start + TimeDelta::days(HISTORY_CHUNK_DAYS)).min(end_time);
+
+            let chart = self
+                .get(&format!("/coins/{id}/market_chart/range"))
+                .query(&[
+                    ("vs_currency", "usd".to_string()),
+                    ("from", chunk_start.timestamp().to_string()),
+                    ("to", chunk_end.timestamp().to_string()),
+                ])
+                .send()
+                .await?
+                .error_for_status()?
+                .json::<MarketChart>()
+                .await?;
+
+            for (ts, price) in chart.prices {
+                let timestamp = match DateTime::from_timestamp_millis(ts) {
+                    Some(timestamp) => timestamp,
+                    None => continue,
+                };
+
+                quotes.push(PriceQuote {
+                    price: BigDecimal::from_str(&price.to_string())?,
+                    timestamp,
+                });
+            }
+
+            chunk_start = chunk_end;
+        }
+
+        Ok(quotes)
+    }
+}
@@ -0,0 +1,61 @@
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Utc};
+
+use crate::config::{
+    currency_config::CurrencyConfig,
+    price_config::{PriceSourceConfig, PriceSourceKind},
+};
+
+pub mod coingecko;
+pub mod tapp;
+
+/// A usd price quote of a currency
+#[derive(Clone, Debug)]
+pub struct PriceQuote {
+    pub price: BigDecimal,
+    /// When the source last updated the price
+    pub timestamp: DateTime<Utc>,
+}
+
+#[async_trait::async_trait]
+pub trait PriceSource: Send + Sync {
+    fn name(&self) -> &str;
+
+    /// Id of the currency at this source when it isn't configured in `price_ids`
+    fn default_id(&self, currency: &CurrencyConfig) -> Option<String>;
+
+    async fn fetch_price(&self, id: &str) -> anyhow::Result<PriceQuote>;
+
+    /// Quotes over a historical range, for sources that keep a price history
+    async fn fetch_history(
+        &self,
+        _id: &str,
+        _start_time: DateTime<Utc>,
+        _end_time: DateTime<Utc>,
+    ) -> anyhow::Result<Vec<PriceQuote>> {
+        anyhow::bail!("{} has no price history", self.name())
+    }
+
+    /// Id of the currency at this source, None if the source doesn't price it
+    fn get_id(&self, currency: &CurrencyConfig) -> Option<String> {
+        currency
+            .price_ids
+            .get(self.name())
+            .cloned()
+            .or_else(|| self.default_id(currency))
+    }
+}
+
+pub fn build_price_sources(configs: &[PriceSourceConfig]) -> Vec<Box<dyn PriceSource>> {
+    configs
+        .iter()
+        .map(|config| -> Box<dyn PriceSource> {
+            match config.kind {
+                PriceSourceKind::Tapp => Box::new(tapp::TappPriceSource::new(config)),
+                PriceSourceKind::Coingecko => {
+                    Box::new(coingecko::CoingeckoPriceSource::new(config))
+                }
+            }
+        })
+        .collect()
+}
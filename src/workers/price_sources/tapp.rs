@@ -0,0 +1,87 @@
+use aptos_indexer_processor_sdk::utils::convert::deserialize_from_string;
+use bigdecimal::BigDecimal;
+use chrono::Utc;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::{currency_config::CurrencyConfig, price_config::PriceSourceConfig},
+    workers::price_sources::{PriceQuote, PriceSource},
+};
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Price {
+    base_currency: String,
+    name: String,
+    price_decimals: i32,
+    quote_currency: String,
+    #[serde(deserialize_with = "deserialize_from_string")]
+    price: BigDecimal,
+    #[serde(rename = "type")]
+    type_: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PriceResponse {
+    id: i32,
+    jsonrpc: String,
+    method: String,
+    result: Price,
+}
+
+/// Index prices of the tapp JSON-RPC api. It only serves the current price, so quotes
+/// are timestamped when they are fetched.
+pub struct TappPriceSource {
+    name: String,
+    url: String,
+    client: Client,
+}
+
+impl TappPriceSource {
+    pub fn new(config: &PriceSourceConfig) -> Self {
+        Self {
+            name: config.name.clone(),
+            url: config.url.clone(),
+            client: Client::new(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl PriceSource for TappPriceSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn default_id(&self, currency: &CurrencyConfig) -> Option<String> {
+        Some(format!("{}_usd", currency.address))
+    }
+
+    async fn fetch_price(&self, id: &str) -> anyhow::Result<PriceQuote> {
+        let now = Utc::now();
+        let body = serde_json::json!({
+            "method": "public/get_index_price",
+            "jsonrpc": "2.0",
+            "id": now.timestamp(),
+            "params": {
+                "name": id
+            }
+        });
+
+        let value = self
+            .client
+            .post(&self.url)
+            .json(&body)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<PriceResponse>()
+            .await?;
+
+        Ok(PriceQuote {
+            price: value.result.price,
+            timestamp: now,
+        })
+    }
+}
@@ -0,0 +1,128 @@
+use std::sync::Arc;
+
+use crate::{
+    config::marketplace_config::NFTMarketplaceConfig,
+    database::{IDatabase, marketplace_events::IMarketplaceEvents},
+    models::{EventModel, db::marketplace_event::DbMarketplaceEvent},
+    utils::{string_utils::capitalize, token_utils::CoinEvent},
+};
+use aptos_indexer_processor_sdk::{
+    aptos_indexer_transaction_stream::utils::time::parse_timestamp,
+    aptos_protos::transaction::v1::{Transaction, transaction::TxnData},
+    traits::{AsyncRunType, AsyncStep, NamedStep, Processable},
+    types::transaction_context::TransactionContext,
+    utils::{convert::standardize_address, errors::ProcessorError},
+};
+use tracing::warn;
+
+/// Archives the raw events of the marketplace contract, along with the coin events the
+/// remapping depends on, so that they can be re-derived later without re-streaming.
+/// Transactions are passed through untouched.
+pub struct EventArchiveStep<TDb: IDatabase>
+where
+    Self: Sized + Send + 'static,
+{
+    name: String,
+    contract_address: String,
+    db: Arc<TDb>,
+}
+
+impl<TDb: IDatabase> EventArchiveStep<TDb> {
+    pub fn new(config: &NFTMarketplaceConfig, db: Arc<TDb>) -> Self {
+        Self {
+            name: config.name.clone(),
+            contract_address: config.contract_address.clone(),
+            db,
+        }
+    }
+
+    fn archive_events(&self, transactions: &[Transaction]) -> Vec<DbMarketplaceEvent> {
+        let contract_address = standardize_address(&self.contract_address);
+        let mut archived = Vec::new();
+
+        for txn in transactions {
+            let (Some(txn_info), Some(TxnData::User(tx_inner))) =
+                (txn.info.as_ref(), txn.txn_data.as_ref())
+            else {
+                continue;
+            };
+
+            let txn_id = format!("0x{}", hex::encode(txn_info.hash.clone()));
+            let txn_version = txn.version as i64;
+            let block_height = txn.block_height as i64;
+            let txn_ts = parse_timestamp(txn.timestamp.as_ref().unwrap(), txn_version).naive_utc();
+
+            for (index, raw_event) in tx_inner.events.iter().enumerate() {
+                // An event that can't be parsed is dead-lettered by the remapping step, it
+                // must not keep the rest of the batch from being archived
+                let event = match EventModel::from_event(
+                    raw_event,
+                    txn_version,
+                    block_height,
+                    index as i64,
+                    txn_ts,
+                ) {
+                    Ok(Some(event)) => event,
+                    Ok(None) => continue,
+                    Err(e) => {
+                        warn!(
+                            "Failed to archive event {} at version {}: {:#}",
+                            raw_event.type_str, txn_version, e
+                        );
+                        continue;
+                    }
+                };
+
+                let is_coin_event = matches!(
+                    CoinEvent::from_event(&event.type_str, &event.data.to_string(), txn_version),
+                    Ok(Some(_))
+                );
+
+                if is_coin_event || event.event_type.get_address() == contract_address {
+                    archived.push(DbMarketplaceEvent::new(
+                        &self.contract_address,
+                        &txn_id,
+                        &event,
+                    ));
+                }
+            }
+        }
+
+        archived
+    }
+}
+
+#[async_trait::async_trait]
+impl<TDb: IDatabase> Processable for EventArchiveStep<TDb>
+where
+    TDb: IDatabase + Send + Sync + 'static,
+{
+    type Input = Vec<Transaction>;
+    type Output = Vec<Transaction>;
+    type RunType = AsyncRunType;
+
+    async fn process(
+        &mut self,
+        transactions: TransactionContext<Vec<Transaction>>,
+    ) -> Result<Option<TransactionContext<Vec<Transaction>>>, ProcessorError> {
+        let archived = self.archive_events(&transactions.data);
+
+        self.db
+            .marketplace_events()
+            .insert_marketplace_events(archived)
+            .await
+            .map_err(|e| ProcessorError::ProcessError {
+                message: format!("{e:#}"),
+            })?;
+
+        Ok(Some(transactions))
+    }
+}
+
+impl<TDb: IDatabase> AsyncStep for EventArchiveStep<TDb> {}
+
+impl<TDb: IDatabase> NamedStep for EventArchiveStep<TDb> {
+    fn name(&self) -> String {
+        format!("{}EventArchiveStep", capitalize(&self.name))
+    }
+}
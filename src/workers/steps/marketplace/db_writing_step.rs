@@ -2,11 +2,17 @@ use std::sync::Arc;
 
 use crate::{
     database::{
-        IDatabase, activities::IActivities, bids::IBids, collections::ICollections,
-        listings::IListings, nfts::INfts,
+        IDatabase, activities::IActivities, auctions::IAuctions, bids::IBids,
+        collections::ICollections, failed_events::IFailedEvents, listings::IListings, nfts::INfts,
     },
     models::db::{
-        activity::DbActivity, bid::DbBid, collection::DbCollection, listing::DbListing, nft::DbNft,
+        activity::DbActivity,
+        auction::DbAuction,
+        bid::{DbBid, DbBidFill},
+        collection::DbCollection,
+        failed_event::DbFailedEvent,
+        listing::{DbListing, DbListingInvalidation},
+        nft::DbNft,
     },
     utils::string_utils::capitalize,
 };
@@ -15,6 +21,7 @@ use aptos_indexer_processor_sdk::{
     types::transaction_context::TransactionContext,
     utils::errors::ProcessorError,
 };
+use sqlx::{Postgres, Transaction};
 
 pub struct DBWritingStep<TDb: IDatabase> {
     pub name: String,
@@ -38,9 +45,12 @@ where
     type Input = (
         Vec<DbActivity>,
         Vec<DbBid>,
+        Vec<DbBidFill>,
         Vec<DbListing>,
+        Vec<DbAuction>,
         Vec<DbCollection>,
         Vec<DbNft>,
+        Vec<DbFailedEvent>,
     );
     type Output = ();
     type RunType = AsyncRunType;
@@ -49,7 +59,8 @@ where
         &mut self,
         input: TransactionContext<Self::Input>,
     ) -> Result<Option<TransactionContext<()>>, ProcessorError> {
-        let (activities, bids, listings, collections, nfts) = input.data;
+        let (activities, bids, bid_fills, listings, auctions, collections, nfts, failed_events) =
+            input.data;
 
         let mut tx =
             self.db
@@ -60,41 +71,28 @@ where
                     message: format!("{e:#}"),
                 })?;
 
-        self.db
-            .activities()
-            .tx_insert_activities(&mut tx, activities)
-            .await
-            .map_err(|e| ProcessorError::ProcessError {
-                message: format!("{e:#}"),
-            })?;
-
-        self.db
-            .bids()
-            .tx_insert_bids(&mut tx, bids)
-            .await
-            .map_err(|e| ProcessorError::ProcessError {
-                message: format!("{e:#}"),
-            })?;
-
-        self.db
-            .listings()
-            .tx_insert_listings(&mut tx, listings)
-            .await
-            .map_err(|e| ProcessorError::ProcessError {
-                message: format!("{e:#}"),
-            })?;
+        tx_write_reduced(
+            self.db.as_ref(),
+            &mut tx,
+            (
+                activities,
+                bids,
+                bid_fills,
+                listings,
+                auctions,
+                collections,
+                nfts,
+            ),
+            false,
+        )
+        .await
+        .map_err(|e| ProcessorError::ProcessError {
+            message: format!("{e:#}"),
+        })?;
 
         self.db
-            .collections()
-            .tx_insert_collections(&mut tx, collections)
-            .await
-            .map_err(|e| ProcessorError::ProcessError {
-                message: format!("{e:#}"),
-            })?;
-
-        self.db
-            .nfts()
-            .tx_insert_nfts(&mut tx, nfts)
+            .failed_events()
+            .tx_insert_failed_events(&mut tx, failed_events)
             .await
             .map_err(|e| ProcessorError::ProcessError {
                 message: format!("{e:#}"),
@@ -113,6 +111,47 @@ where
     }
 }
 
+/// Writes the reduced rows of a batch inside the given transaction. Activities that already
+/// exist are updated when `upsert_activities` is set, and left untouched otherwise.
+pub async fn tx_write_reduced<TDb: IDatabase>(
+    db: &TDb,
+    tx: &mut Transaction<'_, Postgres>,
+    reduced: (
+        Vec<DbActivity>,
+        Vec<DbBid>,
+        Vec<DbBidFill>,
+        Vec<DbListing>,
+        Vec<DbAuction>,
+        Vec<DbCollection>,
+        Vec<DbNft>,
+    ),
+    upsert_activities: bool,
+) -> anyhow::Result<()> {
+    let (activities, bids, bid_fills, listings, auctions, collections, nfts) = reduced;
+    let invalidations = DbListingInvalidation::from_activities(&activities);
+    let bid_ids = bids.iter().map(|bid| bid.id).collect::<Vec<_>>();
+
+    if upsert_activities {
+        db.activities().tx_upsert_activities(tx, activities).await?;
+    } else {
+        db.activities().tx_insert_activities(tx, activities).await?;
+    }
+    db.bids().tx_insert_bids(tx, bids).await?;
+    db.bids().tx_insert_bid_fills(tx, bid_fills).await?;
+    db.bids().tx_refresh_bid_fills(tx, &bid_ids).await?;
+    db.listings().tx_insert_listings(tx, listings).await?;
+    db.listings()
+        .tx_invalidate_listings(tx, invalidations)
+        .await?;
+    db.auctions().tx_insert_auctions(tx, auctions).await?;
+    db.collections()
+        .tx_insert_collections(tx, collections)
+        .await?;
+    db.nfts().tx_insert_nfts(tx, nfts).await?;
+
+    Ok(())
+}
+
 impl<TDb: IDatabase> AsyncStep for DBWritingStep<TDb> {}
 
 impl<TDb: IDatabase> NamedStep for DBWritingStep<TDb> {
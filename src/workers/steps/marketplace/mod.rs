@@ -1,13 +1,19 @@
+use anyhow::Context;
 use jsonpath_rust::JsonPath;
 use std::{
     hash::{Hash, Hasher},
     str::FromStr,
 };
 
+pub mod archive_step;
 pub mod db_writing_step;
+pub mod rederive;
+pub mod redrive;
 pub mod reduction_step;
 pub mod remappers;
 pub mod remapping_step;
+pub mod royalties;
+pub mod usd_pricing;
 
 /// Extracts a string, ensuring proper handling of missing values
 pub fn extract_string(paths: &HashableJsonPath, from: &serde_json::Value) -> Option<String> {
@@ -36,13 +42,15 @@ impl HashableJsonPath {
 
     /// Executes the JsonPath to extract the value from the provided serde_json::Value
     pub fn extract_from(&self, value: &serde_json::Value) -> anyhow::Result<serde_json::Value> {
-        Ok(self
+        let value = self
             .json_path
             .find_slice(value)
             .first()
-            .unwrap() // Unwrap is safe here because the fields are guaranteed by the move types; TODO: Perform checks here or check config
+            .with_context(|| format!("No value found for path {}", self.raw))?
             .clone()
-            .to_data())
+            .to_data();
+
+        Ok(value)
     }
 }
 
@@ -0,0 +1,151 @@
+use anyhow::Context;
+
+use crate::{
+    config::{
+        PriceHistoryConfig, currency_config::CurrencyConfig,
+        marketplace_config::NFTMarketplaceConfig,
+    },
+    database::{IDatabase, failed_events::IFailedEvents, marketplace_events::IMarketplaceEvents},
+    models::db::failed_event::DbFailedEvent,
+    workers::steps::marketplace::{
+        db_writing_step::tx_write_reduced,
+        reduction_step::NFTAccumulator,
+        remappers::event_remapper::{CoinMovements, EventRemapper},
+        royalties::{apply_royalty, fetch_collection_royalties},
+        usd_pricing::{UsdPricer, apply_usd_price},
+    },
+};
+
+/// Number of versions re-derived per database transaction
+const REDERIVE_CHUNK_SIZE: i64 = 100_000;
+
+#[derive(Debug, Default)]
+pub struct RederiveStats {
+    pub events: usize,
+    pub activities: usize,
+    pub failed_events: usize,
+}
+
+/// The part of `[start_version, end_version]` the deployment indexed, if any
+pub fn deployment_versions(
+    config: &NFTMarketplaceConfig,
+    start_version: i64,
+    end_version: i64,
+) -> Option<(i64, i64)> {
+    let start_version = start_version.max(config.starting_version);
+    let end_version = config.ending_version.map_or(end_version, |ending_version| {
+        end_version.min(ending_version)
+    });
+
+    (start_version <= end_version).then_some((start_version, end_version))
+}
+
+/// Re-runs the archived events of a deployment in `[start_version, end_version]` through
+/// its current config and upserts the result. The range is clamped to the versions the
+/// deployment indexed.
+///
+/// Write set changes aren't archived, so resource mappings aren't re-applied. Activity
+/// fields they filled in are kept by the upsert, as is the original usd price. Fungible
+/// asset withdraws and deposits can't be tied to their owner without the write set, so their
+/// currency is only re-derived when the event maps it, and the sale breakdown only from coin
+/// deposits.
+pub async fn rederive_events<TDb: IDatabase>(
+    db: &TDb,
+    config: &NFTMarketplaceConfig,
+    currencies: &[CurrencyConfig],
+    price_history: &PriceHistoryConfig,
+    start_version: i64,
+    end_version: i64,
+) -> anyhow::Result<RederiveStats> {
+    let event_remapper = EventRemapper::new(config)?;
+    let mut pricer = UsdPricer::new(db, price_history);
+
+    let mut stats = RederiveStats::default();
+    let Some((start_version, end_version)) =
+        deployment_versions(config, start_version, end_version)
+    else {
+        return Ok(stats);
+    };
+    let mut chunk_start = start_version;
+
+    while chunk_start <= end_version {
+        let chunk_end = (chunk_start + REDERIVE_CHUNK_SIZE - 1).min(end_version);
+
+        let archived = db
+            .marketplace_events()
+            .fetch_marketplace_events(&config.contract_address, chunk_start, chunk_end)
+            .await?;
+
+        stats.events += archived.len();
+
+        let mut accumulator = NFTAccumulator::default();
+        let mut failed_events = Vec::new();
+
+        // Coin events only feed the sales of their own transaction
+        let mut current_version = None;
+        let mut coins = CoinMovements::default();
+        let mut activities = Vec::new();
+
+        for archived_event in archived.iter() {
+            if current_version != Some(archived_event.tx_version) {
+                current_version = Some(archived_event.tx_version);
+                coins = CoinMovements::default();
+            }
+
+            let event = match archived_event.to_event_model() {
+                Ok(event) => event,
+                Err(e) => {
+                    failed_events.push(DbFailedEvent::from_archived_event(
+                        &config.name,
+                        archived_event,
+                        &e,
+                    ));
+                    continue;
+                }
+            };
+
+            match event_remapper.remap_event(&event, &archived_event.tx_id, &mut coins) {
+                Ok(Some(activity)) => activities.push(activity),
+                Ok(None) => {}
+                Err(e) => {
+                    failed_events.push(DbFailedEvent::from_event_model(
+                        &config.name,
+                        &config.contract_address,
+                        &archived_event.tx_id,
+                        &event,
+                        &e,
+                    ));
+                }
+            }
+        }
+
+        let royalties = fetch_collection_royalties(db, &activities).await;
+
+        for mut activity in activities.into_iter() {
+            apply_royalty(&mut activity, &royalties);
+            apply_usd_price(&mut activity, currencies, &mut pricer).await;
+
+            if let Err(e) = accumulator.fold(&activity) {
+                failed_events.push(DbFailedEvent::from_activity(&activity, &e));
+            }
+        }
+
+        let reduced = accumulator.drain();
+
+        stats.activities += reduced.0.len();
+        stats.failed_events += failed_events.len();
+
+        let mut tx = db.get_pool().begin().await?;
+
+        tx_write_reduced(db, &mut tx, reduced, true).await?;
+        db.failed_events()
+            .tx_insert_failed_events(&mut tx, failed_events)
+            .await?;
+
+        tx.commit().await.context("Failed to commit transaction")?;
+
+        chunk_start = chunk_end + 1;
+    }
+
+    Ok(stats)
+}
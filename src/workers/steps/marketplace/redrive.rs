@@ -0,0 +1,76 @@
+use anyhow::Context;
+
+use crate::{
+    config::{
+        PriceHistoryConfig, currency_config::CurrencyConfig,
+        marketplace_config::NFTMarketplaceConfig,
+    },
+    database::{IDatabase, failed_events::IFailedEvents},
+    models::db::failed_event::{DbFailedEvent, FailedEventStep},
+    workers::steps::marketplace::{
+        db_writing_step::tx_write_reduced,
+        reduction_step::NFTAccumulator,
+        remappers::event_remapper::{CoinMovements, EventRemapper},
+        usd_pricing::{UsdPricer, apply_usd_price},
+    },
+};
+
+/// Re-runs a failed event through the current config of its marketplace and writes the
+/// result, marking the event as resolved. Events the config no longer maps are resolved
+/// without writing anything.
+///
+/// Write set changes and coin events of the original transaction aren't replayed, so
+/// values only known from them (e.g. bluemove buy prices or the currency paid) stay empty.
+pub async fn redrive_failed_event<TDb: IDatabase>(
+    db: &TDb,
+    configs: &[NFTMarketplaceConfig],
+    currencies: &[CurrencyConfig],
+    price_history: &PriceHistoryConfig,
+    failed_event: &DbFailedEvent,
+) -> anyhow::Result<()> {
+    let (_, config) = configs
+        .iter()
+        .flat_map(|config| config.get_deployments())
+        .find(|(_, config)| {
+            config.name == failed_event.marketplace
+                && config.contract_address == failed_event.contract_address
+        })
+        .with_context(|| {
+            format!(
+                "No config for marketplace {} at {}",
+                failed_event.marketplace, failed_event.contract_address
+            )
+        })?;
+
+    if failed_event.step == FailedEventStep::Resources.to_string() {
+        anyhow::bail!("Write set changes can't be re-driven, re-index the transaction instead");
+    }
+
+    let event = failed_event
+        .to_event_model()
+        .context("Failed to parse the raw event")?
+        .context("Event type is not supported")?;
+
+    let event_remapper = EventRemapper::new(&config)?;
+    let activity =
+        event_remapper.remap_event(&event, &failed_event.tx_id, &mut CoinMovements::default())?;
+
+    let mut accumulator = NFTAccumulator::default();
+    if let Some(mut activity) = activity {
+        let mut pricer = UsdPricer::new(db, price_history);
+        apply_usd_price(&mut activity, currencies, &mut pricer).await;
+
+        accumulator.fold(&activity)?;
+    }
+
+    let mut tx = db.get_pool().begin().await?;
+
+    tx_write_reduced(db, &mut tx, accumulator.drain(), false).await?;
+    db.failed_events()
+        .tx_resolve_failed_event(&mut tx, &failed_event.id)
+        .await?;
+
+    tx.commit().await.context("Failed to commit transaction")?;
+
+    Ok(())
+}
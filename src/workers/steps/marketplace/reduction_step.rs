@@ -1,14 +1,31 @@
 use crate::{
     cache::ICache,
-    database::{IDatabase, token_prices::ITokenPrices},
+    config::{
+        PriceHistoryConfig,
+        currency_config::{CurrencyConfig, resolve_currency},
+    },
+    database::IDatabase,
     models::{
         db::{
-            activity::DbActivity, bid::DbBid, collection::DbCollection, listing::DbListing,
+            activity::DbActivity,
+            auction::DbAuction,
+            bid::{DbBid, DbBidFill},
+            collection::DbCollection,
+            failed_event::DbFailedEvent,
+            listing::DbListing,
             nft::DbNft,
         },
-        marketplace::{APT_DECIMAL, MarketplaceField, MarketplaceModel, NftMarketplaceActivity},
+        marketplace::{
+            AuctionModel, BidModel, ListingModel, MarketplaceField, MarketplaceModel,
+            NftMarketplaceActivity,
+        },
+        schema::bid::BidStatus,
     },
     utils::string_utils::capitalize,
+    workers::steps::marketplace::{
+        royalties::{apply_royalty, fetch_collection_royalties},
+        usd_pricing::{UsdPricer, apply_usd_price},
+    },
 };
 use anyhow::Result;
 use aptos_indexer_processor_sdk::{
@@ -20,13 +37,13 @@ use bigdecimal::BigDecimal;
 use std::{collections::HashMap, str::FromStr, sync::Arc};
 use uuid::Uuid;
 
-const APT_TOKEN_ADDR: &str = "0x000000000000000000000000000000000000000000000000000000000000000a";
-
 #[derive(Clone, Debug, Default)]
 pub struct NFTAccumulator {
     activities: HashMap<Uuid, DbActivity>,
     bids: HashMap<Uuid, DbBid>,
+    bid_fills: HashMap<Uuid, DbBidFill>,
     listings: HashMap<Uuid, DbListing>,
+    auctions: HashMap<Uuid, DbAuction>,
     collections: HashMap<Uuid, DbCollection>,
     nfts: HashMap<Uuid, DbNft>,
 }
@@ -38,51 +55,111 @@ impl NFTAccumulator {
         self.activities.insert(activity.id, activity);
     }
 
+    /// Folds a bid event. The tx ids of every event are kept, the created one from the
+    /// latest create, while the status and the other mutable fields only follow the latest
+    /// event by `tx_index`. Accepts after the latest create take their amount off
+    /// `remaining_count` and the bid stays active until nothing remains.
     pub fn fold_bidding(&mut self, activity: &NftMarketplaceActivity) {
         let result: Result<DbBid> = activity.to_owned().try_into();
         if let Ok(bid) = result {
             self.bids
                 .entry(bid.id)
                 .and_modify(|existing: &mut DbBid| {
-                    if let Some(nonce) = bid.nonce.as_ref() {
-                        existing.nonce = Some(nonce.to_string());
-                    }
+                    let is_latest = is_latest(bid.tx_index, existing.tx_index);
+                    // Accepts before the latest create filled an earlier bid under this id
+                    let is_filling_latest_create = existing
+                        .created_tx_index
+                        .is_none_or(|created| bid.tx_index > Some(created));
 
-                    if let Some(tx_id) = bid.created_tx_id.as_ref() {
-                        existing.created_tx_id = Some(tx_id.to_string());
-                        existing.status = Some("active".to_string());
+                    if bid.created_tx_index >= existing.created_tx_index {
+                        if let Some(tx_id) = bid.created_tx_id.as_ref() {
+                            existing.created_tx_id = Some(tx_id.to_string());
+                            existing.created_tx_index = bid.created_tx_index;
+                        }
                     }
 
                     if let Some(tx_id) = bid.accepted_tx_id.as_ref() {
                         existing.accepted_tx_id = Some(tx_id.to_string());
-                        existing.status = Some("matched".to_string());
                     }
 
                     if let Some(tx_id) = bid.cancelled_tx_id.as_ref() {
                         existing.cancelled_tx_id = Some(tx_id.to_string());
-                        existing.status = Some("cancelled".to_string());
+                    }
+
+                    if !is_latest {
+                        return;
+                    }
+
+                    existing.tx_index = bid.tx_index;
+
+                    if bid.created_tx_id.is_some() {
+                        existing.status = Some(BidStatus::Active.to_string());
+                    }
+
+                    if bid.accepted_tx_id.is_some() && is_filling_latest_create {
+                        let remaining = existing
+                            .remaining_count
+                            .map(|count| (count - activity.token_amount.unwrap_or(1)).max(0));
+
+                        existing.remaining_count = remaining;
+                        existing.status = match remaining {
+                            Some(remaining) if remaining > 0 => Some(BidStatus::Active.to_string()),
+                            _ => Some(BidStatus::Matched.to_string()),
+                        };
+                    }
+
+                    if bid.cancelled_tx_id.is_some() {
+                        existing.status = Some(BidStatus::Cancelled.to_string());
+                    }
+
+                    if let Some(nonce) = bid.nonce.as_ref() {
+                        existing.nonce = Some(nonce.to_string());
                     }
 
                     if let Some(receiver) = bid.receiver.as_ref() {
                         existing.receiver = Some(receiver.to_string());
                     }
+
+                    if let Some(expired_at) = bid.expired_at {
+                        existing.expired_at = Some(expired_at);
+                    }
+
+                    if let Some(remaining_count) = bid.remaining_count {
+                        existing.remaining_count = Some(remaining_count);
+                    }
+
+                    if let Some(amount) = bid.amount {
+                        existing.amount = Some(amount);
+                    }
+
+                    if let Some(currency) = bid.currency.as_ref() {
+                        existing.currency = Some(currency.to_string());
+                    }
+
+                    if let Some(attr_type) = bid.attr_type.as_ref() {
+                        existing.attr_type = Some(attr_type.to_string());
+                        existing.attr_value = bid.attr_value.clone();
+                    }
                 })
                 .or_insert(bid);
         }
     }
 
+    pub fn fold_bid_fill(&mut self, activity: &NftMarketplaceActivity) {
+        let result: Result<DbBidFill> = activity.to_owned().try_into();
+        if let Ok(fill) = result {
+            self.bid_fills.insert(fill.activity_id, fill);
+        }
+    }
+
+    /// Folds a listing event, the listing follows the latest event by `tx_index`
     pub fn fold_listing(&mut self, activity: &NftMarketplaceActivity) {
         let result: Result<DbListing> = activity.to_owned().try_into();
         if let Ok(listing) = result {
             self.listings
                 .entry(listing.id)
                 .and_modify(|existing: &mut DbListing| {
-                    let is_latest = listing
-                        .block_time
-                        .zip(existing.block_time)
-                        .map_or(false, |(current, existing)| current.gt(&existing));
-
-                    if is_latest {
+                    if is_latest(listing.tx_index, existing.tx_index) {
                         existing.block_time = listing.block_time.clone();
                         existing.listed = listing.listed.clone();
                         existing.block_height = listing.block_height.clone();
@@ -91,12 +168,70 @@ impl NFTAccumulator {
                         existing.price = listing.price.clone();
                         existing.seller = listing.seller.clone();
                         existing.tx_index = listing.tx_index.clone();
+                        existing.expired_at = listing.expired_at.clone();
+                        existing.currency = listing.currency.clone();
                     }
                 })
                 .or_insert(listing);
         }
     }
 
+    /// Folds an auction event. The highest bid only goes up, while the status and the end
+    /// time follow the latest event by `tx_index`. A newer create of the same key starts the
+    /// auction over, and events before the latest create are ignored.
+    pub fn fold_auction(&mut self, activity: &NftMarketplaceActivity) {
+        let result: Result<DbAuction> = activity.to_owned().try_into();
+        if let Ok(auction) = result {
+            self.auctions
+                .entry(auction.id)
+                .and_modify(|existing: &mut DbAuction| {
+                    if auction.created_tx_index > existing.created_tx_index.or(existing.tx_index) {
+                        *existing = DbAuction {
+                            collection_id: auction.collection_id.or(existing.collection_id),
+                            nft_id: auction.nft_id.or(existing.nft_id),
+                            nonce: auction.nonce.clone().or(existing.nonce.take()),
+                            ..auction.clone()
+                        };
+                        return;
+                    }
+
+                    if auction.created_tx_index.or(auction.tx_index) < existing.created_tx_index {
+                        return;
+                    }
+
+                    if let Some(tx_id) = auction.created_tx_id.as_ref() {
+                        existing.created_tx_id = Some(tx_id.to_string());
+                        existing.created_tx_index = auction.created_tx_index;
+                        existing.seller = auction.seller.clone().or(existing.seller.take());
+                        existing.reserve_price = auction.reserve_price.or(existing.reserve_price);
+                        existing.start_time = auction.start_time.or(existing.start_time);
+                        existing.currency = auction.currency.clone().or(existing.currency.take());
+                    }
+
+                    if let Some(tx_id) = auction.settled_tx_id.as_ref() {
+                        existing.settled_tx_id = Some(tx_id.to_string());
+                    }
+
+                    if let Some(tx_id) = auction.cancelled_tx_id.as_ref() {
+                        existing.cancelled_tx_id = Some(tx_id.to_string());
+                    }
+
+                    if auction.highest_bid > existing.highest_bid {
+                        existing.highest_bid = auction.highest_bid;
+                        existing.highest_bidder = auction.highest_bidder.clone();
+                    }
+
+                    if is_latest(auction.tx_index, existing.tx_index) {
+                        existing.tx_index = auction.tx_index;
+                        existing.status = auction.status.clone();
+                        existing.block_time = auction.block_time;
+                        existing.end_time = auction.end_time.or(existing.end_time);
+                    }
+                })
+                .or_insert(auction);
+        }
+    }
+
     pub fn fold_collection(&mut self, activity: &NftMarketplaceActivity) {
         let result: Result<DbCollection> = activity.to_owned().try_into();
         if let Ok(collection) = result {
@@ -111,25 +246,130 @@ impl NFTAccumulator {
         }
     }
 
+    /// Folds the activity into every table. Bid, listing and auction events that can't be
+    /// keyed are rejected before anything is folded.
+    pub fn fold(&mut self, activity: &NftMarketplaceActivity) -> Result<()> {
+        if activity.get_bid_type().is_some() && activity.get_bid_id().is_none() {
+            return Err(unkeyed_activity_error(
+                "Bid event has no bidder or token/collection address",
+                activity,
+            ));
+        }
+
+        if activity.get_listing_status().is_some() && activity.get_listing_id().is_none() {
+            return Err(unkeyed_activity_error(
+                "Listing event has no token address",
+                activity,
+            ));
+        }
+
+        if activity.get_auction_status().is_some() && activity.get_auction_id().is_none() {
+            return Err(unkeyed_activity_error(
+                "Auction event has no listing id or token address",
+                activity,
+            ));
+        }
+
+        self.fold_activity(activity);
+        self.fold_bidding(activity);
+        self.fold_bid_fill(activity);
+        self.fold_listing(activity);
+        self.fold_auction(activity);
+        self.fold_collection(activity);
+        self.fold_nfts(activity);
+
+        Ok(())
+    }
+
     pub fn drain(
         &mut self,
     ) -> (
         Vec<DbActivity>,
         Vec<DbBid>,
+        Vec<DbBidFill>,
         Vec<DbListing>,
+        Vec<DbAuction>,
         Vec<DbCollection>,
         Vec<DbNft>,
     ) {
         (
             self.activities.drain().map(|(_, v)| v).collect(),
             self.bids.drain().map(|(_, v)| v).collect(),
+            self.bid_fills.drain().map(|(_, v)| v).collect(),
             self.listings.drain().map(|(_, v)| v).collect(),
+            self.auctions.drain().map(|(_, v)| v).collect(),
             self.collections.drain().map(|(_, v)| v).collect(),
             self.nfts.drain().map(|(_, v)| v).collect(),
         )
     }
 }
 
+/// Whether an event at `incoming` should be applied over the state at `existing`. Replaying
+/// the same event applies it again, so that re-derived rows pick up config changes.
+fn is_latest(incoming: Option<i64>, existing: Option<i64>) -> bool {
+    match (incoming, existing) {
+        (Some(incoming), Some(existing)) => incoming >= existing,
+        (_, None) => true,
+        (None, Some(_)) => false,
+    }
+}
+
+fn unkeyed_activity_error(message: &str, activity: &NftMarketplaceActivity) -> anyhow::Error {
+    if activity.missing_paths.is_empty() {
+        anyhow::anyhow!("{}", message)
+    } else {
+        anyhow::anyhow!(
+            "{}, missing paths: {}",
+            message,
+            activity.missing_paths.join(", ")
+        )
+    }
+}
+
+/// Fills the activity fields that are still missing from the remapped resources of its token
+pub fn apply_resource_updates(
+    activity: &mut NftMarketplaceActivity,
+    resource_updates: &HashMap<String, HashMap<String, String>>,
+) {
+    let resource = match activity
+        .token_addr
+        .as_ref()
+        .and_then(|token_addr| resource_updates.get(token_addr))
+    {
+        Some(resource) => resource,
+        None => return,
+    };
+
+    for (column, value) in resource {
+        if let Some((table, column)) = column.split_once('.') {
+            let is_missing = activity
+                .table_fields
+                .get(table)
+                .is_none_or(|fields| !fields.contains_key(column));
+
+            if is_missing {
+                activity.set_table_field(table, column, value.clone());
+            }
+        } else if let Ok(field) = MarketplaceField::from_str(column) {
+            if activity.get_field(field.clone()).is_none() {
+                activity.set_field(field, value.clone());
+            }
+        }
+    }
+}
+
+/// Resolves the payment currency of an activity to its configured address. Returns None,
+/// keeping the detected currency, for currencies that aren't configured.
+pub fn apply_currency(
+    activity: &mut NftMarketplaceActivity,
+    currencies: &[CurrencyConfig],
+) -> Option<CurrencyConfig> {
+    let currency = resolve_currency(currencies, activity.currency.as_deref())?;
+    activity.currency = Some(currency.address.clone());
+
+    Some(currency)
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct NFTReductionStep<TDb: IDatabase, TCache: ICache>
 where
@@ -138,18 +378,39 @@ where
     name: String,
     db: Arc<TDb>,
     cache: Arc<TCache>,
+    currencies: Vec<CurrencyConfig>,
+    price_history: PriceHistoryConfig,
     accumulator: NFTAccumulator,
 }
 
 impl<TDb: IDatabase, TCache: ICache> NFTReductionStep<TDb, TCache> {
-    pub fn new(name: &str, db: Arc<TDb>, cache: Arc<TCache>) -> Self {
+    pub fn new(
+        name: &str,
+        db: Arc<TDb>,
+        cache: Arc<TCache>,
+        currencies: Vec<CurrencyConfig>,
+        price_history: PriceHistoryConfig,
+    ) -> Self {
         Self {
             name: name.to_string(),
             db,
             cache,
+            currencies,
+            price_history,
             accumulator: NFTAccumulator::default(),
         }
     }
+
+    async fn get_latest_prices(&self) -> HashMap<String, BigDecimal> {
+        let mut latest = HashMap::new();
+        for currency in self.currencies.iter() {
+            if let Some(usd) = self.cache.get_token_price(&currency.address).await {
+                latest.insert(currency.address.clone(), usd);
+            }
+        }
+
+        latest
+    }
 }
 
 #[async_trait::async_trait]
@@ -161,13 +422,17 @@ where
     type Input = (
         Vec<NftMarketplaceActivity>,
         HashMap<String, HashMap<String, String>>,
+        Vec<DbFailedEvent>,
     );
     type Output = (
         Vec<DbActivity>,
         Vec<DbBid>,
+        Vec<DbBidFill>,
         Vec<DbListing>,
+        Vec<DbAuction>,
         Vec<DbCollection>,
         Vec<DbNft>,
+        Vec<DbFailedEvent>,
     );
     type RunType = AsyncRunType;
 
@@ -175,46 +440,46 @@ where
         &mut self,
         input: TransactionContext<Self::Input>,
     ) -> Result<Option<TransactionContext<Self::Output>>, ProcessorError> {
-        let (activities, resource_updates) = input.data;
-
-        for activity in activities.iter() {
-            let mut activity = activity.clone();
-
-            let usd = match self.cache.get_token_price(APT_TOKEN_ADDR).await {
-                Some(usd) => usd,
-                None => self
-                    .db
-                    .token_prices()
-                    .fetch_token_price(APT_TOKEN_ADDR)
-                    .await
-                    .unwrap_or_default(),
-            };
-
-            if let Some(token_addr) = activity.token_addr.as_ref() {
-                if let Some(resource) = resource_updates.get(token_addr) {
-                    for (column, value) in resource {
-                        let field = MarketplaceField::from_str(column).unwrap();
-
-                        if activity.get_field(field.clone()).is_none() {
-                            activity.set_field(field.clone(), value.clone());
-                        }
-                    }
-                }
-            }
+        let (mut activities, resource_updates, mut failed_events) = input.data;
+        let mut pricer = UsdPricer::new(self.db.as_ref(), &self.price_history)
+            .with_latest_prices(self.get_latest_prices().await);
 
-            activity.usd_price = Some(BigDecimal::from(activity.price) / APT_DECIMAL as i64 * &usd);
+        for activity in activities.iter_mut() {
+            apply_resource_updates(activity, &resource_updates);
+        }
+
+        let royalties = fetch_collection_royalties(self.db.as_ref(), &activities).await;
+
+        for mut activity in activities.into_iter() {
+            apply_royalty(&mut activity, &royalties);
+            apply_usd_price(&mut activity, &self.currencies, &mut pricer).await;
+
+            if let Err(e) = self.accumulator.fold(&activity) {
+                tracing::warn!(
+                    "Failed to reduce {} at version {}: {:#}",
+                    activity.raw_event_type,
+                    activity.txn_version,
+                    e
+                );
 
-            self.accumulator.fold_activity(&activity);
-            self.accumulator.fold_bidding(&activity);
-            self.accumulator.fold_listing(&activity);
-            self.accumulator.fold_collection(&activity);
-            self.accumulator.fold_nfts(&activity);
+                failed_events.push(DbFailedEvent::from_activity(&activity, &e));
+            }
         }
 
-        let reduced_data = self.accumulator.drain();
+        let (activities, bids, bid_fills, listings, auctions, collections, nfts) =
+            self.accumulator.drain();
 
         Ok(Some(TransactionContext {
-            data: reduced_data,
+            data: (
+                activities,
+                bids,
+                bid_fills,
+                listings,
+                auctions,
+                collections,
+                nfts,
+                failed_events,
+            ),
             metadata: input.metadata,
         }))
     }
@@ -227,3 +492,180 @@ impl<TDb: IDatabase, TCache: ICache> NamedStep for NFTReductionStep<TDb, TCache>
         format!("{}NFTReductionStep", capitalize(&self.name))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::marketplace_config::MarketplaceEventType, models::schema::auction::AuctionStatus,
+    };
+
+    const VERSION: i64 = 1_000;
+
+    fn activity(
+        standard_event_type: MarketplaceEventType,
+        txn_version: i64,
+        price: i64,
+    ) -> NftMarketplaceActivity {
+        NftMarketplaceActivity {
+            txn_id: format!("0x{txn_version}"),
+            txn_version,
+            standard_event_type,
+            price,
+            token_addr: Some("0xa".to_string()),
+            buyer: Some("0xb".to_string()),
+            seller: Some("0xc".to_string()),
+            contract_address: Some("0xd".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn is_latest_follows_tx_index() {
+        assert!(is_latest(Some(2), Some(1)));
+        assert!(is_latest(Some(1), Some(1)));
+        assert!(!is_latest(Some(1), Some(2)));
+        assert!(is_latest(Some(1), None));
+        assert!(!is_latest(None, Some(1)));
+    }
+
+    #[test]
+    fn fold_bidding_keeps_the_newer_event() {
+        let newer = activity(MarketplaceEventType::UnlistBid, VERSION + 1, 150);
+        let older = activity(MarketplaceEventType::SoloBid, VERSION, 100);
+
+        let mut accumulator = NFTAccumulator::default();
+        accumulator.fold_bidding(&newer);
+        accumulator.fold_bidding(&older);
+
+        let (_, bids, ..) = accumulator.drain();
+        assert_eq!(bids.len(), 1);
+        assert_eq!(bids[0].status, Some(BidStatus::Cancelled.to_string()));
+        assert_eq!(bids[0].price, Some(150));
+        assert_eq!(bids[0].tx_index, Some(newer.get_tx_index()));
+        assert_eq!(bids[0].created_tx_id, Some(older.txn_id));
+        assert_eq!(bids[0].cancelled_tx_id, Some(newer.txn_id));
+    }
+
+    #[test]
+    fn fold_bidding_applies_a_replayed_event() {
+        let event = NftMarketplaceActivity {
+            offer_id: Some("1".to_string()),
+            ..activity(MarketplaceEventType::SoloBid, VERSION, 100)
+        };
+        let replayed = NftMarketplaceActivity {
+            offer_id: Some("2".to_string()),
+            ..event.clone()
+        };
+
+        let mut accumulator = NFTAccumulator::default();
+        accumulator.fold_bidding(&event);
+        accumulator.fold_bidding(&replayed);
+
+        let (_, bids, ..) = accumulator.drain();
+        assert_eq!(bids[0].status, Some(BidStatus::Active.to_string()));
+        assert_eq!(bids[0].nonce, Some("2".to_string()));
+        assert_eq!(bids[0].tx_index, Some(event.get_tx_index()));
+    }
+
+    #[test]
+    fn fold_bidding_only_counts_accepts_after_the_latest_create() {
+        let collection_bid = |standard_event_type, txn_version| NftMarketplaceActivity {
+            collection_addr: Some("0xe".to_string()),
+            token_amount: Some(match standard_event_type {
+                MarketplaceEventType::CollectionBid => 10,
+                _ => 1,
+            }),
+            ..activity(standard_event_type, txn_version, 100)
+        };
+
+        let mut accumulator = NFTAccumulator::default();
+        accumulator.fold_bidding(&collection_bid(
+            MarketplaceEventType::CollectionBid,
+            VERSION,
+        ));
+        for version in VERSION + 1..=VERSION + 3 {
+            accumulator.fold_bidding(&collection_bid(
+                MarketplaceEventType::AcceptCollectionBid,
+                version,
+            ));
+        }
+
+        let (_, bids, ..) = accumulator.drain();
+        assert_eq!(bids[0].remaining_count, Some(7));
+
+        let recreated = collection_bid(MarketplaceEventType::CollectionBid, VERSION + 5);
+        let mut accumulator = NFTAccumulator::default();
+        accumulator.fold_bidding(&recreated);
+        accumulator.fold_bidding(&collection_bid(
+            MarketplaceEventType::AcceptCollectionBid,
+            VERSION + 4,
+        ));
+        accumulator.fold_bidding(&collection_bid(
+            MarketplaceEventType::AcceptCollectionBid,
+            VERSION + 6,
+        ));
+
+        let (_, bids, ..) = accumulator.drain();
+        assert_eq!(bids[0].remaining_count, Some(9));
+        assert_eq!(bids[0].status, Some(BidStatus::Active.to_string()));
+        assert_eq!(bids[0].created_tx_id, Some(recreated.txn_id.clone()));
+        assert_eq!(bids[0].created_tx_index, Some(recreated.get_tx_index()));
+    }
+
+    #[test]
+    fn fold_auction_starts_over_on_a_newer_create() {
+        let first = activity(MarketplaceEventType::AuctionCreate, VERSION, 100);
+        let bid = activity(MarketplaceEventType::AuctionBid, VERSION + 1, 150);
+        let settle = activity(MarketplaceEventType::AuctionSettle, VERSION + 2, 150);
+        let second = activity(MarketplaceEventType::AuctionCreate, VERSION + 3, 200);
+
+        let mut accumulator = NFTAccumulator::default();
+        for event in [&first, &bid, &second, &settle] {
+            accumulator.fold_auction(event);
+        }
+
+        let (.., auctions, _, _) = accumulator.drain();
+        assert_eq!(auctions.len(), 1);
+        assert_eq!(auctions[0].status, Some(AuctionStatus::Active.to_string()));
+        assert_eq!(auctions[0].reserve_price, Some(200));
+        assert_eq!(auctions[0].highest_bid, None);
+        assert_eq!(auctions[0].highest_bidder, None);
+        assert_eq!(auctions[0].created_tx_id, Some(second.txn_id.clone()));
+        assert_eq!(auctions[0].settled_tx_id, None);
+        assert_eq!(auctions[0].tx_index, Some(second.get_tx_index()));
+    }
+
+    #[test]
+    fn fold_listing_keeps_the_newer_event() {
+        let newer = activity(MarketplaceEventType::Relist, VERSION + 1, 150);
+        let older = activity(MarketplaceEventType::List, VERSION, 100);
+
+        let mut accumulator = NFTAccumulator::default();
+        accumulator.fold_listing(&newer);
+        accumulator.fold_listing(&older);
+
+        let (.., listings, _, _, _) = accumulator.drain();
+        assert_eq!(listings.len(), 1);
+        assert_eq!(listings[0].listed, Some(true));
+        assert_eq!(listings[0].price, Some(150));
+        assert_eq!(listings[0].tx_index, Some(newer.get_tx_index()));
+    }
+
+    #[test]
+    fn fold_listing_applies_a_replayed_event() {
+        let event = activity(MarketplaceEventType::List, VERSION, 100);
+        let replayed = NftMarketplaceActivity {
+            price: 120,
+            ..event.clone()
+        };
+
+        let mut accumulator = NFTAccumulator::default();
+        accumulator.fold_listing(&event);
+        accumulator.fold_listing(&replayed);
+
+        let (.., listings, _, _, _) = accumulator.drain();
+        assert_eq!(listings[0].price, Some(120));
+        assert_eq!(listings[0].tx_index, Some(event.get_tx_index()));
+    }
+}
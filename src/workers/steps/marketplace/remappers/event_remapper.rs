@@ -5,26 +5,154 @@ use crate::{
     },
     models::{
         EventModel,
+        db::failed_event::DbFailedEvent,
         marketplace::{BidModel, MarketplaceField, MarketplaceModel, NftMarketplaceActivity},
     },
-    workers::steps::marketplace::{HashableJsonPath, remappers::TableType},
+    workers::steps::marketplace::{
+        HashableJsonPath,
+        remappers::{TableType, field_mapping::FieldMapping, predicate::EventModelRules},
+    },
 };
 use ahash::AHashMap;
 use anyhow::Result;
 use aptos_indexer_processor_sdk::{
     aptos_indexer_transaction_stream::utils::time::parse_timestamp,
-    aptos_protos::transaction::v1::{Transaction, transaction::TxnData},
+    aptos_protos::transaction::v1::{
+        Event, Transaction, transaction::TxnData, write_set_change::Change,
+    },
     utils::{convert::standardize_address, extract::hash_str},
 };
 use bigdecimal::{BigDecimal, ToPrimitive};
 use std::{collections::HashMap, str::FromStr, sync::Arc};
 use tracing::{debug, warn};
 
+/// Coin and fungible asset movements of the transaction being remapped, for the marketplace
+/// events that don't carry their price, seller or currency
+#[derive(Debug, Default)]
+pub struct CoinMovements {
+    /// Amount withdrawn per account by coin module and fungible asset events
+    pub buy_prices: AHashMap<String, BigDecimal>,
+    /// Amount withdrawn per account by legacy handle events, which are emitted alongside the
+    /// coin module events during the event migration
+    pub legacy_withdraws: AHashMap<String, BigDecimal>,
+    /// Account of the last deposit per transaction version
+    pub buy_seller: AHashMap<i64, String>,
+    /// Currency withdrawn per account, a coin type or a fungible asset metadata address
+    pub currencies: AHashMap<String, String>,
+    /// Owner and metadata address of the fungible stores written by the transaction
+    pub fungible_stores: AHashMap<String, (Option<String>, Option<String>)>,
+    /// Amount deposited per account since the last sale
+    pub deposits: AHashMap<String, BigDecimal>,
+    /// Amount deposited per account since the last sale by legacy handle events
+    pub legacy_deposits: AHashMap<String, BigDecimal>,
+}
+
+impl CoinMovements {
+    /// Collects the fungible stores written by the transaction. Archived events are
+    /// replayed without them, so only coin withdraws tell their currency there.
+    pub fn from_transaction(txn: &Transaction) -> Self {
+        let mut movements = Self::default();
+
+        let changes = txn.info.as_ref().map(|info| info.changes.as_slice());
+        for wsc in changes.unwrap_or_default() {
+            let write_resource = match wsc.change.as_ref() {
+                Some(Change::WriteResource(wr)) => wr,
+                _ => continue,
+            };
+
+            let is_store = match write_resource.type_str.as_str() {
+                "0x1::object::ObjectCore" => false,
+                "0x1::fungible_asset::FungibleStore" => true,
+                _ => continue,
+            };
+
+            let data: serde_json::Value =
+                serde_json::from_str(&write_resource.data).unwrap_or_default();
+            let store = movements
+                .fungible_stores
+                .entry(standardize_address(&write_resource.address))
+                .or_default();
+
+            if is_store {
+                store.1 = data
+                    .pointer("/metadata/inner")
+                    .and_then(|e| e.as_str())
+                    .map(standardize_address);
+            } else {
+                store.0 = data
+                    .get("owner")
+                    .and_then(|e| e.as_str())
+                    .map(standardize_address);
+            }
+        }
+
+        movements
+    }
+
+    fn record_withdraw(&mut self, account: String, amount: BigDecimal, currency: Option<String>) {
+        if let Some(currency) = currency {
+            self.currencies.insert(account.clone(), currency);
+        }
+
+        add_amount(&mut self.buy_prices, account, amount);
+    }
+
+    /// Amount withdrawn by the account. Legacy withdraw events only count when the account
+    /// has no module withdraw event, which would duplicate them.
+    fn take_withdrawn(&mut self, account: &str) -> Option<BigDecimal> {
+        let legacy = self.legacy_withdraws.remove(account);
+        self.buy_prices.remove(account).or(legacy)
+    }
+
+    fn record_deposit(&mut self, account: String, amount: BigDecimal) {
+        add_amount(&mut self.deposits, account, amount);
+    }
+
+    /// Splits the price of a sale into the deposits made for it: the seller proceeds, the
+    /// marketplace fee and the other deposits, one of which may be the creator royalty.
+    /// The deposits are consumed, so each sale of a sweep only sees its own. Legacy deposit
+    /// events only count for the accounts without a module deposit event.
+    fn apply_sale_deposits(&mut self, activity: &mut NftMarketplaceActivity, fee_account: &str) {
+        let mut deposits = std::mem::take(&mut self.deposits);
+        for (account, amount) in std::mem::take(&mut self.legacy_deposits) {
+            deposits.entry(account).or_insert(amount);
+        }
+
+        if let Some(buyer) = activity.buyer.as_ref() {
+            deposits.remove(&standardize_address(buyer));
+        }
+
+        let seller_proceeds = activity
+            .seller
+            .as_ref()
+            .and_then(|seller| deposits.remove(&standardize_address(seller)))
+            .and_then(|amount| amount.to_i64());
+
+        // Without the seller deposit the deposits can't be told apart
+        if seller_proceeds.is_none() {
+            return;
+        }
+
+        activity.seller_proceeds = seller_proceeds;
+        activity.marketplace_fee = Some(
+            deposits
+                .remove(fee_account)
+                .and_then(|amount| amount.to_i64())
+                .unwrap_or_default(),
+        );
+        activity.royalty_deposits = deposits
+            .into_values()
+            .filter_map(|amount| amount.to_i64())
+            .collect();
+    }
+}
+
 pub struct EventRemapper {
     field_remappings: EventFieldRemappings,
     marketplace_name: String,
     marketplace_contract_id: String,
-    marketplace_event_type_mapping: HashMap<String, MarketplaceEventType>,
+    fee_account: String,
+    marketplace_event_type_mapping: HashMap<String, EventModelRules>,
 }
 
 impl EventRemapper {
@@ -42,7 +170,7 @@ impl EventRemapper {
                     .iter()
                     .map(|db_mapping| {
                         // We only map json path here for now, might have to support move_type as well.
-                        Ok(db_mapping.clone())
+                        FieldMapping::new(db_mapping)
                     })
                     .collect::<anyhow::Result<Vec<_>>>()?;
 
@@ -52,216 +180,319 @@ impl EventRemapper {
             field_remappings.insert(event_type, db_mappings_for_event);
         }
 
-        let mut marketplace_event_type_mapping: HashMap<String, MarketplaceEventType> =
-            HashMap::new();
-        for (event_type, marketplace_event_type) in &config.event_model_mapping {
+        let mut marketplace_event_type_mapping: HashMap<String, EventModelRules> = HashMap::new();
+        for (event_type, event_model_mapping) in &config.event_model_mapping {
             let event_type = format!("{}::{}", config.contract_address, event_type);
-            marketplace_event_type_mapping.insert(event_type, marketplace_event_type.clone());
+            marketplace_event_type_mapping
+                .insert(event_type, EventModelRules::new(event_model_mapping)?);
         }
 
         Ok(Arc::new(Self {
             field_remappings,
             marketplace_name: config.name.clone(),
             marketplace_contract_id: config.contract_address.clone(),
+            fee_account: config.get_fee_account(),
             marketplace_event_type_mapping,
         }))
     }
 
-    pub fn remap_events(&self, txn: Transaction) -> Result<Vec<NftMarketplaceActivity>> {
+    /// Remaps the events of a transaction into activities. Events that fail to parse or
+    /// remap are returned as failed events instead of failing the whole transaction.
+    pub fn remap_events(
+        &self,
+        txn: Transaction,
+    ) -> Result<(Vec<NftMarketplaceActivity>, Vec<DbFailedEvent>)> {
         let mut activities: Vec<NftMarketplaceActivity> = Vec::new();
+        let mut failed_events: Vec<DbFailedEvent> = Vec::new();
 
         if let Some(txn_info) = txn.info.as_ref() {
-            let mut buy_prices: AHashMap<String, BigDecimal> = AHashMap::new();
-            let mut buy_seller: AHashMap<i64, String> = AHashMap::new();
+            let mut coins = CoinMovements::from_transaction(&txn);
 
             let txn_id = format!("0x{}", hex::encode(txn_info.hash.clone()));
-            let events = self.get_events(Arc::new(txn))?;
-            for event in events {
-                let coin_result = CoinEvent::from_event(
-                    &event.type_str,
-                    &event.data.to_string(),
-                    event.transaction_version,
-                )?;
-
-                if let Some(coin) = coin_result {
-                    match coin {
-                        CoinEvent::WithdrawEvent(inner) => {
-                            buy_prices
-                                .entry(event.account_address.clone())
-                                .and_modify(|existing| {
-                                    *existing += &inner.amount;
-                                })
-                                .or_insert(inner.amount);
-                        }
-                        CoinEvent::DepositEvent(_) => {
-                            buy_seller
-                                .insert(event.transaction_version, event.account_address.clone());
-                        }
+            let txn_version = txn.version as i64;
+            let block_height = txn.block_height as i64;
+            let txn_ts = parse_timestamp(txn.timestamp.as_ref().unwrap(), txn_version).naive_utc();
+
+            for (index, raw_event) in self.get_events(&txn).iter().enumerate() {
+                let result = EventModel::from_event(
+                    raw_event,
+                    txn_version,
+                    block_height,
+                    index as i64,
+                    txn_ts,
+                )
+                .and_then(|event| match event {
+                    Some(event) => self.remap_event(&event, &txn_id, &mut coins),
+                    None => Ok(None),
+                });
+
+                match result {
+                    Ok(Some(activity)) => activities.push(activity),
+                    Ok(None) => {}
+                    Err(e) => {
+                        warn!(
+                            "Failed to remap event {} at version {}: {:#}",
+                            raw_event.type_str, txn_version, e
+                        );
+
+                        failed_events.push(DbFailedEvent::from_raw_event(
+                            &self.marketplace_name,
+                            &self.marketplace_contract_id,
+                            &txn_id,
+                            txn_version,
+                            index as i64,
+                            raw_event,
+                            &e,
+                            txn_ts,
+                            block_height,
+                        ));
                     }
                 }
+            }
+        }
 
-                let event_type_str = event.event_type.to_string();
-
-                // Handle nft activity event
-                if let Some(remappings) = self.field_remappings.get(&event.event_type) {
-                    let event_type = self.marketplace_event_type_mapping.get(&event_type_str);
-
-                    if let Some(event_type) = event_type.cloned() {
-                        let mut activity = NftMarketplaceActivity {
-                            marketplace: Some(self.marketplace_name.clone()),
-                            txn_id: txn_id.to_string(),
-                            txn_version: event.transaction_version,
-                            index: event.event_index,
-                            contract_address: Some(self.marketplace_contract_id.clone()),
-                            block_timestamp: event.block_timestamp,
-                            block_height: event.transaction_block_height,
-                            raw_event_type: event.event_type.to_string(),
-                            json_data: serde_json::to_value(&event).unwrap(),
-                            standard_event_type: event_type.clone(),
-                            ..Default::default()
-                        };
-
-                        remappings.iter().try_for_each(|(json_path, db_mappings)| {
-                            db_mappings.iter().try_for_each(|db_mapping| {
-                                // Extract value, continue on error instead of failing
-                                let extracted_value = match json_path.extract_from(&event.data) {
-                                    Ok(value) => value,
-                                    Err(e) => {
-                                        debug!(
-                                            "Failed to extract value for path {}: {}",
-                                            json_path.raw, e
-                                        );
-                                        return Ok::<(), anyhow::Error>(());
-                                    }
-                                };
-
-                                let value = extracted_value
-                                    .as_str()
-                                    .map(|s| s.to_string())
-                                    .or_else(|| extracted_value.as_u64().map(|n| n.to_string()))
-                                    .unwrap_or_default();
-
-                                if value.is_empty() {
-                                    debug!(
-                                        "Skipping empty value for path {} for column {}",
-                                        json_path.raw, db_mapping.column
-                                    );
-                                    return Ok(());
-                                }
-
-                                match TableType::from_str(db_mapping.table.as_str()) {
-                                    Some(TableType::Activities) => {
-                                        match MarketplaceField::from_str(db_mapping.column.as_str())
-                                        {
-                                            Ok(field) => {
-                                                activity.set_field(field, value);
-                                            }
-                                            Err(e) => {
-                                                warn!(
-                                                    "Skipping invalid field {}: {}",
-                                                    db_mapping.column, e
-                                                );
-                                            }
-                                        }
-                                    }
-                                    _ => {
-                                        warn!("Unknown table: {}", db_mapping.table);
-                                        return Ok(());
-                                    }
-                                }
-
-                                Ok(())
-                            })
-                        })?;
-
-                        // After processing all field remappings, generate necessary id fields if needed for PK
-                        if activity
-                            .get_field(MarketplaceField::CollectionAddr)
-                            .is_none()
-                        {
-                            let collection_addr = generate_collection_addr(
-                                activity.creator_address.clone(),
-                                activity.collection_name.clone(),
-                            );
-
-                            if let Some(collection_addr) = collection_addr {
-                                activity
-                                    .set_field(MarketplaceField::CollectionAddr, collection_addr);
-                            }
-                        }
+        Ok((activities, failed_events))
+    }
 
-                        if activity.get_field(MarketplaceField::TokenAddr).is_none() {
-                            let token_addr = generate_token_addr(
-                                activity.creator_address.clone(),
-                                activity.collection_name.clone(),
-                                activity.token_name.clone(),
-                            );
+    /// Remaps a single event, returns None if the event isn't a marketplace activity.
+    /// Coin events only feed the coin movements for the marketplace events after them.
+    pub fn remap_event(
+        &self,
+        event: &EventModel,
+        txn_id: &str,
+        coins: &mut CoinMovements,
+    ) -> Result<Option<NftMarketplaceActivity>> {
+        // A coin event that can't be parsed isn't the marketplace's failure, it only leaves
+        // the coin movements without it
+        let coin_result = CoinEvent::from_event(
+            &event.type_str,
+            &event.data.to_string(),
+            event.transaction_version,
+        )
+        .unwrap_or_else(|e| {
+            warn!(
+                "Failed to parse coin event {} at version {}: {:#}",
+                event.type_str, event.transaction_version, e
+            );
+            None
+        });
+
+        if let Some(coin) = coin_result {
+            match coin {
+                CoinEvent::WithdrawEvent(inner) => {
+                    add_amount(
+                        &mut coins.legacy_withdraws,
+                        event.account_address.clone(),
+                        inner.amount,
+                    );
+                }
+                CoinEvent::CoinWithdrawEvent(inner) => {
+                    let currency = Some(inner.coin_type.clone());
+                    coins.record_withdraw(inner.get_account(), inner.amount, currency);
+                }
+                CoinEvent::FungibleAssetWithdrawEvent(inner) => {
+                    if let Some((Some(owner), metadata)) =
+                        coins.fungible_stores.get(&inner.get_store()).cloned()
+                    {
+                        coins.record_withdraw(owner, inner.amount, metadata);
+                    }
+                }
+                CoinEvent::DepositEvent(inner) => {
+                    coins
+                        .buy_seller
+                        .insert(event.transaction_version, event.account_address.clone());
+                    add_amount(
+                        &mut coins.legacy_deposits,
+                        event.account_address.clone(),
+                        inner.amount,
+                    );
+                }
+                CoinEvent::CoinDepositEvent(inner) => {
+                    coins.record_deposit(inner.get_account(), inner.amount);
+                }
+                CoinEvent::FungibleAssetDepositEvent(inner) => {
+                    if let Some((Some(owner), _)) =
+                        coins.fungible_stores.get(&inner.get_store()).cloned()
+                    {
+                        coins.record_deposit(owner, inner.amount);
+                    }
+                }
+            }
+        }
 
-                            if let Some(token_addr) = token_addr {
-                                activity.set_field(MarketplaceField::TokenAddr, token_addr);
-                            }
-                        }
+        let event_type_str = event.event_type.to_string();
 
-                        // Handle collection_offer_id separately since it's specific to collection offers
-                        let is_collection_bid = activity
-                            .get_bid_type()
-                            .map_or(false, |bid_type| bid_type.as_str() == "collection");
-                        let is_offer_id_exists = activity
-                            .get_field(MarketplaceField::CollectionOfferId)
-                            .is_some();
-                        if is_collection_bid && !is_offer_id_exists {
-                            activity.offer_id = generate_collection_offer_id(
-                                activity.creator_address.clone(),
-                                activity.buyer.clone(),
-                            );
-                        }
+        // Handle nft activity event
+        let remappings = match self.field_remappings.get(&event.event_type) {
+            Some(remappings) => remappings,
+            None => return Ok(None),
+        };
 
-                        // Handle bluemove marketplace, where buy event doesn't have price and seller
-                        if activity.standard_event_type == MarketplaceEventType::Buy {
-                            if let Some(buyer) = activity.buyer.as_ref() {
-                                if let Some(price) = buy_prices.remove(buyer) {
-                                    activity.price = price.to_i64().unwrap_or_default();
-                                }
-                            }
+        let event_type = match self
+            .marketplace_event_type_mapping
+            .get(&event_type_str)
+            .and_then(|rules| rules.resolve(&event.data))
+        {
+            Some(event_type) => event_type,
+            None => return Ok(None),
+        };
+
+        let mut activity = NftMarketplaceActivity {
+            marketplace: Some(self.marketplace_name.clone()),
+            txn_id: txn_id.to_string(),
+            txn_version: event.transaction_version,
+            index: event.event_index,
+            contract_address: Some(self.marketplace_contract_id.clone()),
+            block_timestamp: event.block_timestamp,
+            block_height: event.transaction_block_height,
+            raw_event_type: event.event_type.to_string(),
+            json_data: serde_json::to_value(event)?,
+            standard_event_type: event_type.clone(),
+            ..Default::default()
+        };
+
+        remappings.iter().try_for_each(|(json_path, db_mappings)| {
+            // Extract value, continue on error instead of failing
+            let extracted_value = match json_path.extract_from(&event.data) {
+                Ok(value) => Some(value),
+                Err(e) => {
+                    debug!("Failed to extract value for path {}: {}", json_path.raw, e);
+                    activity.missing_paths.push(json_path.raw.clone());
+                    None
+                }
+            };
+
+            db_mappings.iter().try_for_each(|db_mapping| {
+                let value = match db_mapping.resolve(extracted_value.as_ref(), &event.data) {
+                    Some(value) => value,
+                    None => {
+                        debug!(
+                            "Skipping empty value for path {} for column {}",
+                            json_path.raw, db_mapping.column
+                        );
+                        return Ok::<(), anyhow::Error>(());
+                    }
+                };
 
-                            if activity.seller.is_none() {
-                                let txn_version = activity.txn_version;
-                                if let Some(seller) = buy_seller.remove(&txn_version).as_ref() {
-                                    activity.seller = Some(seller.clone());
-                                }
+                match TableType::from_str(db_mapping.table.as_str()) {
+                    Some(TableType::Activities) => {
+                        match MarketplaceField::from_str(db_mapping.column.as_str()) {
+                            Ok(field) => {
+                                activity.set_field(field, value);
+                            }
+                            Err(e) => {
+                                warn!("Skipping invalid field {}: {}", db_mapping.column, e);
                             }
                         }
-
-                        activities.push(activity);
                     }
+                    Some(_) => {
+                        activity.set_table_field(&db_mapping.table, &db_mapping.column, value);
+                    }
+                    None => {
+                        warn!("Unknown table: {}", db_mapping.table);
+                        return Ok(());
+                    }
+                }
+
+                Ok(())
+            })
+        })?;
+
+        // After processing all field remappings, generate necessary id fields if needed for PK
+        if activity
+            .get_field(MarketplaceField::CollectionAddr)
+            .is_none()
+        {
+            let collection_addr = generate_collection_addr(
+                activity.creator_address.clone(),
+                activity.collection_name.clone(),
+            );
+
+            if let Some(collection_addr) = collection_addr {
+                activity.set_field(MarketplaceField::CollectionAddr, collection_addr);
+            }
+        }
+
+        if activity.get_field(MarketplaceField::TokenAddr).is_none() {
+            let token_addr = generate_token_addr(
+                activity.creator_address.clone(),
+                activity.collection_name.clone(),
+                activity.token_name.clone(),
+            );
+
+            if let Some(token_addr) = token_addr {
+                activity.set_field(MarketplaceField::TokenAddr, token_addr);
+            }
+        }
+
+        // Handle collection_offer_id separately since it's specific to collection offers
+        let is_collection_bid = activity
+            .get_bid_type()
+            .map_or(false, |bid_type| bid_type.as_str() == "collection");
+        let is_offer_id_exists = activity
+            .get_field(MarketplaceField::CollectionOfferId)
+            .is_some();
+        if is_collection_bid && !is_offer_id_exists {
+            activity.offer_id = generate_collection_offer_id(
+                activity.creator_address.clone(),
+                activity.buyer.clone(),
+            );
+        }
+
+        // Handle bluemove marketplace, where buy event doesn't have price and seller
+        if activity.standard_event_type == MarketplaceEventType::Buy {
+            if let Some(buyer) = activity.buyer.as_ref() {
+                if let Some(price) = coins.take_withdrawn(buyer) {
+                    activity.price = price.to_i64().unwrap_or_default();
+                }
+            }
+
+            if activity.seller.is_none() {
+                let txn_version = activity.txn_version;
+                if let Some(seller) = coins.buy_seller.remove(&txn_version).as_ref() {
+                    activity.seller = Some(seller.clone());
                 }
             }
         }
 
-        Ok(activities)
+        // Events that don't map their currency are paid in what the buyer withdrew
+        if activity.currency.is_none() {
+            if let Some(buyer) = activity.buyer.as_ref() {
+                activity.currency = coins.currencies.get(buyer).cloned();
+            }
+        }
+
+        if activity.is_sale() {
+            coins.apply_sale_deposits(&mut activity, &self.fee_account);
+        }
+
+        Ok(Some(activity))
     }
 
-    fn get_events(&self, transaction: Arc<Transaction>) -> Result<Vec<EventModel>> {
-        let txn_version = transaction.version as i64;
-        let block_height = transaction.block_height as i64;
-        let txn_data = match transaction.txn_data.as_ref() {
-            Some(data) => data,
+    fn get_events<'a>(&self, transaction: &'a Transaction) -> &'a [Event] {
+        match transaction.txn_data.as_ref() {
+            Some(TxnData::User(tx_inner)) => tx_inner.events.as_slice(),
+            Some(_) => &[],
             None => {
-                debug!("No transaction data found for version {}", txn_version);
-                return Ok(vec![]);
+                debug!(
+                    "No transaction data found for version {}",
+                    transaction.version
+                );
+                &[]
             }
-        };
-        let txn_ts =
-            parse_timestamp(transaction.timestamp.as_ref().unwrap(), txn_version).naive_utc();
-        let default = vec![];
-        let raw_events = match txn_data {
-            TxnData::User(tx_inner) => tx_inner.events.as_slice(),
-            _ => &default,
-        };
-        EventModel::from_events(raw_events, txn_version, block_height, txn_ts)
+        }
     }
 }
 
+fn add_amount(amounts: &mut AHashMap<String, BigDecimal>, account: String, amount: BigDecimal) {
+    amounts
+        .entry(account)
+        .and_modify(|existing| {
+            *existing += &amount;
+        })
+        .or_insert(amount);
+}
+
 fn generate_token_addr(
     creator_address: Option<String>,
     collection_name: Option<String>,
@@ -320,3 +551,96 @@ fn generate_collection_offer_id(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::marketplace::CollectionModel, workers::steps::marketplace::royalties::apply_royalty,
+    };
+    use serde_json::json;
+
+    const BUYER: &str = "0xb";
+    const SELLER: &str = "0x5";
+    const FEE_ACCOUNT: &str = "0xfee";
+    const CREATOR: &str = "0xc";
+
+    fn coin_event(type_str: &str, account: &str, data: serde_json::Value) -> EventModel {
+        EventModel {
+            sequence_number: 0,
+            creation_number: 0,
+            account_address: standardize_address(account),
+            transaction_version: 1,
+            transaction_block_height: 1,
+            event_type: type_str.try_into().unwrap(),
+            type_str: type_str.to_string(),
+            data,
+            event_index: 0,
+            block_timestamp: Default::default(),
+        }
+    }
+
+    /// A coin movement during the event migration, emitted as a module and a legacy event
+    fn coin_events(kind: &str, account: &str, amount: i64) -> Vec<EventModel> {
+        let coin_type = "0x1::aptos_coin::AptosCoin";
+        vec![
+            coin_event(
+                &format!("0x1::coin::Coin{kind}"),
+                "0x0",
+                json!({ "coin_type": coin_type, "account": account, "amount": amount.to_string() }),
+            ),
+            coin_event(
+                &format!("0x1::coin::{kind}Event"),
+                account,
+                json!({ "amount": amount.to_string() }),
+            ),
+        ]
+    }
+
+    #[test]
+    fn sale_counts_module_and_legacy_coin_events_once() {
+        let remapper = EventRemapper::new(&NFTMarketplaceConfig {
+            contract_address: "0xaa".to_string(),
+            fee_account: Some(FEE_ACCOUNT.to_string()),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let events = [
+            coin_events("Withdraw", BUYER, 1000),
+            coin_events("Deposit", SELLER, 900),
+            coin_events("Deposit", FEE_ACCOUNT, 25),
+            coin_events("Deposit", CREATOR, 75),
+        ];
+
+        let mut coins = CoinMovements::default();
+        for event in events.iter().flatten() {
+            let activity = remapper.remap_event(event, "0x1", &mut coins).unwrap();
+            assert!(activity.is_none());
+        }
+
+        let mut activity = NftMarketplaceActivity {
+            standard_event_type: MarketplaceEventType::Buy,
+            buyer: Some(standardize_address(BUYER)),
+            seller: Some(SELLER.to_string()),
+            collection_addr: Some("0xc0".to_string()),
+            ..Default::default()
+        };
+
+        let price = coins.take_withdrawn(&standardize_address(BUYER));
+        assert_eq!(price, Some(BigDecimal::from(1000)));
+        activity.price = 1000;
+
+        coins.apply_sale_deposits(&mut activity, &standardize_address(FEE_ACCOUNT));
+        assert_eq!(activity.seller_proceeds, Some(900));
+        assert_eq!(activity.marketplace_fee, Some(25));
+        assert_eq!(activity.royalty_deposits, vec![75]);
+
+        let royalties = HashMap::from([(
+            activity.get_collection_id().unwrap(),
+            BigDecimal::from_str("7.5").unwrap(),
+        )]);
+        apply_royalty(&mut activity, &royalties);
+        assert_eq!(activity.royalty_amount, Some(75));
+    }
+}
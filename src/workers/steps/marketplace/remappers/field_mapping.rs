@@ -0,0 +1,271 @@
+use anyhow::Context;
+use bigdecimal::BigDecimal;
+use serde_json::Value;
+use std::str::FromStr;
+
+use crate::{
+    config::marketplace_config::{DbColumn, ValueDecoding},
+    models::marketplace::NFT_MARKETPLACE_ACTIVITIES_TABLE_NAME,
+    workers::steps::marketplace::{HashableJsonPath, remappers::predicate::Predicate},
+};
+
+/// A `DbColumn` with its json paths compiled, ready to resolve values from raw data
+#[derive(Debug, Clone)]
+pub struct FieldMapping {
+    pub table: String,
+    pub column: String,
+    default: Option<String>,
+    decimals: Option<i64>,
+    concat: Vec<HashableJsonPath>,
+    separator: String,
+    decode: Option<ValueDecoding>,
+    condition: Option<Predicate>,
+}
+
+impl FieldMapping {
+    pub fn new(db_column: &DbColumn) -> anyhow::Result<Self> {
+        let concat = db_column
+            .concat
+            .iter()
+            .map(|path| HashableJsonPath::new(path))
+            .collect::<anyhow::Result<Vec<_>>>()
+            .context("Invalid concat path")?;
+
+        let condition = db_column.when.as_ref().map(Predicate::new).transpose()?;
+
+        Ok(Self {
+            table: db_column.table.clone(),
+            column: db_column.column.clone(),
+            default: db_column.default.clone(),
+            decimals: db_column.decimals,
+            concat,
+            separator: db_column.separator.clone().unwrap_or_default(),
+            decode: db_column.decode,
+            condition,
+        })
+    }
+
+    /// Key of the mapping in the resource updates, activity columns are kept unqualified
+    pub fn resource_key(&self) -> String {
+        if self.table == NFT_MARKETPLACE_ACTIVITIES_TABLE_NAME {
+            self.column.clone()
+        } else {
+            format!("{}.{}", self.table, self.column)
+        }
+    }
+
+    /// Applies the configured transforms to the value extracted from the mapped path.
+    /// Returns None when the condition doesn't match or no value could be resolved.
+    pub fn resolve(&self, value: Option<&Value>, data: &Value) -> Option<String> {
+        if let Some(condition) = self.condition.as_ref() {
+            if !condition.matches(data) {
+                return None;
+            }
+        }
+
+        let mut parts = Vec::new();
+        if let Some(value) = value.and_then(|value| self.decode(value)) {
+            parts.push(value);
+        }
+
+        for path in self.concat.iter() {
+            if let Some(value) = path.extract_from(data).ok().and_then(|v| self.decode(&v)) {
+                parts.push(value);
+            }
+        }
+
+        let value = parts
+            .into_iter()
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join(&self.separator);
+
+        let value = match self.decimals {
+            Some(decimals) if !value.is_empty() => scale(&value, decimals)?,
+            _ => value,
+        };
+
+        if value.is_empty() {
+            return self.default.clone();
+        }
+
+        Some(value)
+    }
+
+    fn decode(&self, value: &Value) -> Option<String> {
+        match self.decode {
+            Some(ValueDecoding::Utf8) => decode_utf8(value),
+            Some(ValueDecoding::HexNumber) => decode_hex_number(value),
+            None => stringify(value),
+        }
+    }
+}
+
+pub(super) fn stringify(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+fn decode_utf8(value: &Value) -> Option<String> {
+    let bytes = match value {
+        Value::String(s) => hex::decode(s.trim_start_matches("0x")).ok()?,
+        Value::Array(items) => items
+            .iter()
+            .map(|item| item.as_u64().and_then(|n| u8::try_from(n).ok()))
+            .collect::<Option<Vec<_>>>()?,
+        _ => return None,
+    };
+
+    String::from_utf8(bytes).ok()
+}
+
+fn decode_hex_number(value: &Value) -> Option<String> {
+    let hex = value.as_str()?.trim_start_matches("0x");
+
+    u128::from_str_radix(hex, 16).ok().map(|n| n.to_string())
+}
+
+fn scale(value: &str, decimals: i64) -> Option<String> {
+    let value = BigDecimal::from_str(value).ok()? * BigDecimal::new(1.into(), -decimals);
+
+    if value.is_integer() {
+        Some(value.with_scale(0).to_string())
+    } else {
+        Some(value.normalized().to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn mapping(yaml: &str) -> FieldMapping {
+        let db_column: DbColumn = serde_yaml::from_str(yaml).unwrap();
+        FieldMapping::new(&db_column).unwrap()
+    }
+
+    fn resolve(yaml: &str, data: Value) -> Option<String> {
+        let value = data.get("value").cloned();
+        mapping(yaml).resolve(value.as_ref(), &data)
+    }
+
+    #[test]
+    fn scale_shifts_the_decimal_point() {
+        assert_eq!(scale("1.5", 2), Some("150".to_string()));
+        assert_eq!(scale("1.5", 8), Some("150000000".to_string()));
+        assert_eq!(scale("150", -2), Some("1.5".to_string()));
+        assert_eq!(scale("100", 0), Some("100".to_string()));
+    }
+
+    #[test]
+    fn scale_keeps_the_fraction_instead_of_rounding() {
+        assert_eq!(scale("1.23456", 2), Some("123.456".to_string()));
+        assert_eq!(scale("1", -3), Some("0.001".to_string()));
+        assert_eq!(scale("1.20", 1), Some("12".to_string()));
+    }
+
+    #[test]
+    fn scale_handles_negative_and_large_values() {
+        assert_eq!(scale("-1.5", 2), Some("-150".to_string()));
+        assert_eq!(scale("-150", -2), Some("-1.5".to_string()));
+
+        // Beyond u128 and i64, the value is kept exact rather than wrapped
+        assert_eq!(
+            scale("340282366920938463463374607431768211456", 2),
+            Some("34028236692093846346337460743176821145600".to_string())
+        );
+    }
+
+    #[test]
+    fn scale_rejects_non_numbers() {
+        assert_eq!(scale("abc", 2), None);
+        assert_eq!(scale("", 2), None);
+    }
+
+    #[test]
+    fn resolve_scales_numbers_and_strings() {
+        let yaml = "{ table: t, column: price, decimals: 8 }";
+        assert_eq!(
+            resolve(yaml, json!({ "value": 1.5 })),
+            Some("150000000".to_string())
+        );
+        assert_eq!(
+            resolve(yaml, json!({ "value": "2" })),
+            Some("200000000".to_string())
+        );
+        assert_eq!(resolve(yaml, json!({ "value": "n/a" })), None);
+    }
+
+    #[test]
+    fn resolve_falls_back_to_the_default() {
+        let yaml = "{ table: t, column: price, default: '0' }";
+        assert_eq!(resolve(yaml, json!({})), Some("0".to_string()));
+        assert_eq!(resolve(yaml, json!({ "value": "" })), Some("0".to_string()));
+        assert_eq!(
+            resolve(yaml, json!({ "value": "7" })),
+            Some("7".to_string())
+        );
+    }
+
+    #[test]
+    fn resolve_concatenates_the_present_parts() {
+        let yaml = "{ table: t, column: token_addr, concat: ['$.name'], separator: '::' }";
+        assert_eq!(
+            resolve(yaml, json!({ "value": "0x1", "name": "token" })),
+            Some("0x1::token".to_string())
+        );
+        assert_eq!(
+            resolve(yaml, json!({ "value": "0x1" })),
+            Some("0x1".to_string())
+        );
+        assert_eq!(
+            resolve(yaml, json!({ "name": "token" })),
+            Some("token".to_string())
+        );
+    }
+
+    #[test]
+    fn resolve_decodes_utf8() {
+        let yaml = "{ table: t, column: token_name, decode: utf8 }";
+        assert_eq!(
+            resolve(yaml, json!({ "value": "0x6869" })),
+            Some("hi".to_string())
+        );
+        assert_eq!(
+            resolve(yaml, json!({ "value": [104, 105] })),
+            Some("hi".to_string())
+        );
+        assert_eq!(resolve(yaml, json!({ "value": [104, 256] })), None);
+        assert_eq!(resolve(yaml, json!({ "value": "0xff" })), None);
+    }
+
+    #[test]
+    fn resolve_decodes_hex_numbers() {
+        let yaml = "{ table: t, column: price, decode: hex_number }";
+        assert_eq!(
+            resolve(yaml, json!({ "value": "0xff" })),
+            Some("255".to_string())
+        );
+        assert_eq!(resolve(yaml, json!({ "value": 255 })), None);
+
+        // One digit past u128
+        let overflow = format!("0x1{}", "0".repeat(32));
+        assert_eq!(resolve(yaml, json!({ "value": overflow })), None);
+    }
+
+    #[test]
+    fn resolve_only_maps_when_the_condition_holds() {
+        let yaml = "{ table: t, column: price, when: { path: '$.kind', equals: sale } }";
+        assert_eq!(
+            resolve(yaml, json!({ "value": "5", "kind": "sale" })),
+            Some("5".to_string())
+        );
+        assert_eq!(resolve(yaml, json!({ "value": "5", "kind": "bid" })), None);
+        assert_eq!(resolve(yaml, json!({ "value": "5" })), None);
+    }
+}
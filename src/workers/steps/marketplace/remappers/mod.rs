@@ -1,18 +1,47 @@
 pub mod event_remapper;
+pub mod field_mapping;
+pub mod predicate;
 pub mod resource_remapper;
 
-use crate::models::marketplace::NFT_MARKETPLACE_ACTIVITIES_TABLE_NAME;
+use std::str::FromStr;
+
+use crate::models::marketplace::{
+    AUCTIONS_TABLE_NAME, AuctionField, BIDS_TABLE_NAME, BidField, COLLECTIONS_TABLE_NAME,
+    CollectionField, LISTINGS_TABLE_NAME, ListingField, MarketplaceField,
+    NFT_MARKETPLACE_ACTIVITIES_TABLE_NAME, NFTS_TABLE_NAME, NftField,
+};
 
 #[derive(Debug, PartialEq, Eq)]
-enum TableType {
+pub(crate) enum TableType {
     Activities,
+    Bids,
+    Listings,
+    Auctions,
+    Collections,
+    Nfts,
 }
 
 impl TableType {
-    fn from_str(table_name: &str) -> Option<Self> {
+    pub(crate) fn from_str(table_name: &str) -> Option<Self> {
         match table_name {
             NFT_MARKETPLACE_ACTIVITIES_TABLE_NAME => Some(TableType::Activities),
+            BIDS_TABLE_NAME => Some(TableType::Bids),
+            LISTINGS_TABLE_NAME => Some(TableType::Listings),
+            AUCTIONS_TABLE_NAME => Some(TableType::Auctions),
+            COLLECTIONS_TABLE_NAME => Some(TableType::Collections),
+            NFTS_TABLE_NAME => Some(TableType::Nfts),
             _ => None,
         }
     }
+
+    pub(crate) fn is_valid_column(&self, column: &str) -> bool {
+        match self {
+            TableType::Activities => MarketplaceField::from_str(column).is_ok(),
+            TableType::Bids => BidField::from_str(column).is_ok(),
+            TableType::Listings => ListingField::from_str(column).is_ok(),
+            TableType::Auctions => AuctionField::from_str(column).is_ok(),
+            TableType::Collections => CollectionField::from_str(column).is_ok(),
+            TableType::Nfts => NftField::from_str(column).is_ok(),
+        }
+    }
 }
@@ -0,0 +1,100 @@
+use anyhow::Context;
+use serde_json::Value;
+
+use crate::{
+    config::marketplace_config::{EventModelMapping, FieldCondition, MarketplaceEventType},
+    workers::steps::marketplace::{HashableJsonPath, remappers::field_mapping::stringify},
+};
+
+/// A `FieldCondition` with its json path compiled
+#[derive(Debug, Clone)]
+pub struct Predicate {
+    path: HashableJsonPath,
+    equals: Option<String>,
+    one_of: Option<Vec<String>>,
+    exists: Option<bool>,
+}
+
+impl Predicate {
+    pub fn new(condition: &FieldCondition) -> anyhow::Result<Self> {
+        if condition.equals.is_none() && condition.r#in.is_none() && condition.exists.is_none() {
+            anyhow::bail!(
+                "Condition on {} needs one of equals, in or exists",
+                condition.path
+            );
+        }
+
+        Ok(Self {
+            path: HashableJsonPath::new(&condition.path).context("Invalid condition path")?,
+            equals: condition.equals.clone(),
+            one_of: condition.r#in.clone(),
+            exists: condition.exists,
+        })
+    }
+
+    pub fn matches(&self, data: &Value) -> bool {
+        let value = self
+            .path
+            .extract_from(data)
+            .ok()
+            .filter(|value| !value.is_null());
+
+        if let Some(exists) = self.exists {
+            if value.is_some() != exists {
+                return false;
+            }
+        }
+
+        let value = value.as_ref().and_then(stringify);
+
+        if let Some(expected) = self.equals.as_ref() {
+            if value.as_ref() != Some(expected) {
+                return false;
+            }
+        }
+
+        if let Some(one_of) = self.one_of.as_ref() {
+            if !value.is_some_and(|value| one_of.contains(&value)) {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+/// Compiled `EventModelMapping`, resolves the marketplace event type of a raw event
+#[derive(Debug, Clone)]
+pub struct EventModelRules {
+    rules: Vec<(Vec<Predicate>, MarketplaceEventType)>,
+}
+
+impl EventModelRules {
+    pub fn new(mapping: &EventModelMapping) -> anyhow::Result<Self> {
+        let rules = match mapping {
+            EventModelMapping::Single(event_type) => vec![(Vec::new(), event_type.clone())],
+            EventModelMapping::Conditional(rules) => rules
+                .iter()
+                .map(|rule| {
+                    let predicates = rule
+                        .when
+                        .iter()
+                        .map(Predicate::new)
+                        .collect::<anyhow::Result<Vec<_>>>()?;
+
+                    Ok((predicates, rule.r#type.clone()))
+                })
+                .collect::<anyhow::Result<Vec<_>>>()?,
+        };
+
+        Ok(Self { rules })
+    }
+
+    /// Returns the event type of the first matching rule, None if the event should be ignored
+    pub fn resolve(&self, data: &Value) -> Option<MarketplaceEventType> {
+        self.rules
+            .iter()
+            .find(|(predicates, _)| predicates.iter().all(|predicate| predicate.matches(data)))
+            .map(|(_, event_type)| event_type.clone())
+    }
+}
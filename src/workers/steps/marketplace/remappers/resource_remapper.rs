@@ -1,6 +1,6 @@
 use crate::{
     config::marketplace_config::{NFTMarketplaceConfig, ResourceFieldRemappings},
-    workers::steps::marketplace::{HashableJsonPath, extract_string},
+    workers::steps::marketplace::{HashableJsonPath, remappers::field_mapping::FieldMapping},
 };
 use anyhow::Result;
 use aptos_indexer_processor_sdk::{
@@ -27,7 +27,7 @@ impl ResourceMapper {
                 let json_path = HashableJsonPath::new(json_path)?;
                 let db_mappings = db_mappings
                     .iter()
-                    .map(|db_mapping| Ok(db_mapping.clone()))
+                    .map(FieldMapping::new)
                     .collect::<anyhow::Result<Vec<_>>>()?;
 
                 db_mappings_for_resource.insert(json_path, db_mappings);
@@ -71,12 +71,16 @@ impl ResourceMapper {
                 let resource_type = &write_resource.type_str;
                 if let Some(remappings) = self.field_remappings.get(resource_type) {
                     remappings.iter().try_for_each(|(json_path, db_mappings)| {
+                        let extracted_value = json_path.extract_from(&data).ok();
+
                         db_mappings.iter().try_for_each(|db_mapping| {
-                            let value = extract_string(json_path, &data).unwrap_or_default();
+                            let value = db_mapping
+                                .resolve(extracted_value.as_ref(), &data)
+                                .unwrap_or_default();
                             resource_updates
                                 .entry(resource_address.clone()) // Use resource address as key
                                 .or_default()
-                                .insert(db_mapping.column.clone(), value);
+                                .insert(db_mapping.resource_key(), value);
                             anyhow::Ok(())
                         })
                     })?;
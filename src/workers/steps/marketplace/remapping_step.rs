@@ -1,6 +1,6 @@
 use crate::{
     config::marketplace_config::NFTMarketplaceConfig,
-    models::marketplace::NftMarketplaceActivity,
+    models::{db::failed_event::DbFailedEvent, marketplace::NftMarketplaceActivity},
     utils::string_utils::capitalize,
     workers::steps::marketplace::remappers::{
         event_remapper::EventRemapper, resource_remapper::ResourceMapper,
@@ -8,6 +8,7 @@ use crate::{
 };
 use anyhow::Result;
 use aptos_indexer_processor_sdk::{
+    aptos_indexer_transaction_stream::utils::time::parse_timestamp,
     aptos_protos::transaction::v1::Transaction,
     traits::{AsyncRunType, AsyncStep, NamedStep, Processable},
     types::transaction_context::TransactionContext,
@@ -15,12 +16,14 @@ use aptos_indexer_processor_sdk::{
 };
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 use std::{collections::HashMap, sync::Arc};
+use tracing::warn;
 
 pub struct RemappingStep
 where
     Self: Sized + Send + 'static,
 {
     name: String,
+    contract_address: String,
     event_remapper: Arc<EventRemapper>,
     resource_remapper: Arc<ResourceMapper>,
 }
@@ -32,49 +35,57 @@ impl RemappingStep {
 
         Ok(Self {
             name: config.name,
+            contract_address: config.contract_address,
             event_remapper,
             resource_remapper,
         })
     }
-}
 
-#[async_trait::async_trait]
-impl Processable for RemappingStep {
-    type Input = Vec<Transaction>;
-    type Output = (
+    /// Remaps the events and resources of a batch of transactions, along with the events
+    /// that failed to remap
+    pub fn remap(
+        &self,
+        transactions: &[Transaction],
+    ) -> anyhow::Result<(
         Vec<NftMarketplaceActivity>,
         HashMap<String, HashMap<String, String>>,
-    );
-    type RunType = AsyncRunType;
-
-    async fn process(
-        &mut self,
-        transactions: TransactionContext<Vec<Transaction>>,
-    ) -> Result<Option<TransactionContext<Self::Output>>, ProcessorError> {
+        Vec<DbFailedEvent>,
+    )> {
         let results = transactions
-            .data
             .par_iter()
             .map(|transaction| {
                 let event_remapper = self.event_remapper.clone();
                 let resource_remapper = self.resource_remapper.clone();
 
-                let activities = event_remapper.remap_events(transaction.clone())?;
-                let resource_updates = resource_remapper.remap_resources(transaction.clone())?;
+                let (activities, mut failed_events) =
+                    event_remapper.remap_events(transaction.clone())?;
+                let resource_updates = match resource_remapper.remap_resources(transaction.clone())
+                {
+                    Ok(resource_updates) => resource_updates,
+                    Err(e) => {
+                        warn!(
+                            "Failed to remap resources at version {}: {:#}",
+                            transaction.version, e
+                        );
+
+                        failed_events.push(self.failed_transaction(transaction, &e));
+                        HashMap::new()
+                    }
+                };
 
-                Ok((activities, resource_updates))
+                Ok((activities, resource_updates, failed_events))
             })
-            .collect::<anyhow::Result<Vec<_>>>()
-            .map_err(|e| ProcessorError::ProcessError {
-                message: format!("{e:#}"),
-            })?;
+            .collect::<anyhow::Result<Vec<_>>>()?;
 
-        let (mut all_activities, mut all_resource_updates) = (
+        let (mut all_activities, mut all_resource_updates, mut all_failed_events) = (
             Vec::new(),
             HashMap::<String, HashMap<String, String>>::new(),
+            Vec::new(),
         );
 
-        for (activities, resource_updates) in results {
+        for (activities, resource_updates, failed_events) in results {
             all_activities.extend(activities);
+            all_failed_events.extend(failed_events);
 
             // Merge resource_updates by key
             resource_updates.into_iter().for_each(|(key, value_map)| {
@@ -85,8 +96,59 @@ impl Processable for RemappingStep {
             });
         }
 
+        Ok((all_activities, all_resource_updates, all_failed_events))
+    }
+
+    fn failed_transaction(
+        &self,
+        transaction: &Transaction,
+        error: &anyhow::Error,
+    ) -> DbFailedEvent {
+        let tx_id = transaction
+            .info
+            .as_ref()
+            .map(|info| format!("0x{}", hex::encode(&info.hash)))
+            .unwrap_or_default();
+        let block_time = transaction
+            .timestamp
+            .as_ref()
+            .map(|timestamp| parse_timestamp(timestamp, transaction.version as i64).naive_utc())
+            .unwrap_or_default();
+
+        DbFailedEvent::from_transaction(
+            &self.name,
+            &self.contract_address,
+            &tx_id,
+            transaction.version as i64,
+            error,
+            block_time,
+            transaction.block_height as i64,
+        )
+    }
+}
+
+#[async_trait::async_trait]
+impl Processable for RemappingStep {
+    type Input = Vec<Transaction>;
+    type Output = (
+        Vec<NftMarketplaceActivity>,
+        HashMap<String, HashMap<String, String>>,
+        Vec<DbFailedEvent>,
+    );
+    type RunType = AsyncRunType;
+
+    async fn process(
+        &mut self,
+        transactions: TransactionContext<Vec<Transaction>>,
+    ) -> Result<Option<TransactionContext<Self::Output>>, ProcessorError> {
+        let data = self
+            .remap(&transactions.data)
+            .map_err(|e| ProcessorError::ProcessError {
+                message: format!("{e:#}"),
+            })?;
+
         Ok(Some(TransactionContext {
-            data: (all_activities, all_resource_updates),
+            data,
             metadata: transactions.metadata,
         }))
     }
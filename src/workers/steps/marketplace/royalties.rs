@@ -0,0 +1,64 @@
+use std::collections::{HashMap, HashSet};
+
+use bigdecimal::{BigDecimal, ToPrimitive, Zero};
+use uuid::Uuid;
+
+use crate::{
+    database::{IDatabase, collections::ICollections},
+    models::marketplace::{CollectionModel, NftMarketplaceActivity},
+};
+
+/// Royalty percentages of the collections of the sales with a price breakdown
+pub async fn fetch_collection_royalties<TDb: IDatabase>(
+    db: &TDb,
+    activities: &[NftMarketplaceActivity],
+) -> HashMap<Uuid, BigDecimal> {
+    let collection_ids = activities
+        .iter()
+        .filter(|activity| activity.seller_proceeds.is_some())
+        .filter_map(|activity| activity.get_collection_id())
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect::<Vec<_>>();
+
+    match db.collections().fetch_royalties(&collection_ids).await {
+        Ok(royalties) => royalties,
+        Err(e) => {
+            tracing::warn!("Failed to fetch collection royalties: {e:#}");
+            HashMap::new()
+        }
+    }
+}
+
+/// Picks the royalty of a sale among its other deposits, the one closest to what the
+/// collection royalty expects within 1% of it. A sale with no such deposit paid no royalty.
+/// When the collection royalty isn't known, what's left of the price after the seller
+/// proceeds and the marketplace fee is taken as the royalty.
+pub fn apply_royalty(activity: &mut NftMarketplaceActivity, royalties: &HashMap<Uuid, BigDecimal>) {
+    let deposits = std::mem::take(&mut activity.royalty_deposits);
+    let (Some(proceeds), Some(fee)) = (activity.seller_proceeds, activity.marketplace_fee) else {
+        return;
+    };
+
+    let royalty = activity
+        .get_collection_id()
+        .and_then(|collection_id| royalties.get(&collection_id));
+
+    activity.royalty_amount = match royalty {
+        Some(royalty) if royalty.is_zero() => Some(0),
+        Some(royalty) => {
+            let expected = (BigDecimal::from(activity.price) * royalty / 100)
+                .to_i64()
+                .unwrap_or_default();
+            let tolerance = (expected / 100).max(1).unsigned_abs();
+
+            let paid = deposits
+                .into_iter()
+                .filter(|amount| amount.abs_diff(expected) <= tolerance)
+                .min_by_key(|amount| amount.abs_diff(expected));
+
+            Some(paid.unwrap_or_default())
+        }
+        None => Some(activity.price - proceeds - fee).filter(|remainder| *remainder >= 0),
+    };
+}
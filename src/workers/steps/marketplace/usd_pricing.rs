@@ -0,0 +1,177 @@
+use std::collections::{HashMap, HashSet};
+
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::{
+    config::{
+        PriceFallback, PriceHistoryConfig,
+        currency_config::{CurrencyConfig, resolve_currency},
+    },
+    database::{
+        IDatabase, activities::IActivities, collections::ICollections, token_prices::ITokenPrices,
+    },
+    models::marketplace::NftMarketplaceActivity,
+    workers::steps::marketplace::reduction_step::apply_currency,
+};
+
+/// Number of activities re-priced per update
+const RECOMPUTE_CHUNK_SIZE: i64 = 10_000;
+
+/// Looks up the usd price of currencies at a block time from the price history. Prices are
+/// stored per minute, so lookups are memoized per currency and minute.
+pub struct UsdPricer<'a, TDb: IDatabase> {
+    db: &'a TDb,
+    config: &'a PriceHistoryConfig,
+    prices: HashMap<(String, i64), Option<BigDecimal>>,
+    latest: HashMap<String, Option<BigDecimal>>,
+}
+
+impl<'a, TDb: IDatabase> UsdPricer<'a, TDb> {
+    pub fn new(db: &'a TDb, config: &'a PriceHistoryConfig) -> Self {
+        Self {
+            db,
+            config,
+            prices: HashMap::new(),
+            latest: HashMap::new(),
+        }
+    }
+
+    /// Uses already known latest prices, e.g. from the cache, for the `latest` fallback
+    pub fn with_latest_prices(mut self, latest: HashMap<String, BigDecimal>) -> Self {
+        self.latest = latest
+            .into_iter()
+            .map(|(address, price)| (address, Some(price)))
+            .collect();
+        self
+    }
+
+    /// The price nearest to `at` if it's within `max_gap_secs`, otherwise the configured
+    /// fallback
+    pub async fn price_at(&mut self, address: &str, at: DateTime<Utc>) -> Option<BigDecimal> {
+        let key = (address.to_string(), at.timestamp().div_euclid(60));
+        if let Some(price) = self.prices.get(&key) {
+            return price.clone();
+        }
+
+        let nearest = match self
+            .db
+            .token_prices()
+            .fetch_token_price_at(address, at)
+            .await
+        {
+            Ok(nearest) => nearest,
+            Err(e) => {
+                tracing::warn!("Failed to fetch the price of {address} at {at}: {e:#}");
+                None
+            }
+        };
+
+        let price = match nearest {
+            Some((price, gap)) if gap <= self.config.max_gap_secs => Some(price),
+            nearest => match self.config.fallback {
+                PriceFallback::Nearest => nearest.map(|(price, _)| price),
+                PriceFallback::Latest => self.latest_price(address).await,
+                PriceFallback::None => None,
+            },
+        };
+
+        self.prices.insert(key, price.clone());
+
+        price
+    }
+
+    async fn latest_price(&mut self, address: &str) -> Option<BigDecimal> {
+        if let Some(price) = self.latest.get(address) {
+            return price.clone();
+        }
+
+        let price = self.db.token_prices().fetch_token_price(address).await.ok();
+        self.latest.insert(address.to_string(), price.clone());
+
+        price
+    }
+
+    /// Forgets the memoized prices, the latest prices are kept
+    pub fn clear(&mut self) {
+        self.prices.clear();
+    }
+}
+
+/// Resolves the currency of an activity and prices it in USD at its block time. Activities
+/// paid in a currency that isn't configured get no USD price.
+pub async fn apply_usd_price<TDb: IDatabase>(
+    activity: &mut NftMarketplaceActivity,
+    currencies: &[CurrencyConfig],
+    pricer: &mut UsdPricer<'_, TDb>,
+) {
+    activity.usd_price = match apply_currency(activity, currencies) {
+        Some(currency) => pricer
+            .price_at(&currency.address, activity.block_timestamp.and_utc())
+            .await
+            .map(|usd| currency.to_usd(activity.price, &usd)),
+        None => None,
+    };
+}
+
+#[derive(Debug, Default)]
+pub struct RecomputeStats {
+    pub activities: usize,
+    pub collections: usize,
+}
+
+/// Recomputes the usd price of the activities with a block time in
+/// `[start_time, end_time]` from the price history, then the usd volume of their
+/// collections. Meant to be run after the price history is backfilled.
+pub async fn recompute_usd_prices<TDb: IDatabase>(
+    db: &TDb,
+    currencies: &[CurrencyConfig],
+    config: &PriceHistoryConfig,
+    start_time: Option<DateTime<Utc>>,
+    end_time: Option<DateTime<Utc>>,
+) -> anyhow::Result<RecomputeStats> {
+    let mut pricer = UsdPricer::new(db, config);
+    let mut stats = RecomputeStats::default();
+    let mut collection_ids = HashSet::new();
+    let mut after = None;
+
+    loop {
+        let activities = db
+            .activities()
+            .fetch_activity_prices(start_time, end_time, after, RECOMPUTE_CHUNK_SIZE)
+            .await?;
+
+        let Some(last) = activities.last() else {
+            break;
+        };
+        after = Some((last.block_time, last.id));
+
+        let mut usd_prices: Vec<(Uuid, Option<BigDecimal>)> = Vec::with_capacity(activities.len());
+        for activity in activities.iter() {
+            let usd_price = match resolve_currency(currencies, activity.currency.as_deref()) {
+                Some(currency) => pricer
+                    .price_at(&currency.address, activity.block_time)
+                    .await
+                    .map(|usd| currency.to_usd(activity.price, &usd)),
+                None => None,
+            };
+
+            usd_prices.push((activity.id, usd_price));
+        }
+
+        stats.activities += usd_prices.len();
+        collection_ids.extend(db.activities().update_usd_prices(usd_prices).await?);
+
+        // Chunks are ordered by block time, earlier minutes won't be looked up again
+        pricer.clear();
+    }
+
+    let collection_ids = collection_ids.into_iter().collect::<Vec<_>>();
+    for chunk in collection_ids.chunks(RECOMPUTE_CHUNK_SIZE as usize) {
+        db.collections().refresh_volume_usd(chunk).await?;
+    }
+    stats.collections = collection_ids.len();
+
+    Ok(stats)
+}
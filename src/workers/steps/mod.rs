@@ -1,3 +1,4 @@
 pub mod marketplace;
 pub mod processor_status_saver_step;
 pub mod token;
+pub mod transaction_source_step;
@@ -1,12 +1,17 @@
 use std::sync::Arc;
 
 use crate::{
+    config::marketplace_config::MarketplaceEventType,
     database::{
-        IDatabase, activities::IActivities, collections::ICollections, nfts::INfts,
-        wallets::IWallets,
+        IDatabase, activities::IActivities, collections::ICollections, listings::IListings,
+        nft_balances::INftBalances, nft_ownerships::INftOwnerships, nfts::INfts, wallets::IWallets,
+    },
+    models::db::{
+        activity::DbActivity, collection::DbCollection, listing::DbListingInvalidation, nft::DbNft,
+        nft_balance_change::DbNftBalanceChange, nft_ownership::DbNftOwnership,
     },
-    models::db::{activity::DbActivity, collection::DbCollection, nft::DbNft},
 };
+use ahash::AHashSet;
 use aptos_indexer_processor_sdk::{
     traits::{AsyncStep, NamedStep, Processable, async_step::AsyncRunType},
     types::transaction_context::TransactionContext,
@@ -28,7 +33,14 @@ impl<TDb: IDatabase> Processable for DBWritingStep<TDb>
 where
     TDb: Send + Sync,
 {
-    type Input = (Vec<DbActivity>, Vec<DbCollection>, Vec<DbNft>, Vec<String>);
+    type Input = (
+        Vec<DbActivity>,
+        Vec<DbCollection>,
+        Vec<DbNft>,
+        Vec<String>,
+        Vec<DbNftOwnership>,
+        Vec<DbNftBalanceChange>,
+    );
     type Output = ();
     type RunType = AsyncRunType;
 
@@ -36,7 +48,30 @@ where
         &mut self,
         input: TransactionContext<Self::Input>,
     ) -> Result<Option<TransactionContext<()>>, ProcessorError> {
-        let (activities, collections, nfts, wallets) = input.data;
+        let (activities, collections, nfts, wallets, ownerships, balance_changes) = input.data;
+        let invalidations = DbListingInvalidation::from_activities(&activities);
+
+        // Burns close the last ownership of their nft
+        let mut ownership_nft_ids = activities
+            .iter()
+            .filter(|activity| activity.tx_type == Some(MarketplaceEventType::Burn.to_string()))
+            .filter_map(|activity| activity.nft_id)
+            .chain(ownerships.iter().map(|ownership| ownership.nft_id))
+            .collect::<Vec<_>>();
+        ownership_nft_ids.sort();
+        ownership_nft_ids.dedup();
+
+        // Tokens moved by v1 deposits and withdrawals keep their editions, the others hold a
+        // single one that follows the ownership
+        let edition_nft_ids = balance_changes
+            .iter()
+            .map(|change| change.nft_id)
+            .collect::<AHashSet<_>>();
+        let single_edition_nft_ids = ownership_nft_ids
+            .iter()
+            .filter(|nft_id| !edition_nft_ids.contains(*nft_id))
+            .copied()
+            .collect::<Vec<_>>();
 
         let mut tx =
             self.db
@@ -63,6 +98,14 @@ where
                 message: format!("{e:#}"),
             })?;
 
+        self.db
+            .listings()
+            .tx_invalidate_listings(&mut tx, invalidations)
+            .await
+            .map_err(|e| ProcessorError::ProcessError {
+                message: format!("{e:#}"),
+            })?;
+
         self.db
             .collections()
             .tx_insert_collections(&mut tx, collections)
@@ -79,6 +122,38 @@ where
                 message: format!("{e:#}"),
             })?;
 
+        self.db
+            .nft_ownerships()
+            .tx_insert_ownerships(&mut tx, ownerships)
+            .await
+            .map_err(|e| ProcessorError::ProcessError {
+                message: format!("{e:#}"),
+            })?;
+
+        self.db
+            .nft_ownerships()
+            .tx_close_ownerships(&mut tx, &ownership_nft_ids)
+            .await
+            .map_err(|e| ProcessorError::ProcessError {
+                message: format!("{e:#}"),
+            })?;
+
+        self.db
+            .nft_balances()
+            .tx_apply_balances(&mut tx, balance_changes)
+            .await
+            .map_err(|e| ProcessorError::ProcessError {
+                message: format!("{e:#}"),
+            })?;
+
+        self.db
+            .nft_balances()
+            .tx_sync_balances(&mut tx, &single_edition_nft_ids)
+            .await
+            .map_err(|e| ProcessorError::ProcessError {
+                message: format!("{e:#}"),
+            })?;
+
         tx.commit()
             .await
             .map_err(|e| ProcessorError::ProcessError {
@@ -1,12 +1,15 @@
 use std::sync::Arc;
 
 use crate::database::IDatabase;
-use crate::utils::generate_nft_id;
+use crate::utils::{generate_collection_id, generate_nft_id};
 use crate::{
     config::marketplace_config::MarketplaceEventType,
     models::{
-        EventModel,
-        db::{activity::DbActivity, collection::DbCollection, nft::DbNft},
+        EventModel, TX_INDEX_MULTIPLIER,
+        db::{
+            activity::DbActivity, collection::DbCollection, nft::DbNft,
+            nft_balance_change::DbNftBalanceChange, nft_ownership::DbNftOwnership,
+        },
         resources::{FromWriteResource, V2TokenResource},
     },
     utils::{
@@ -36,6 +39,8 @@ where
     current_nfts: AHashMap<Uuid, DbNft>,
     current_burn_nfts: AHashMap<Uuid, DbNft>,
     current_activities: AHashMap<i64, DbActivity>,
+    current_ownerships: AHashMap<Uuid, Vec<DbNftOwnership>>,
+    current_balance_changes: Vec<DbNftBalanceChange>,
 }
 
 impl<TDb: IDatabase> TokenExtractor<TDb> {
@@ -47,6 +52,8 @@ impl<TDb: IDatabase> TokenExtractor<TDb> {
             current_nfts: AHashMap::new(),
             current_burn_nfts: AHashMap::new(),
             current_activities: AHashMap::new(),
+            current_ownerships: AHashMap::new(),
+            current_balance_changes: Vec::new(),
         }
     }
 }
@@ -57,7 +64,14 @@ where
     TDb: Send + Sync,
 {
     type Input = Vec<Transaction>;
-    type Output = (Vec<DbActivity>, Vec<DbCollection>, Vec<DbNft>, Vec<String>);
+    type Output = (
+        Vec<DbActivity>,
+        Vec<DbCollection>,
+        Vec<DbNft>,
+        Vec<String>,
+        Vec<DbNftOwnership>,
+        Vec<DbNftBalanceChange>,
+    );
     type RunType = AsyncRunType;
 
     async fn process(
@@ -144,7 +158,8 @@ where
                 }
 
                 let mut token_owner: AHashMap<Uuid, Option<String>> = AHashMap::new();
-                for event in events.iter() {
+                let mut deposits: Vec<DbNftOwnership> = Vec::new();
+                for (event_index, event) in events.iter().enumerate() {
                     let token_event = TokenEvent::from_event(
                         event.type_str.as_ref(),
                         event.data.as_str(),
@@ -157,21 +172,63 @@ where
                             .as_ref()
                             .map(|key| standardize_address(&key.account_address));
 
-                        match token_event {
+                        // Module events carry the account in their data, handle events in their key
+                        let (token_id, account_address, amount) = match &token_event {
                             TokenEvent::DepositTokenEvent(inner) => {
-                                token_owner.insert(
-                                    generate_nft_id(inner.id.token_data_id.to_addr().as_str()),
-                                    account_address,
-                                );
+                                (&inner.id, account_address, inner.amount.to_i64())
                             }
                             TokenEvent::TokenDeposit(inner) => {
-                                token_owner.insert(
-                                    generate_nft_id(inner.id.token_data_id.to_addr().as_str()),
-                                    account_address,
-                                );
+                                (&inner.id, Some(inner.get_account()), inner.amount.to_i64())
                             }
-                            _ => {}
+                            TokenEvent::WithdrawTokenEvent(inner) => (
+                                &inner.id,
+                                account_address,
+                                inner.amount.to_i64().map(|a| -a),
+                            ),
+                            TokenEvent::TokenWithdraw(inner) => (
+                                &inner.id,
+                                Some(inner.get_account()),
+                                inner.amount.to_i64().map(|a| -a),
+                            ),
+                            _ => continue,
+                        };
+
+                        let token_data_id = &token_id.token_data_id;
+                        let nft_id = generate_nft_id(token_data_id.to_addr().as_str());
+                        let collection_id =
+                            generate_collection_id(token_data_id.get_collection_addr().as_str());
+
+                        if let Some((owner, amount)) = account_address.as_ref().zip(amount) {
+                            self.current_balance_changes.push(DbNftBalanceChange {
+                                nft_id,
+                                owner: owner.clone(),
+                                tx_index: txn_version * TX_INDEX_MULTIPLIER + event_index as i64,
+                                collection_id: Some(collection_id),
+                                amount,
+                            });
                         }
+
+                        // Withdrawals only lower the balance, the next deposit opens the ownership
+                        if !matches!(
+                            token_event,
+                            TokenEvent::DepositTokenEvent(_) | TokenEvent::TokenDeposit(_)
+                        ) {
+                            continue;
+                        }
+
+                        if let Some(owner) = account_address.as_ref() {
+                            deposits.push(DbNftOwnership {
+                                nft_id,
+                                tx_index: txn_version * TX_INDEX_MULTIPLIER + event_index as i64,
+                                collection_id: Some(collection_id),
+                                owner: owner.clone(),
+                                tx_id: txn_id.clone(),
+                                activity_id: None,
+                                block_time: txn_ts,
+                            });
+                        }
+
+                        token_owner.insert(nft_id, account_address);
                     }
                 }
 
@@ -262,6 +319,19 @@ where
                     }
                 }
 
+                // The mint of a v1 token already opens its first ownership
+                for deposit in deposits {
+                    let minted = self
+                        .current_ownerships
+                        .get(&deposit.nft_id)
+                        .and_then(|ownerships| ownerships.last())
+                        .is_some_and(|last| last.get_version() == txn_version);
+
+                    if !minted {
+                        self.push_ownership(deposit);
+                    }
+                }
+
                 for wsc in txn_info.changes.iter() {
                     let (collection_result, nft_result, nft_collection_result) =
                         match wsc.change.as_ref().unwrap() {
@@ -347,7 +417,16 @@ impl<TDb: IDatabase> NamedStep for TokenExtractor<TDb> {
 }
 
 impl<TDb: IDatabase> TokenExtractor<TDb> {
-    fn drain(&mut self) -> (Vec<DbActivity>, Vec<DbCollection>, Vec<DbNft>, Vec<String>) {
+    fn drain(
+        &mut self,
+    ) -> (
+        Vec<DbActivity>,
+        Vec<DbCollection>,
+        Vec<DbNft>,
+        Vec<String>,
+        Vec<DbNftOwnership>,
+        Vec<DbNftBalanceChange>,
+    ) {
         let mut nfts = self
             .current_nfts
             .drain()
@@ -367,9 +446,23 @@ impl<TDb: IDatabase> TokenExtractor<TDb> {
             self.current_collections.drain().map(|(_, v)| v).collect(),
             nfts,
             self.current_wallets.drain().map(|v| v).collect(),
+            self.current_ownerships
+                .drain()
+                .flat_map(|(_, v)| v)
+                .collect(),
+            self.current_balance_changes.drain(..).collect(),
         )
     }
 
+    // Consecutive ownerships of the same owner are merged when closing them, against the
+    // stored ones too
+    fn push_ownership(&mut self, ownership: DbNftOwnership) {
+        self.current_ownerships
+            .entry(ownership.nft_id)
+            .or_default()
+            .push(ownership);
+    }
+
     fn merge_update(
         &mut self,
         activity: DbActivity,
@@ -418,6 +511,10 @@ impl<TDb: IDatabase> TokenExtractor<TDb> {
             self.current_wallets.insert(address.to_string());
         }
 
+        if let Some(ownership) = DbNftOwnership::from_activity(&activity) {
+            self.push_ownership(ownership);
+        }
+
         self.current_activities.insert(activity.tx_index, activity);
     }
 }
@@ -0,0 +1,389 @@
+use std::{collections::VecDeque, fs, path::PathBuf, time::Duration};
+
+use anyhow::Context;
+use aptos_indexer_processor_sdk::{
+    aptos_indexer_transaction_stream::{
+        BooleanTransactionFilter, Filterable, TransactionStreamConfig,
+    },
+    aptos_protos::{indexer::v1::TransactionsResponse, transaction::v1::Transaction},
+    common_steps::TransactionStreamStep,
+    traits::{NamedStep, PollableAsyncRunType, PollableAsyncStep, Processable},
+    types::transaction_context::{TransactionContext, TransactionMetadata},
+    utils::errors::ProcessorError,
+};
+use prost::Message;
+
+use crate::config::{ReplayConfig, ReplayFormat, StreamConfig};
+
+/// First step of the processor pipelines, either the live gRPC stream or a replay of
+/// transactions recorded on disk.
+pub enum TransactionSourceStep
+where
+    Self: Sized + Send + 'static,
+{
+    Stream(TransactionStreamStep),
+    Replay(TransactionReplayStep),
+}
+
+impl TransactionSourceStep {
+    pub async fn new(
+        stream_config: &StreamConfig,
+        starting_version: u64,
+        request_ending_version: Option<u64>,
+        filter: BooleanTransactionFilter,
+        request_name_header: &str,
+    ) -> anyhow::Result<Self> {
+        if let Some(replay_config) = stream_config.replay.as_ref() {
+            let step = TransactionReplayStep::new(
+                replay_config,
+                starting_version,
+                request_ending_version,
+                filter,
+            )?;
+
+            return Ok(Self::Replay(step));
+        }
+
+        let step = TransactionStreamStep::new(TransactionStreamConfig {
+            indexer_grpc_data_service_address: url::Url::parse(&stream_config.indexer_grpc)?,
+            starting_version: Some(starting_version),
+            request_ending_version,
+            auth_token: stream_config.auth_token.clone(),
+            request_name_header: request_name_header.to_string(),
+            additional_headers: Default::default(),
+            indexer_grpc_http2_ping_interval_secs: 30,
+            indexer_grpc_http2_ping_timeout_secs: 10,
+            indexer_grpc_reconnection_timeout_secs: 5,
+            indexer_grpc_response_item_timeout_secs: 60,
+            indexer_grpc_reconnection_max_retries: 5,
+            transaction_filter: Some(filter),
+        })
+        .await?;
+
+        Ok(Self::Stream(step))
+    }
+}
+
+#[async_trait::async_trait]
+impl Processable for TransactionSourceStep {
+    type Input = ();
+    type Output = Vec<Transaction>;
+    type RunType = PollableAsyncRunType;
+
+    async fn init(&mut self) {
+        if let Self::Stream(step) = self {
+            step.init().await;
+        }
+    }
+
+    async fn process(
+        &mut self,
+        _item: TransactionContext<()>,
+    ) -> Result<Option<TransactionContext<Vec<Transaction>>>, ProcessorError> {
+        Ok(None)
+    }
+}
+
+#[async_trait::async_trait]
+impl PollableAsyncStep for TransactionSourceStep {
+    fn poll_interval(&self) -> Duration {
+        match self {
+            Self::Stream(step) => step.poll_interval(),
+            Self::Replay(_) => Duration::from_secs(0),
+        }
+    }
+
+    async fn poll(
+        &mut self,
+    ) -> Result<Option<Vec<TransactionContext<Vec<Transaction>>>>, ProcessorError> {
+        match self {
+            Self::Stream(step) => step.poll().await,
+            Self::Replay(step) => step
+                .next_batch()
+                .map(|batch| batch.map(|batch| vec![batch]))
+                .map_err(|e| ProcessorError::ProcessError {
+                    message: format!("{e:#}"),
+                }),
+        }
+    }
+
+    async fn should_continue_polling(&mut self) -> bool {
+        match self {
+            Self::Stream(step) => step.should_continue_polling().await,
+            Self::Replay(step) => step.has_next(),
+        }
+    }
+}
+
+impl NamedStep for TransactionSourceStep {
+    fn name(&self) -> String {
+        match self {
+            Self::Stream(step) => step.name(),
+            Self::Replay(_) => "TransactionReplayStep".to_string(),
+        }
+    }
+}
+
+/// Reads recorded transaction batches from disk, one batch per file.
+///
+/// Only the transactions inside the version window that match the filter of the live
+/// stream are emitted, so a replay yields what the stream would have.
+pub struct TransactionReplayStep {
+    files: VecDeque<PathBuf>,
+    format: ReplayFormat,
+    starting_version: u64,
+    ending_version: Option<u64>,
+    filter: BooleanTransactionFilter,
+}
+
+impl TransactionReplayStep {
+    pub fn new(
+        config: &ReplayConfig,
+        starting_version: u64,
+        ending_version: Option<u64>,
+        filter: BooleanTransactionFilter,
+    ) -> anyhow::Result<Self> {
+        let path = PathBuf::from(&config.path);
+
+        let mut files = if path.is_dir() {
+            fs::read_dir(&path)
+                .with_context(|| format!("Failed to read replay directory {}", config.path))?
+                .map(|entry| entry.map(|e| e.path()))
+                .collect::<Result<Vec<_>, _>>()
+                .with_context(|| format!("Failed to list replay directory {}", config.path))?
+                .into_iter()
+                .filter(|path| path.is_file())
+                .collect::<Vec<_>>()
+        } else {
+            vec![path]
+        };
+
+        files.sort();
+
+        Ok(Self {
+            files: files.into(),
+            format: config.format,
+            starting_version,
+            ending_version,
+            filter,
+        })
+    }
+
+    pub fn has_next(&self) -> bool {
+        !self.files.is_empty()
+    }
+
+    /// Returns the next batch that overlaps the version window, or None once every file is read
+    pub fn next_batch(&mut self) -> anyhow::Result<Option<TransactionContext<Vec<Transaction>>>> {
+        while let Some(file) = self.files.pop_front() {
+            let bytes = fs::read(&file)
+                .with_context(|| format!("Failed to read replay file {}", file.display()))?;
+            let transactions = self
+                .decode(&bytes)
+                .with_context(|| format!("Failed to decode replay file {}", file.display()))?;
+
+            let (first, last) = match (transactions.first(), transactions.last()) {
+                (Some(first), Some(last)) => (first, last),
+                _ => continue,
+            };
+
+            let start_version = first.version.max(self.starting_version);
+            let end_version = match self.ending_version {
+                Some(ending_version) => last.version.min(ending_version),
+                None => last.version,
+            };
+
+            if start_version > end_version {
+                continue;
+            }
+
+            let metadata = TransactionMetadata {
+                start_version,
+                end_version,
+                start_transaction_timestamp: first.timestamp.clone(),
+                end_transaction_timestamp: last.timestamp.clone(),
+                total_size_in_bytes: bytes.len() as u64,
+            };
+
+            let data = transactions
+                .into_iter()
+                .filter(|txn| txn.version >= start_version && txn.version <= end_version)
+                .filter(|txn| self.filter.matches(txn))
+                .collect::<Vec<_>>();
+
+            return Ok(Some(TransactionContext { data, metadata }));
+        }
+
+        Ok(None)
+    }
+
+    fn decode(&self, bytes: &[u8]) -> anyhow::Result<Vec<Transaction>> {
+        let transactions = match self.format {
+            ReplayFormat::Json => serde_json::from_slice::<Vec<Transaction>>(bytes)?,
+            ReplayFormat::Binary => TransactionsResponse::decode(bytes)?.transactions,
+        };
+
+        Ok(transactions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use aptos_indexer_processor_sdk::{
+        aptos_indexer_transaction_stream::{
+            EventFilterBuilder, MoveStructTagFilterBuilder, TransactionRootFilterBuilder,
+        },
+        aptos_protos::transaction::v1::{
+            Event, MoveStructTag, MoveType, MoveTypes, TransactionInfo, UserTransaction, move_type,
+            transaction::TransactionType, transaction::TxnData,
+        },
+    };
+
+    use super::*;
+
+    const CONTRACT: &str = "0x2c7bccf7b31baf770fdbcc768d9e9cb3d87805e255355df5db32ac9a669010a2";
+    const OTHER: &str = "0x0000000000000000000000000000000000000000000000000000000000000001";
+
+    fn txn(version: u64, txn_type: TransactionType, success: bool, address: &str) -> Transaction {
+        let event = Event {
+            r#type: Some(MoveType {
+                r#type: MoveTypes::Struct as i32,
+                content: Some(move_type::Content::Struct(MoveStructTag {
+                    address: address.to_string(),
+                    module: "marketplace".to_string(),
+                    name: "ListingFilledEvent".to_string(),
+                    generic_type_params: vec![],
+                })),
+            }),
+            type_str: format!("{address}::marketplace::ListingFilledEvent"),
+            ..Default::default()
+        };
+
+        Transaction {
+            version,
+            r#type: txn_type as i32,
+            info: Some(TransactionInfo {
+                success,
+                ..Default::default()
+            }),
+            txn_data: Some(TxnData::User(UserTransaction {
+                events: vec![event],
+                ..Default::default()
+            })),
+            ..Default::default()
+        }
+    }
+
+    fn contract_filter() -> BooleanTransactionFilter {
+        let struct_filter = MoveStructTagFilterBuilder::default()
+            .address(CONTRACT)
+            .build()
+            .unwrap();
+        let event_filter = EventFilterBuilder::default()
+            .struct_type(struct_filter)
+            .build()
+            .unwrap();
+        let tx_filter = TransactionRootFilterBuilder::default()
+            .success(true)
+            .txn_type(TransactionType::User)
+            .build()
+            .unwrap();
+
+        BooleanTransactionFilter::from(tx_filter).and(event_filter)
+    }
+
+    /// Writes each batch to its own file in a fresh directory, named so that they sort in
+    /// reverse of the order they are given in
+    fn write_batches(name: &str, format: ReplayFormat, batches: Vec<Vec<Transaction>>) -> String {
+        let dir = std::env::temp_dir().join(format!("replay-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        let count = batches.len();
+        for (i, transactions) in batches.into_iter().enumerate() {
+            let bytes = match format {
+                ReplayFormat::Json => serde_json::to_vec(&transactions).unwrap(),
+                ReplayFormat::Binary => TransactionsResponse {
+                    transactions,
+                    ..Default::default()
+                }
+                .encode_to_vec(),
+            };
+            fs::write(dir.join(format!("{:03}.batch", count - i)), bytes).unwrap();
+        }
+
+        dir.to_string_lossy().to_string()
+    }
+
+    fn replay_versions(step: &mut TransactionReplayStep) -> Vec<(u64, u64, Vec<u64>)> {
+        let mut batches = Vec::new();
+        while let Some(batch) = step.next_batch().unwrap() {
+            batches.push((
+                batch.metadata.start_version,
+                batch.metadata.end_version,
+                batch.data.iter().map(|txn| txn.version).collect(),
+            ));
+        }
+
+        batches
+    }
+
+    #[test]
+    fn replay_applies_the_stream_filter() {
+        let path = write_batches(
+            "filter",
+            ReplayFormat::Json,
+            vec![vec![
+                txn(1, TransactionType::User, true, CONTRACT),
+                txn(2, TransactionType::User, true, OTHER),
+                txn(3, TransactionType::User, false, CONTRACT),
+                txn(4, TransactionType::Genesis, true, CONTRACT),
+                txn(5, TransactionType::User, true, CONTRACT),
+            ]],
+        );
+        let config = ReplayConfig {
+            path,
+            format: ReplayFormat::Json,
+        };
+
+        let mut step = TransactionReplayStep::new(&config, 0, None, contract_filter()).unwrap();
+
+        assert_eq!(replay_versions(&mut step), vec![(1, 5, vec![1, 5])]);
+        assert!(!step.has_next());
+    }
+
+    #[test]
+    fn replay_is_deterministic_across_runs() {
+        let path = write_batches(
+            "deterministic",
+            ReplayFormat::Binary,
+            vec![
+                vec![
+                    txn(20, TransactionType::User, true, CONTRACT),
+                    txn(21, TransactionType::User, true, CONTRACT),
+                ],
+                vec![
+                    txn(10, TransactionType::User, true, CONTRACT),
+                    txn(11, TransactionType::User, true, OTHER),
+                    txn(12, TransactionType::User, true, CONTRACT),
+                ],
+            ],
+        );
+        let config = ReplayConfig {
+            path,
+            format: ReplayFormat::Binary,
+        };
+
+        let runs = (0..2)
+            .map(|_| {
+                let mut step =
+                    TransactionReplayStep::new(&config, 11, Some(20), contract_filter()).unwrap();
+                replay_versions(&mut step)
+            })
+            .collect::<Vec<_>>();
+
+        // Files are read in name order and each batch is cut to the version window
+        assert_eq!(runs[0], vec![(11, 12, vec![12]), (20, 20, vec![20])]);
+        assert_eq!(runs[0], runs[1]);
+    }
+}
@@ -3,13 +3,11 @@ use std::sync::Arc;
 use aptos_indexer_processor_sdk::{
     aptos_indexer_transaction_stream::{
         BooleanTransactionFilter, EventFilterBuilder, MoveStructTagFilterBuilder,
-        TransactionRootFilterBuilder, TransactionStreamConfig,
+        TransactionRootFilterBuilder,
     },
     aptos_protos::transaction::v1::transaction::TransactionType,
     builder::ProcessorBuilder,
-    common_steps::{
-        DEFAULT_UPDATE_PROCESSOR_STATUS_SECS, TransactionStreamStep, VersionTrackerStep,
-    },
+    common_steps::{DEFAULT_UPDATE_PROCESSOR_STATUS_SECS, VersionTrackerStep},
     traits::IntoRunnableStep,
 };
 
@@ -20,6 +18,7 @@ use crate::{
     workers::steps::{
         processor_status_saver_step::DbProcessorStatusSaver,
         token::{db_writing_step::DBWritingStep, extractor_step::TokenExtractor},
+        transaction_source_step::TransactionSourceStep,
     },
 };
 
@@ -77,22 +76,13 @@ where
 
         let filter = BooleanTransactionFilter::from(tx_filter).and(token_filter);
 
-        let transaction_stream = TransactionStreamStep::new(TransactionStreamConfig {
-            indexer_grpc_data_service_address: url::Url::parse(
-                &self.config.stream_config.indexer_grpc,
-            )?,
-            starting_version: Some(starting_version as u64),
+        let transaction_stream = TransactionSourceStep::new(
+            &self.config.stream_config,
+            starting_version as u64,
             request_ending_version,
-            auth_token: self.config.stream_config.auth_token.clone(),
-            request_name_header: "marketplace-event-processor".to_string(),
-            additional_headers: Default::default(),
-            indexer_grpc_http2_ping_interval_secs: 30,
-            indexer_grpc_http2_ping_timeout_secs: 10,
-            indexer_grpc_reconnection_timeout_secs: 5,
-            indexer_grpc_response_item_timeout_secs: 60,
-            indexer_grpc_reconnection_max_retries: 5,
-            transaction_filter: Some(filter),
-        })
+            filter,
+            "token-event-processor",
+        )
         .await?;
 
         let remapping_step = TokenExtractor::new(Arc::clone(&self.db));